@@ -0,0 +1,355 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Runs a ROM without a display, for batch jobs and CI.
+
+mod output;
+mod script;
+
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use sturdygb_core::cartridge::CartridgeOptions;
+use sturdygb_core::gb::Gb;
+use sturdygb_core::link::SerialCapture;
+use sturdygb_core::power_on::RamPattern;
+use sturdygb_core::prelude::GbInstance;
+use sturdygb_core::symbols::Symbols;
+use sturdygb_core::trace::{TraceOptions, Tracer};
+
+use crate::script::InputScript;
+
+#[derive(Parser, Debug)]
+#[command(name = "sturdygb-cli", about = "Runs a Game Boy ROM headlessly")]
+struct Cli {
+    #[arg(value_name = "ROM")]
+    rom: PathBuf,
+
+    /// Boot ROM to run before the cartridge
+    #[arg(long, value_name = "FILE")]
+    boot_rom: Option<PathBuf>,
+
+    /// Load ROMs with a bad header checksum
+    #[arg(long)]
+    ignore_header_checksum: bool,
+
+    /// Fill the power-on RAM from this seed and run the RTC on emulated time, so runs with
+    /// the same input are reproducible
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Stop after this many frames
+    #[arg(long, required_unless_present = "timeout")]
+    frames: Option<u64>,
+
+    /// Stop after this many seconds of wall-clock time
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<f64>,
+
+    /// Stop when the CPU reaches this address, in hex
+    #[arg(long, value_name = "ADDRESS", value_parser = parse_address)]
+    until_pc: Option<u16>,
+
+    /// Stop once the serial output contains this string
+    #[arg(long, value_name = "TEXT")]
+    until_serial: Option<String>,
+
+    /// Joypad input script, each line holds a frame number and the buttons held from then on
+    #[arg(long, value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Save the last frame as a PNG
+    #[arg(long, value_name = "FILE")]
+    screenshot: Option<PathBuf>,
+
+    /// Also save every Nth frame, numbered, next to the final screenshot
+    #[arg(long, value_name = "N", requires = "screenshot")]
+    screenshot_every: Option<u64>,
+
+    /// Save the bytes sent through the serial port, `-` prints them
+    #[arg(long, value_name = "FILE")]
+    serial: Option<PathBuf>,
+
+    /// Record the audio output as a WAV file
+    #[arg(long, value_name = "FILE")]
+    wav: Option<PathBuf>,
+
+    #[arg(long, value_name = "HZ", default_value_t = 44100)]
+    sample_rate: u32,
+
+    /// Dump the 64 KiB address space when the run ends
+    #[arg(long, value_name = "FILE")]
+    dump: Option<PathBuf>,
+
+    /// RGBDS style symbol file used by the trace, defaults to the ROM's `.sym` file
+    #[arg(long, value_name = "FILE")]
+    symbols: Option<PathBuf>,
+
+    /// Log every executed instruction in the Gameboy Doctor format
+    #[arg(long, value_name = "FILE")]
+    trace: Option<PathBuf>,
+
+    /// Add the disassembly, cycle count, LY, bank numbers and labels to the trace
+    #[arg(long, requires = "trace")]
+    trace_details: bool,
+
+    /// Only trace instructions inside this address range, in hex
+    #[arg(long, value_name = "START-END", requires = "trace", value_parser = parse_range)]
+    trace_pc: Option<RangeInclusive<u16>>,
+
+    /// Only trace instructions running from this ROM bank
+    #[arg(long, value_name = "BANK", requires = "trace")]
+    trace_bank: Option<usize>,
+
+    /// Start tracing at this frame
+    #[arg(long, value_name = "FRAME", requires = "trace", default_value_t = 0)]
+    trace_from: u64,
+
+    /// Stop tracing at this frame
+    #[arg(long, value_name = "FRAME", requires = "trace")]
+    trace_until: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum StopReason {
+    Frames,
+    Timeout,
+    Breakpoint,
+    Serial,
+    Locked,
+}
+
+// Only the conditions asked for count as a successful run
+fn succeeded(cli: &Cli, reason: StopReason) -> bool {
+    let waiting = cli.until_pc.is_some() || cli.until_serial.is_some();
+    match reason {
+        StopReason::Breakpoint | StopReason::Serial => true,
+        StopReason::Frames | StopReason::Timeout => !waiting,
+        StopReason::Locked => false,
+    }
+}
+
+fn parse_address(value: &str) -> Result<u16, String> {
+    let digits = value
+        .trim_start_matches("0x")
+        .trim_start_matches("0X")
+        .trim_start_matches('$');
+    u16::from_str_radix(digits, 16).map_err(|_| format!("invalid address {value}"))
+}
+
+fn parse_range(value: &str) -> Result<RangeInclusive<u16>, String> {
+    let (start, end) = value
+        .split_once('-')
+        .ok_or_else(|| format!("invalid range {value}, expected START-END"))?;
+    Ok(parse_address(start)?..=parse_address(end)?)
+}
+
+// Runs until the frame ends, or the CPU reaches the breakpoint. Frames keep going by with
+// the LCD off, so this always returns.
+fn run_frame(gb: &mut Gb, breakpoint: Option<u16>) -> bool {
+    while !gb.ppu.frame_ready && !gb.waiting_for_input() && !gb.cpu.is_locked {
+        gb.run();
+        if breakpoint == Some(gb.cpu.pc) {
+            return true;
+        }
+    }
+    gb.ppu.frame_ready = false;
+    false
+}
+
+fn run(cli: Cli) -> Result<ExitCode, String> {
+    let boot_rom = match &cli.boot_rom {
+        Some(path) => Some(
+            std::fs::read(path)
+                .map_err(|e| format!("Failed to read boot ROM {}: {e}", path.display()))?,
+        ),
+        None => None,
+    };
+    let options = CartridgeOptions {
+        ignore_header_checksum: cli.ignore_header_checksum,
+    };
+    let mut builder = GbInstance::default();
+    if let Some(seed) = cli.seed {
+        builder = builder.ram_pattern(RamPattern::Seeded(seed));
+    }
+    let mut gb = builder
+        .load_file(&cli.rom.to_string_lossy(), boot_rom, options)
+        .map_err(|e| e.to_string())?;
+    gb.print_serial = false;
+    gb.set_sample_rate(cli.sample_rate);
+
+    let default_symbols = cli.rom.with_extension("sym");
+    if let Some(path) = &cli.symbols {
+        gb.symbols = Symbols::load(path)?;
+    } else if default_symbols.exists() {
+        gb.symbols = Symbols::load(&default_symbols)?;
+    }
+
+    let serial = SerialCapture::default();
+    gb.connect_link(Box::new(serial.clone()));
+
+    let mut script = match &cli.input {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            Some(InputScript::parse(&text)?)
+        }
+        None => None,
+    };
+
+    // Created up front so a bad path is reported before running anything
+    let mut tracer = match &cli.trace {
+        Some(path) => {
+            let options = TraceOptions {
+                disassembly: cli.trace_details,
+                cycles: cli.trace_details,
+                ly: cli.trace_details,
+                banks: cli.trace_details,
+                symbols: cli.trace_details,
+                pc_range: cli.trace_pc.clone(),
+                rom_bank: cli.trace_bank,
+            };
+            Some(Tracer::create(path, options)?)
+        }
+        None => None,
+    };
+
+    let timeout = cli.timeout.map(Duration::from_secs_f64);
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    let mut frame = 0;
+    let reason = loop {
+        if cli.frames.is_some_and(|frames| frame >= frames) {
+            break StopReason::Frames;
+        }
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            break StopReason::Timeout;
+        }
+        if let Some(script) = &mut script {
+            script.apply(&mut gb, frame);
+        }
+        if frame >= cli.trace_from {
+            if let Some(tracer) = tracer.take() {
+                gb.start_trace(tracer)?;
+            }
+        }
+        if cli.trace_until == Some(frame) {
+            gb.stop_trace()?;
+        }
+
+        let breakpoint = run_frame(&mut gb, cli.until_pc);
+        // Drained every frame, the core keeps buffering samples otherwise
+        let audio = gb.get_audio_buffer();
+        if cli.wav.is_some() {
+            samples.extend(audio);
+        }
+        if breakpoint {
+            break StopReason::Breakpoint;
+        }
+        frame += 1;
+
+        if let (Some(every), Some(path)) = (cli.screenshot_every, &cli.screenshot) {
+            if frame % every == 0 {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                output::save_screenshot(
+                    &mut gb,
+                    &path.with_file_name(format!("{stem}-{frame}.png")),
+                )?;
+            }
+        }
+        if let Some(text) = &cli.until_serial {
+            if String::from_utf8_lossy(&serial.data()).contains(text.as_str()) {
+                break StopReason::Serial;
+            }
+        }
+        if gb.cpu.is_locked {
+            break StopReason::Locked;
+        }
+    };
+
+    eprintln!(
+        "Stopped after {frame} frames ({reason:?}) at PC={:04X}",
+        gb.cpu.pc
+    );
+
+    gb.stop_trace()?;
+    gb.flush_save()?;
+    if let Some(path) = &cli.screenshot {
+        output::save_screenshot(&mut gb, path)?;
+    }
+    if let Some(path) = &cli.serial {
+        output::save_serial(&serial.data(), path)?;
+    }
+    if let Some(path) = &cli.wav {
+        output::save_wav(&samples, cli.sample_rate, path)?;
+    }
+    if let Some(path) = &cli.dump {
+        output::save_memory_dump(&gb, path)?;
+    }
+
+    Ok(if succeeded(&cli, reason) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_cli(args: &[&str]) -> Cli {
+        Cli::parse_from(["sturdygb-cli", "game.gb"].iter().chain(args))
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x0150"), Ok(0x0150));
+        assert_eq!(parse_address("0XC000"), Ok(0xC000));
+        assert_eq!(parse_address("$ff80"), Ok(0xFF80));
+        assert_eq!(parse_address("100"), Ok(0x0100));
+        assert!(parse_address("0x10000").is_err());
+        assert!(parse_address("start").is_err());
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("0x4000-$7FFF"), Ok(0x4000..=0x7FFF));
+        assert!(parse_range("0x4000").is_err());
+        assert!(parse_range("0x4000-end").is_err());
+    }
+
+    #[test]
+    fn test_until_options_decide_exit_code() {
+        let cli = parse_cli(&["--frames", "10"]);
+        assert!(succeeded(&cli, StopReason::Frames));
+        assert!(succeeded(&cli, StopReason::Timeout));
+        assert!(!succeeded(&cli, StopReason::Locked));
+
+        // Running out of frames means what was waited for never happened
+        for args in [["--until-pc", "0x0150"], ["--until-serial", "Passed"]] {
+            let cli = parse_cli(&[&["--frames", "10"], &args[..]].concat());
+            assert!(!succeeded(&cli, StopReason::Frames));
+            assert!(!succeeded(&cli, StopReason::Timeout));
+            assert!(!succeeded(&cli, StopReason::Locked));
+        }
+        let cli = parse_cli(&["--timeout", "1", "--until-pc", "0x0150"]);
+        assert!(succeeded(&cli, StopReason::Breakpoint));
+        let cli = parse_cli(&["--timeout", "1", "--until-serial", "Passed"]);
+        assert!(succeeded(&cli, StopReason::Serial));
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use std::io::Write;
+use std::path::Path;
+
+use sturdygb_core::cartridge::GbMode;
+use sturdygb_core::gb::Gb;
+
+// Same as the greyscale palette of the desktop frontend
+const DMG_COLORS: [[u8; 3]; 4] = [[255, 255, 255], [192, 192, 192], [96, 96, 96], [0, 0, 0]];
+
+fn rgb555_to_rgb888(color: u16) -> [u8; 3] {
+    let scale = |c: u16| -> u8 {
+        let c = (c & 0x1F) as u8;
+        (c << 3) | (c >> 2)
+    };
+    [scale(color), scale(color >> 5), scale(color >> 10)]
+}
+
+pub fn save_screenshot(gb: &mut Gb, path: &Path) -> Result<(), String> {
+    let is_cgb = gb.gb_mode == GbMode::CgbMode;
+    let mut rgb = Vec::with_capacity(160 * 144 * 3);
+    for &pixel in gb.get_screen_data().iter().flatten() {
+        if is_cgb {
+            rgb.extend_from_slice(&rgb555_to_rgb888(pixel));
+        } else {
+            rgb.extend_from_slice(&DMG_COLORS[pixel as usize & 3]);
+        }
+    }
+    image::save_buffer(path, &rgb, 160, 144, image::ColorType::Rgb8)
+        .map_err(|e| format!("Failed to save screenshot {}: {e}", path.display()))
+}
+
+/// Writes interleaved stereo samples as a 16-bit PCM WAV file.
+pub fn save_wav(samples: &[f32], sample_rate: u32, path: &Path) -> Result<(), String> {
+    let data_size = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, 2 channels
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+    wav.extend_from_slice(&4u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    std::fs::write(path, wav).map_err(|e| format!("Failed to save {}: {e}", path.display()))
+}
+
+/// Dumps the whole address space as seen by the CPU.
+pub fn save_memory_dump(gb: &Gb, path: &Path) -> Result<(), String> {
+    let memory = (0..=0xFFFF)
+        .map(|address| gb.read_byte(address))
+        .collect::<Vec<u8>>();
+    std::fs::write(path, memory).map_err(|e| format!("Failed to save {}: {e}", path.display()))
+}
+
+pub fn save_serial(data: &[u8], path: &Path) -> Result<(), String> {
+    if path == Path::new("-") {
+        let mut stdout = std::io::stdout();
+        return stdout
+            .write_all(data)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("Failed to print the serial output: {e}"));
+    }
+    std::fs::write(path, data).map_err(|e| format!("Failed to save {}: {e}", path.display()))
+}
@@ -0,0 +1,104 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use sturdygb_core::gb::Gb;
+use sturdygb_core::joypad::JoypadButton;
+
+const BUTTONS: [(&str, JoypadButton); 8] = [
+    ("a", JoypadButton::A),
+    ("b", JoypadButton::B),
+    ("left", JoypadButton::Left),
+    ("right", JoypadButton::Right),
+    ("up", JoypadButton::Up),
+    ("down", JoypadButton::Down),
+    ("start", JoypadButton::Start),
+    ("select", JoypadButton::Select),
+];
+
+/// Scripted joypad input. Each line of the file holds a frame number followed by the
+/// buttons held from that frame on, a line with no buttons releases everything:
+///
+/// ```text
+/// # Press start on the title screen
+/// 120 start
+/// 125
+/// 300 a right
+/// ```
+pub struct InputScript {
+    // Sorted by frame
+    entries: Vec<(u64, Vec<JoypadButton>)>,
+    next: usize,
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let Some(frame) = words.next() else {
+                continue;
+            };
+            let frame = frame
+                .parse::<u64>()
+                .map_err(|_| format!("Line {}: invalid frame number {frame}", number + 1))?;
+            let buttons = words
+                .map(|word| {
+                    BUTTONS
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(word))
+                        .map(|(_, button)| *button)
+                        .ok_or_else(|| format!("Line {}: unknown button {word}", number + 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.push((frame, buttons));
+        }
+        entries.sort_by_key(|(frame, _)| *frame);
+        Ok(Self { entries, next: 0 })
+    }
+
+    /// Updates the held buttons if the script has an entry for this frame.
+    pub fn apply(&mut self, gb: &mut Gb, frame: u64) {
+        while let Some((entry_frame, held)) = self.entries.get(self.next) {
+            if *entry_frame > frame {
+                break;
+            }
+            for (_, button) in BUTTONS {
+                if held.contains(&button) {
+                    gb.press_button(button);
+                } else {
+                    gb.release_button(button);
+                }
+            }
+            self.next += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_sorts_entries_and_skips_comments() {
+        let script =
+            InputScript::parse("# Title screen\n\n300 A Right # jump\n120 start\n  125\n").unwrap();
+        assert_eq!(
+            script.entries,
+            [
+                (120, vec![JoypadButton::Start]),
+                (125, vec![]),
+                (300, vec![JoypadButton::A, JoypadButton::Right]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let error = InputScript::parse("10 a\n20 jump").err().unwrap();
+        assert_eq!(error, "Line 2: unknown button jump");
+        let error = InputScript::parse("# comment\nsoon a").err().unwrap();
+        assert_eq!(error, "Line 2: invalid frame number soon");
+    }
+}
@@ -3,31 +3,129 @@
 // SPDX-License-Identifier: MIT
 
 use crate::mbcs::get_mbc;
+use crate::storage::SaveStorage;
 use std::fs;
 
-pub fn load_cartridge(filename: &str) -> Result<(Box<dyn Mbc>, GbMode), String> {
-    let rom_data =
-        fs::read(filename).map_err(|e| format!("Unable to read ROM '{filename}': {e}"))?;
-    let save_path = std::path::PathBuf::from(filename).with_extension("sav");
-    match CartridgeHeader::new(&rom_data) {
-        Ok(header) => Ok(get_mbc(rom_data, header, save_path)),
-        Err(f) => Err(f.to_string()),
+/// Why a ROM could not be turned into a cartridge.
+#[derive(Debug)]
+pub enum CartridgeError {
+    Io(std::io::Error),
+    /// The ROM is too small to hold a cartridge header.
+    TooSmall(usize),
+    /// The header checksum at 0x014D doesn't match the header contents.
+    BadHeaderChecksum {
+        expected: u8,
+        found: u8,
+    },
+    /// The cartridge type at 0x0147 is not a mapper we know about.
+    UnsupportedMapper(u8),
+    /// The battery RAM couldn't be loaded from its [`SaveStorage`].
+    Save(String),
+}
+
+impl std::fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartridgeError::Io(e) => write!(f, "Unable to read ROM: {e}"),
+            CartridgeError::TooSmall(size) => {
+                write!(f, "ROM is too small to be a GB cartridge ({size} bytes)")
+            }
+            CartridgeError::BadHeaderChecksum { expected, found } => write!(
+                f,
+                "Header checksum mismatch (expected {expected:02X}, found {found:02X})"
+            ),
+            CartridgeError::UnsupportedMapper(mbc_type) => {
+                write!(f, "Unsupported cartridge type {mbc_type:02X}")
+            }
+            CartridgeError::Save(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CartridgeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for CartridgeError {
+    fn from(e: std::io::Error) -> Self {
+        CartridgeError::Io(e)
     }
 }
 
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CartridgeOptions {
+    /// Load ROMs with a bad header checksum, homebrew often leaves it unset. Real hardware
+    /// refuses to boot those, so this is off by default.
+    pub ignore_header_checksum: bool,
+}
+
+/// Loads the ROM at `filename`, with its battery RAM from `storage`.
+pub fn load_cartridge(
+    filename: &str,
+    storage: &mut dyn SaveStorage,
+    options: CartridgeOptions,
+) -> Result<(Box<dyn Mbc>, GbMode), CartridgeError> {
+    let rom_data = fs::read(filename)?;
+    load_cartridge_from_bytes(rom_data, storage, options)
+}
+
 pub fn load_cartridge_from_bytes(
     rom_data: Vec<u8>,
-    save_path: Option<std::path::PathBuf>,
-) -> Result<(Box<dyn Mbc>, GbMode), String> {
-    let path = save_path.unwrap_or_else(|| std::path::PathBuf::new());
-    match CartridgeHeader::new(&rom_data) {
-        Ok(header) => Ok(get_mbc(rom_data, header, path)),
-        Err(f) => Err(f.to_string()),
-    }
+    storage: &mut dyn SaveStorage,
+    options: CartridgeOptions,
+) -> Result<(Box<dyn Mbc>, GbMode), CartridgeError> {
+    let header = CartridgeHeader::parse(&rom_data, options)?;
+    get_mbc(rom_data, header, storage)
 }
 
 #[allow(unused_variables)]
 pub trait Mbc {
+    /// The whole ROM, including the banks that aren't mapped.
+    fn rom(&self) -> &[u8];
+
+    /// Cartridge RAM with every bank back to back, for tools that need to see past the
+    /// banking registers.
+    fn ram(&self) -> &[u8] {
+        &[]
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut []
+    }
+
+    /// Bank currently mapped at 0x4000. MBC6 maps 8 KiB banks, this is the one at 0x4000.
+    fn rom_bank(&self) -> usize {
+        1
+    }
+
+    /// Reads `offset` inside the 16 KiB ROM `bank`, ignoring the banking registers.
+    fn peek_rom(&self, bank: usize, offset: u16) -> u8 {
+        self.rom()
+            .get(bank * 0x4000 + offset as usize)
+            .copied()
+            .unwrap_or(0xFF)
+    }
+
+    /// Reads `offset` inside the 8 KiB RAM `bank`, ignoring the banking registers and
+    /// whether the RAM is enabled.
+    fn peek_ram(&self, bank: usize, offset: u16) -> u8 {
+        self.ram()
+            .get(bank * 0x2000 + offset as usize)
+            .copied()
+            .unwrap_or(0xFF)
+    }
+
+    fn poke_ram(&mut self, bank: usize, offset: u16, value: u8) {
+        if let Some(byte) = self.ram_mut().get_mut(bank * 0x2000 + offset as usize) {
+            *byte = value;
+        }
+    }
+
     fn read_rom(&self, address: u16) -> u8;
 
     fn read_ram(&self, address: u16) -> u8 {
@@ -38,11 +136,34 @@ pub trait Mbc {
 
     fn write_ram(&mut self, address: u16, value: u8) {}
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
+    /// Whether the cartridge has anything for a `.sav` file to keep.
+    fn has_battery(&self) -> bool {
+        false
+    }
+
+    /// Contents of the `.sav` file: battery backed RAM followed by any extra cartridge
+    /// state, such as the RTC footer.
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
         None
     }
 
     fn set_battery_ram(&mut self, _data: &[u8]) {}
+
+    /// Advances hardware living on the cartridge by `cycles` base clock cycles.
+    fn tick(&mut self, cycles: u32) {}
+
+    fn set_rtc_mode(&mut self, mode: RtcMode) {}
+
+    fn set_camera_source(&mut self, source: Box<dyn CameraSource>) {}
+
+    /// Serializes the banking registers and RAM so they can be stored in a save state.
+    fn save_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 pub struct CartridgeHeader {
@@ -83,22 +204,55 @@ pub enum MBCTypes {
     },
     Mbc6,
     Mbc7,
-    Unknown,
+    PocketCamera,
+    Tama5,
+    HuC3,
+    HuC1,
+    Unknown(u8),
+}
+
+pub const CAMERA_WIDTH: usize = 128;
+pub const CAMERA_HEIGHT: usize = 112;
+
+/// Feeds the Pocket Camera sensor. `capture` fills `image` with one brightness value per
+/// pixel, row by row, 0 being black and 255 white.
+pub trait CameraSource {
+    fn capture(&mut self, image: &mut [u8; CAMERA_WIDTH * CAMERA_HEIGHT]);
+}
+
+impl<F: FnMut(&mut [u8; CAMERA_WIDTH * CAMERA_HEIGHT])> CameraSource for F {
+    fn capture(&mut self, image: &mut [u8; CAMERA_WIDTH * CAMERA_HEIGHT]) {
+        self(image)
+    }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+/// What drives the real-time clock of cartridges that have one.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum RtcMode {
+    /// Follows the host's wall clock, including the time the emulator was closed
+    #[default]
+    HostClock,
+    /// Advances with emulated cycles only, so runs are reproducible
+    Emulated,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum GbMode {
     DmgMode,
     NonCgbMode,
     CgbMode,
 }
 
-fn checksum(rom_data: &Vec<u8>) -> bool {
+fn header_checksum(rom_data: &[u8]) -> u8 {
     let mut x: u8 = 0;
     for i in 0x0134..=0x014C {
         x = x.wrapping_sub(rom_data[i]).wrapping_sub(1);
     }
-    x == rom_data[0x014D]
+    x
+}
+
+fn checksum(rom_data: &[u8]) -> bool {
+    header_checksum(rom_data) == rom_data[0x014D]
 }
 
 fn get_mbc_type(mbc_type: &u8) -> MBCTypes {
@@ -193,7 +347,22 @@ fn get_mbc_type(mbc_type: &u8) -> MBCTypes {
         },
         0x20 => MBCTypes::Mbc6,
         0x22 => MBCTypes::Mbc7,
-        _ => MBCTypes::Unknown,
+        0xFC => MBCTypes::PocketCamera,
+        0xFD => MBCTypes::Tama5,
+        0xFE => MBCTypes::HuC3,
+        0xFF => MBCTypes::HuC1,
+        _ => MBCTypes::Unknown(*mbc_type),
+    }
+}
+
+// Size in KiB
+fn get_rom_size(rom_size: u8) -> u32 {
+    match rom_size {
+        0x00..=0x08 => 32 << rom_size,
+        0x52 => 1152,
+        0x53 => 1280,
+        0x54 => 1536,
+        _ => 0,
     }
 }
 
@@ -208,34 +377,58 @@ fn get_ram_size(ram_size: &u8) -> u32 {
     }
 }
 
+// MMM01 multicarts boot into a menu stored in the last 32 KiB of the ROM, and that is
+// where the header describing the cartridge itself lives
+fn header_offset(rom_data: &[u8]) -> usize {
+    if rom_data.len() > 0x8000 {
+        let offset = rom_data.len() - 0x8000;
+        if (0x0B..=0x0D).contains(&rom_data[offset + 0x0147]) && checksum(&rom_data[offset..]) {
+            return offset;
+        }
+    }
+    0
+}
+
 impl CartridgeHeader {
-    pub fn new(rom_data: &Vec<u8>) -> Result<Self, &'static str> {
-        if checksum(rom_data) {
-            let mbc_type: MBCTypes = get_mbc_type(&rom_data[0x0147]);
-            let rom_size: u32 = 32 * (1 << rom_data[0x0148]);
-            let ram_size: u32 = get_ram_size(&rom_data[0x0149]);
-            let sgb_flag = rom_data[0x146] == 0x03;
-            let company = get_company_name(rom_data[0x14B], &rom_data[0x144..=0x145]);
-            Ok(Self {
-                entry: rom_data[0x100..=0x103].try_into().unwrap(),
-                logo: rom_data[0x104..=0x133].try_into().unwrap(),
-                title: rom_data[0x134..=0x143]
-                    .iter()
-                    .filter(|&&b| b != 0)
-                    .map(|&b| b as char)
-                    .collect::<String>()
-                    .trim()
-                    .to_string(),
-                cgb_flag: rom_data[0x143],
-                sgb_flag,
-                mbc_type,
-                rom_size,
-                ram_size,
-                company,
-            })
-        } else {
-            Err("Cartridge is not a valid GB ROM")
+    pub fn new(rom_data: &[u8]) -> Result<Self, CartridgeError> {
+        Self::parse(rom_data, CartridgeOptions::default())
+    }
+
+    pub fn parse(rom_data: &[u8], options: CartridgeOptions) -> Result<Self, CartridgeError> {
+        if rom_data.len() < 0x150 {
+            return Err(CartridgeError::TooSmall(rom_data.len()));
         }
+        let rom_data = &rom_data[header_offset(rom_data)..];
+        let expected = header_checksum(rom_data);
+        if expected != rom_data[0x014D] && !options.ignore_header_checksum {
+            return Err(CartridgeError::BadHeaderChecksum {
+                expected,
+                found: rom_data[0x014D],
+            });
+        }
+
+        let mbc_type: MBCTypes = get_mbc_type(&rom_data[0x0147]);
+        let rom_size: u32 = get_rom_size(rom_data[0x0148]);
+        let ram_size: u32 = get_ram_size(&rom_data[0x0149]);
+        let sgb_flag = rom_data[0x146] == 0x03;
+        let company = get_company_name(rom_data[0x14B], &rom_data[0x144..=0x145]);
+        Ok(Self {
+            entry: rom_data[0x100..=0x103].try_into().unwrap(),
+            logo: rom_data[0x104..=0x133].try_into().unwrap(),
+            title: rom_data[0x134..=0x143]
+                .iter()
+                .filter(|&&b| b != 0)
+                .map(|&b| b as char)
+                .collect::<String>()
+                .trim()
+                .to_string(),
+            cgb_flag: rom_data[0x143],
+            sgb_flag,
+            mbc_type,
+            rom_size,
+            ram_size,
+            company,
+        })
     }
 }
 
@@ -310,3 +503,62 @@ fn get_company_name(old_code: u8, new_code: &[u8]) -> String {
         _ => format!("Unknown: {code_str}"),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{GbInstance, LoadError};
+    use crate::storage::MemoryStorage;
+
+    fn make_rom(mbc_type: u8, size: usize) -> Vec<u8> {
+        let mut rom = vec![0; size];
+        rom[0x147] = mbc_type;
+        rom[0x14D] = header_checksum(&rom);
+        rom
+    }
+
+    #[test]
+    fn test_short_rom_is_rejected() {
+        let result = load_cartridge_from_bytes(
+            vec![0; 0x100],
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        );
+        assert!(matches!(result, Err(CartridgeError::TooSmall(0x100))));
+    }
+
+    #[test]
+    fn test_header_checksum_override() {
+        let mut rom = make_rom(0x00, 0x8000);
+        rom[0x14D] = rom[0x14D].wrapping_add(1);
+        assert!(matches!(
+            CartridgeHeader::new(&rom),
+            Err(CartridgeError::BadHeaderChecksum { .. })
+        ));
+
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        assert!(load_cartridge_from_bytes(rom, &mut MemoryStorage::default(), options).is_ok());
+    }
+
+    #[test]
+    fn test_unsupported_mapper() {
+        let rom = make_rom(0x42, 0x8000);
+        let result = load_cartridge_from_bytes(
+            rom.clone(),
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(CartridgeError::UnsupportedMapper(0x42))
+        ));
+        assert!(matches!(
+            GbInstance::build_from_bytes(rom, None, None, CartridgeOptions::default()),
+            Err(LoadError::Cartridge(CartridgeError::UnsupportedMapper(
+                0x42
+            )))
+        ));
+    }
+}
@@ -30,7 +30,7 @@ const CB_CYCLES: [usize; 256] = [
 // DECODE CB PREFIXED INSTRUCTIONS
 impl Gb {
     pub fn decode_cb_prefix(&mut self) {
-        let prefix = self.read_byte(self.cpu.pc.wrapping_add(1));
+        let prefix = self.cpu_read(self.cpu.pc.wrapping_add(1));
         self.cpu.instruction_cycles = CB_CYCLES[prefix as usize];
         match prefix {
             0x00 => self.rlc_b(),
@@ -368,78 +368,78 @@ impl Gb {
 
     fn rlc_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.rlc_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn rl_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.rl_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn rrc_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.rrc_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn rr_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.rr_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn sla_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.sla_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn sra_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.sra_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn swap_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.swap_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn srl_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = self.srl_common(value);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn bit_dhl(&mut self, bit: u8) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         self.bit_common(value, bit);
     }
 
     fn res_dhl(&mut self, bit: u8) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = value & !(1 << bit);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 
     fn set_dhl(&mut self, bit: u8) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         let r = value | (1 << bit);
-        self.write_byte(hl, r);
+        self.cpu_write(hl, r);
     }
 }
 
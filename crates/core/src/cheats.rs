@@ -0,0 +1,279 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Game Genie and GameShark cheats.
+//!
+//! Game Genie codes (`ABC-DEF-GHI` or `ABC-DEF`) patch what the CPU reads from the ROM,
+//! optionally only when the original byte matches a compare value so the patch doesn't
+//! leak into other banks. GameShark codes (`ttvvaaaa`) write to RAM at every VBlank.
+
+use crate::gb::Gb;
+use crate::inspect::MemoryRegion;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheatCode {
+    GameGenie {
+        address: u16,
+        value: u8,
+        compare: Option<u8>,
+    },
+    GameShark {
+        /// 0x00 and 0x01 write through whatever is mapped, 0x80-0x8F pick a cartridge RAM
+        /// bank and 0x90-0x97 a WRAM bank.
+        kind: u8,
+        address: u16,
+        value: u8,
+    },
+}
+
+fn hex_digits(code: &str) -> Result<Vec<u8>, String> {
+    code.chars()
+        .filter(|c| *c != '-')
+        .map(|c| {
+            c.to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or_else(|| format!("Invalid cheat code {code}"))
+        })
+        .collect()
+}
+
+impl CheatCode {
+    pub fn parse(code: &str) -> Result<Self, String> {
+        let digits = hex_digits(code)?;
+        let byte = |i: usize| (digits[i] << 4) | digits[i + 1];
+        match digits.len() {
+            // Game Genie: AB is the new value and FCDE the address XORed with 0xF000. GI
+            // rotated right by 2 and XORed with 0xBA gives the compare value. H isn't used.
+            6 | 9 => {
+                let address = (u16::from(digits[5] ^ 0x0F) << 12)
+                    | (u16::from(digits[2]) << 8)
+                    | (u16::from(digits[3]) << 4)
+                    | u16::from(digits[4]);
+                if address >= 0x8000 {
+                    return Err(format!("Game Genie code {code} doesn't patch the ROM"));
+                }
+                let compare = (digits.len() == 9)
+                    .then(|| ((digits[6] << 4) | digits[8]).rotate_right(2) ^ 0xBA);
+                Ok(CheatCode::GameGenie {
+                    address,
+                    value: byte(0),
+                    compare,
+                })
+            }
+            // GameShark: tt is the code type, vv the value and aaaa the address, low byte
+            // first
+            8 => {
+                let kind = byte(0);
+                if !matches!(kind, 0x00 | 0x01 | 0x80..=0x8F | 0x90..=0x97) {
+                    return Err(format!(
+                        "Unsupported GameShark code type {kind:02X} in {code}"
+                    ));
+                }
+                Ok(CheatCode::GameShark {
+                    kind,
+                    address: u16::from_le_bytes([byte(4), byte(6)]),
+                    value: byte(2),
+                })
+            }
+            _ => Err(format!("Invalid cheat code {code}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Cheat {
+    pub name: String,
+    /// The codes as entered, several can be separated by spaces, commas or `+`.
+    pub code: String,
+    pub enabled: bool,
+    codes: Vec<CheatCode>,
+}
+
+impl Cheat {
+    pub fn new(name: &str, code: &str) -> Result<Self, String> {
+        let codes = code
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '+')
+            .filter(|part| !part.is_empty())
+            .map(CheatCode::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if codes.is_empty() {
+            return Err("The cheat has no codes".to_string());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            code: code.to_string(),
+            enabled: true,
+            codes,
+        })
+    }
+
+    pub fn codes(&self) -> &[CheatCode] {
+        &self.codes
+    }
+}
+
+/// The cheats of the running game. The codes of the enabled ones are kept apart, as the
+/// Game Genie ones are checked on every ROM read.
+#[derive(Default)]
+pub struct Cheats {
+    cheats: Vec<Cheat>,
+    rom_patches: Vec<CheatCode>,
+    ram_writes: Vec<CheatCode>,
+}
+
+impl Cheats {
+    pub fn list(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cheats.is_empty()
+    }
+
+    pub fn add(&mut self, cheat: Cheat) {
+        self.cheats.push(cheat);
+        self.update_codes();
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.cheats.len() {
+            self.cheats.remove(index);
+            self.update_codes();
+        }
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(cheat) = self.cheats.get_mut(index) {
+            cheat.enabled = enabled;
+            self.update_codes();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cheats.clear();
+        self.update_codes();
+    }
+
+    fn update_codes(&mut self) {
+        let codes = self
+            .cheats
+            .iter()
+            .filter(|cheat| cheat.enabled)
+            .flat_map(|cheat| cheat.codes.iter().copied());
+        (self.rom_patches, self.ram_writes) =
+            codes.partition(|code| matches!(code, CheatCode::GameGenie { .. }));
+    }
+
+    /// The byte the CPU sees when `value` is read from the ROM at `address`.
+    pub(crate) fn patch_rom(&self, address: u16, value: u8) -> u8 {
+        for code in &self.rom_patches {
+            if let CheatCode::GameGenie {
+                address: patch_address,
+                value: patch_value,
+                compare,
+            } = *code
+            {
+                if patch_address == address && compare.is_none_or(|compare| compare == value) {
+                    return patch_value;
+                }
+            }
+        }
+        value
+    }
+}
+
+impl Gb {
+    // Called when VBlank starts, like the GameShark hooking the VBlank interrupt
+    pub(crate) fn apply_gameshark_codes(&mut self) {
+        if self.cheats.ram_writes.is_empty() {
+            return;
+        }
+        for index in 0..self.cheats.ram_writes.len() {
+            let CheatCode::GameShark {
+                kind,
+                address,
+                value,
+            } = self.cheats.ram_writes[index]
+            else {
+                continue;
+            };
+            match (kind, address) {
+                (0x80..=0x8F, 0xA000..=0xBFFF) => self.poke(
+                    MemoryRegion::CartridgeRam,
+                    (kind & 0x0F) as usize,
+                    address - 0xA000,
+                    value,
+                ),
+                (0x90..=0x97, 0xD000..=0xDFFF) => self.poke(
+                    MemoryRegion::Wram,
+                    ((kind & 0x07) as usize).max(1),
+                    address - 0xD000,
+                    value,
+                ),
+                _ => self.write_byte(address, value),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_codes() {
+        assert_eq!(
+            CheatCode::parse("01A-B2C-E6F"),
+            Ok(CheatCode::GameGenie {
+                address: 0x3AB2,
+                value: 0x01,
+                compare: Some(0x41),
+            })
+        );
+        assert_eq!(
+            CheatCode::parse("01AB2C"),
+            Ok(CheatCode::GameGenie {
+                address: 0x3AB2,
+                value: 0x01,
+                compare: None,
+            })
+        );
+        assert_eq!(
+            CheatCode::parse("01FF34C1"),
+            Ok(CheatCode::GameShark {
+                kind: 0x01,
+                address: 0xC134,
+                value: 0xFF,
+            })
+        );
+        assert!(CheatCode::parse("01A-B27-E6F").is_err());
+        assert!(CheatCode::parse("XYZ").is_err());
+    }
+
+    #[test]
+    fn test_rom_patch_compare() {
+        let mut cheats = Cheats::default();
+        cheats.add(Cheat::new("Lives", "01A-B2C-E6F").unwrap());
+        assert_eq!(cheats.patch_rom(0x3AB2, 0x41), 0x01);
+        // Another bank mapped at the address
+        assert_eq!(cheats.patch_rom(0x3AB2, 0x42), 0x42);
+        cheats.set_enabled(0, false);
+        assert_eq!(cheats.patch_rom(0x3AB2, 0x41), 0x41);
+    }
+
+    #[test]
+    fn test_gameshark_wram_bank() {
+        let options = crate::cartridge::CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb =
+            crate::prelude::GbInstance::build_from_bytes(vec![0; 0x8000], None, None, options)
+                .unwrap();
+        gb.cheats
+            .add(Cheat::new("Items", "0163C1C0 + 910234D1").unwrap());
+        gb.apply_gameshark_codes();
+        assert_eq!(gb.read_byte(0xC0C1), 0x63);
+        assert_eq!(gb.peek(MemoryRegion::Wram, 1, 0x0134), 0x02);
+    }
+}
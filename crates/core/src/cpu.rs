@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: MIT
 
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Cpu {
     pub registers: [u8; 8],
     pub sp: u16,
@@ -12,13 +13,18 @@ pub struct Cpu {
     pub zero: bool,
     pub current_instruction: u8,
     pub instruction_cycles: usize,
+    // M-cycles the current instruction has already spent on the bus
+    #[serde(skip)]
+    pub bus_cycles: usize,
     pub pending_cycles: usize,
     pub interrupt_master: bool,
     pub is_halted: bool,
     pub ime_toggle: bool,
     pub d_ime_toggle: bool,
     pub is_stopped: bool,
+    pub stall_cycles: usize,
     pub halt_bug: bool,
+    pub is_locked: bool,
     pub ticks: u32,
 }
 
@@ -43,6 +49,11 @@ const OPCODES_SIZE: [u16; 256] = [
     2, 1, 1, 1, 1, 1, 2, 1, 2, 1, 3, 1, 1, 1, 2, 1,
 ];
 
+/// Size in bytes of the instruction starting with this opcode.
+pub fn instruction_length(opcode: u8) -> u16 {
+    OPCODES_SIZE[opcode as usize]
+}
+
 fn get_initial_flag_states(value: u8) -> (bool, bool, bool, bool) {
     let carry = value & 0x10 == 0x10;
     let half_carry = value & 0x20 == 0x20;
@@ -64,13 +75,16 @@ impl Cpu {
             pc: 0x0100,
             current_instruction: 0,
             instruction_cycles: 0,
+            bus_cycles: 0,
             pending_cycles: 0,
             interrupt_master: false,
             is_halted: false,
             ime_toggle: false,
             d_ime_toggle: false,
             is_stopped: false,
+            stall_cycles: 0,
             halt_bug: false,
+            is_locked: false,
             ticks: 0,
         }
     }
@@ -0,0 +1,401 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use super::gb::Gb;
+
+// Deep enough for any sane program, a runaway recursion shouldn't eat all the memory
+const MAX_CALL_STACK: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakpointKind {
+    /// The CPU is about to execute the instruction at the address
+    Execute,
+    /// The CPU reads the address
+    Read,
+    /// The CPU writes to the address
+    Write,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Register(&'static str),
+    Memory(u16),
+    /// The byte read or written by the access that hit a watchpoint
+    Value,
+    Constant(u16),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+const REGISTERS: [&str; 14] = [
+    "a", "f", "b", "c", "d", "e", "h", "l", "af", "bc", "de", "hl", "sp", "pc",
+];
+
+const COMPARISONS: [(&str, Comparison); 6] = [
+    ("==", Comparison::Equal),
+    ("!=", Comparison::NotEqual),
+    ("<=", Comparison::LessEqual),
+    (">=", Comparison::GreaterEqual),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+];
+
+/// Extra check for a breakpoint, like `a == $10`, `[c000] != 0` or `value > 7`.
+///
+/// Numbers are decimal unless prefixed with `$` or `0x`, addresses in brackets are hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Condition {
+    pub left: Operand,
+    pub comparison: Comparison,
+    pub right: Operand,
+}
+
+fn parse_number(text: &str) -> Option<u16> {
+    if let Some(hex) = text
+        .strip_prefix('$')
+        .or_else(|| text.strip_prefix("0x"))
+        .or_else(|| text.strip_prefix("0X"))
+    {
+        u16::from_str_radix(hex, 16).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn parse_operand(text: &str) -> Result<Operand, String> {
+    let text = text.trim().to_ascii_lowercase();
+    if text == "value" {
+        return Ok(Operand::Value);
+    }
+    if let Some(register) = REGISTERS.iter().find(|register| **register == text) {
+        return Ok(Operand::Register(register));
+    }
+    if let Some(address) = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+    {
+        let address = address
+            .trim()
+            .trim_start_matches('$')
+            .trim_start_matches("0x");
+        return u16::from_str_radix(address, 16)
+            .map(Operand::Memory)
+            .map_err(|_| format!("Invalid address {address}"));
+    }
+    parse_number(&text)
+        .map(Operand::Constant)
+        .ok_or_else(|| format!("Invalid operand {text}"))
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (index, symbol, comparison) = COMPARISONS
+            .iter()
+            .filter_map(|(symbol, comparison)| {
+                text.find(symbol).map(|index| (index, *symbol, *comparison))
+            })
+            // "<" is also found inside "<=", which comes first in the list
+            .min_by_key(|(index, _, _)| *index)
+            .ok_or_else(|| format!("No comparison in {text}"))?;
+        Ok(Self {
+            left: parse_operand(&text[..index])?,
+            comparison,
+            right: parse_operand(&text[index + symbol.len()..])?,
+        })
+    }
+
+    fn operand(&self, gb: &Gb, operand: Operand, value: Option<u8>) -> u16 {
+        match operand {
+            Operand::Register(register) => match register {
+                "a" => gb.cpu.a() as u16,
+                "f" => gb.cpu.f() as u16,
+                "b" => gb.cpu.b() as u16,
+                "c" => gb.cpu.c() as u16,
+                "d" => gb.cpu.d() as u16,
+                "e" => gb.cpu.e() as u16,
+                "h" => gb.cpu.h() as u16,
+                "l" => gb.cpu.l() as u16,
+                "af" => gb.cpu.af(),
+                "bc" => gb.cpu.bc(),
+                "de" => gb.cpu.de(),
+                "hl" => gb.cpu.hl(),
+                "sp" => gb.cpu.sp,
+                _ => gb.cpu.pc,
+            },
+            Operand::Memory(address) => gb.read_byte(address) as u16,
+            Operand::Value => value.unwrap_or_default() as u16,
+            Operand::Constant(constant) => constant,
+        }
+    }
+
+    pub fn evaluate(&self, gb: &Gb, value: Option<u8>) -> bool {
+        let left = self.operand(gb, self.left, value);
+        let right = self.operand(gb, self.right, value);
+        match self.comparison {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterEqual => left >= right,
+        }
+    }
+}
+
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{register}"),
+            Operand::Memory(address) => write!(f, "[{address:04x}]"),
+            Operand::Value => write!(f, "value"),
+            Operand::Constant(constant) => write!(f, "${constant:02x}"),
+        }
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = COMPARISONS
+            .iter()
+            .find(|(_, comparison)| *comparison == self.comparison)
+            .map(|(symbol, _)| *symbol)
+            .unwrap_or_default();
+        write!(f, "{} {symbol} {}", self.left, self.right)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub kind: BreakpointKind,
+    pub address: u16,
+    pub condition: Option<Condition>,
+    pub enabled: bool,
+}
+
+impl Breakpoint {
+    pub fn new(kind: BreakpointKind, address: u16) -> Self {
+        Self {
+            kind,
+            address,
+            condition: None,
+            enabled: true,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+}
+
+/// Why the emulation stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BreakHit {
+    pub kind: BreakpointKind,
+    pub address: u16,
+    /// Byte read or written, for watchpoints
+    pub value: Option<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+    /// Address of the call or rst instruction, or where the interrupt was taken
+    pub call_site: u16,
+    pub target: u16,
+    pub interrupt: bool,
+    // SP after pushing the return address, the frame is gone once SP is above it
+    sp: u16,
+}
+
+/// Breakpoints, watchpoints and the call stack of a running `Gb`.
+///
+/// When a breakpoint is hit the current instruction is finished and `Gb::run_one_frame`
+/// returns early, with the reason available through [`Debugger::hit`].
+#[derive(Default)]
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    run_to: Option<u16>,
+    hit: Option<BreakHit>,
+    call_stack: Vec<CallFrame>,
+}
+
+impl Debugger {
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) {
+        if index < self.breakpoints.len() {
+            self.breakpoints.remove(index);
+        }
+    }
+
+    /// Stops once the CPU reaches `address`, then forgets about it.
+    pub fn run_to(&mut self, address: u16) {
+        self.run_to = Some(address);
+    }
+
+    pub fn hit(&self) -> Option<BreakHit> {
+        self.hit
+    }
+
+    pub fn clear_hit(&mut self) {
+        self.hit = None;
+    }
+
+    /// Innermost call last.
+    pub fn call_stack(&self) -> &[CallFrame] {
+        &self.call_stack
+    }
+
+    fn check(
+        &self,
+        gb: &Gb,
+        kind: BreakpointKind,
+        address: u16,
+        value: Option<u8>,
+    ) -> Option<BreakHit> {
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| {
+                breakpoint.enabled && breakpoint.kind == kind && breakpoint.address == address
+            })
+            .any(|breakpoint| {
+                breakpoint
+                    .condition
+                    .is_none_or(|condition| condition.evaluate(gb, value))
+            })
+            .then_some(BreakHit {
+                kind,
+                address,
+                value,
+            })
+    }
+}
+
+impl Gb {
+    /// Runs a single instruction, or an interrupt dispatch.
+    pub fn step_instruction(&mut self) {
+        self.debugger.hit = None;
+        self.run();
+    }
+
+    // Called by the CPU for every memory access it makes
+    pub(crate) fn debug_access(&mut self, kind: BreakpointKind, address: u16, value: u8) {
+        if self.debugger.breakpoints.is_empty() || self.debugger.hit.is_some() {
+            return;
+        }
+        self.debugger.hit = self.debugger.check(self, kind, address, Some(value));
+    }
+
+    // Called after each instruction, with PC pointing at the next one
+    pub(crate) fn debug_check_pc(&mut self) {
+        let pc = self.cpu.pc;
+        if self.debugger.run_to == Some(pc) {
+            self.debugger.run_to = None;
+            self.debugger.hit = Some(BreakHit {
+                kind: BreakpointKind::Execute,
+                address: pc,
+                value: None,
+            });
+        }
+        if self.debugger.breakpoints.is_empty() || self.debugger.hit.is_some() {
+            return;
+        }
+        self.debugger.hit = self.debugger.check(self, BreakpointKind::Execute, pc, None);
+    }
+
+    pub(crate) fn debug_call(&mut self, call_site: u16, interrupt: bool) {
+        let call_stack = &mut self.debugger.call_stack;
+        if call_stack.len() == MAX_CALL_STACK {
+            call_stack.remove(0);
+        }
+        call_stack.push(CallFrame {
+            call_site,
+            target: self.cpu.pc,
+            interrupt,
+            sp: self.cpu.sp,
+        });
+    }
+
+    pub(crate) fn debug_return(&mut self) {
+        let sp = self.cpu.sp;
+        // Also drops frames left behind by code that juggles the stack by hand
+        while self
+            .debugger
+            .call_stack
+            .last()
+            .is_some_and(|frame| frame.sp < sp)
+        {
+            self.debugger.call_stack.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::{load_cartridge, CartridgeOptions};
+    use crate::gb::GbTypes;
+    use crate::storage::MemoryStorage;
+
+    fn setup_gb() -> Gb {
+        let (mbc, gb_mode) = load_cartridge(
+            "../../roms/cpu_instrs.gb",
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        )
+        .unwrap();
+        Gb::new(mbc, gb_mode, GbTypes::Dmg)
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        let condition = Condition::parse("a == $10").unwrap();
+        assert_eq!(condition.left, Operand::Register("a"));
+        assert_eq!(condition.comparison, Comparison::Equal);
+        assert_eq!(condition.right, Operand::Constant(0x10));
+
+        let condition = Condition::parse("[C000]<=value").unwrap();
+        assert_eq!(condition.left, Operand::Memory(0xC000));
+        assert_eq!(condition.comparison, Comparison::LessEqual);
+        assert_eq!(condition.right, Operand::Value);
+
+        assert!(Condition::parse("a = 1").is_err());
+        assert!(Condition::parse("x == 1").is_err());
+    }
+
+    #[test]
+    fn test_watchpoint_stops_frame() {
+        let mut gb = setup_gb();
+        gb.print_serial = false;
+        // The test prints its name through the serial port first thing
+        gb.debugger
+            .add_breakpoint(Breakpoint::new(BreakpointKind::Write, 0xFF01));
+        for _ in 0..60 {
+            gb.run_one_frame();
+            gb.get_screen_data();
+            if gb.debugger.hit().is_some() {
+                break;
+            }
+        }
+        let hit = gb.debugger.hit().unwrap();
+        assert_eq!(hit.kind, BreakpointKind::Write);
+        assert_eq!(hit.address, 0xFF01);
+        // Printing happens in a subroutine
+        assert!(!gb.debugger.call_stack().is_empty());
+
+        // Resuming runs until the next write
+        gb.run_one_frame();
+        assert!(gb.debugger.hit().is_some());
+    }
+}
@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: MIT
 
+use crate::cpu::instruction_length;
 use crate::gb::Gb;
 
 // Register name constants for better maintainability and consistency
@@ -19,27 +20,35 @@ fn format_word(value: u16) -> String {
 }
 
 impl Gb {
+    /// Formats a jump or call target, using its label when there is one
+    fn format_target(&self, value: u16) -> String {
+        match self.symbol_at(value) {
+            Some(label) => label.to_string(),
+            None => format_word(value),
+        }
+    }
+
     /// Disassembles a load instruction with an immediate word value
-    fn d_ld_rr_nn(&self, target: &str) -> String {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
+    fn d_ld_rr_nn(&self, address: u16, target: &str) -> String {
+        let value = self.read_word(address.wrapping_add(1));
         format!("ld {}, {}", target, format_word(value))
     }
 
     /// Disassembles a load instruction that adds SP to an immediate byte
-    fn d_ld_hl_sp_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1)) as i8;
+    fn d_ld_hl_sp_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1)) as i8;
         format!("ld hl, sp + {}", format_byte(value as u8))
     }
 
     /// Disassembles a store SP to memory instruction
-    fn d_ld_nn_sp(&self) -> String {
-        let addr = self.read_word(self.cpu.pc.wrapping_add(1));
+    fn d_ld_nn_sp(&self, address: u16) -> String {
+        let addr = self.read_word(address.wrapping_add(1));
         format!("ld [{}], sp", format_word(addr))
     }
 
     /// Disassembles a load immediate byte to register instruction
-    fn d_ld_r_n(&self, target: &str) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_ld_r_n(&self, address: u16, target: &str) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("ld {}, {}", target, format_byte(value))
     }
 
@@ -49,20 +58,20 @@ impl Gb {
     }
 
     /// Disassembles a load immediate word to register pair instruction
-    fn d_ld_nn_rr(&self, target: &str) -> String {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
+    fn d_ld_nn_rr(&self, address: u16, target: &str) -> String {
+        let value = self.read_word(address.wrapping_add(1));
         format!("ld {}, {}", format_word(value), target)
     }
 
     /// Disassembles a load high memory instruction with register
-    fn d_ldh_n_r(&self, target: &str) -> String {
-        let addr = 0xFF00 | self.read_byte(self.cpu.pc.wrapping_add(1)) as u16;
+    fn d_ldh_n_r(&self, address: u16, target: &str) -> String {
+        let addr = 0xFF00 | self.read_byte(address.wrapping_add(1)) as u16;
         format!("ldh [{}], {}", format_word(addr), target)
     }
 
     /// Disassembles a load register from high memory instruction
-    fn d_ldh_r_n(&self, target: &str) -> String {
-        let addr = 0xFF00 | self.read_byte(self.cpu.pc.wrapping_add(1)) as u16;
+    fn d_ldh_r_n(&self, address: u16, target: &str) -> String {
+        let addr = 0xFF00 | self.read_byte(address.wrapping_add(1)) as u16;
         format!("ldh {}, [{}]", target, format_word(addr))
     }
 
@@ -92,14 +101,14 @@ impl Gb {
     }
 
     /// Disassembles an add SP to immediate byte instruction
-    fn d_add_sp_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1)) as i8;
+    fn d_add_sp_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1)) as i8;
         format!("add sp, {}", format_byte(value as u8))
     }
 
     /// Disassembles an add A to immediate byte instruction
-    fn d_add_a_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_add_a_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("add a, {}", format_byte(value))
     }
 
@@ -109,8 +118,8 @@ impl Gb {
     }
 
     /// Disassembles an ADC A to immediate byte instruction
-    fn d_adc_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_adc_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("adc {}", format_byte(value))
     }
 
@@ -125,8 +134,8 @@ impl Gb {
     }
 
     /// Disassembles a SUB immediate byte instruction
-    fn d_sub_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_sub_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("sub {}", format_byte(value))
     }
 
@@ -136,8 +145,8 @@ impl Gb {
     }
 
     /// Disassembles a SBC immediate byte instruction
-    fn d_sbc_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_sbc_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("sbc {}", format_byte(value))
     }
 
@@ -147,8 +156,8 @@ impl Gb {
     }
 
     /// Disassembles an AND immediate byte instruction
-    fn d_and_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_and_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("and {}", format_byte(value))
     }
 
@@ -158,8 +167,8 @@ impl Gb {
     }
 
     /// Disassembles an OR immediate byte instruction
-    fn d_or_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_or_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("or {}", format_byte(value))
     }
 
@@ -169,8 +178,8 @@ impl Gb {
     }
 
     /// Disassembles an XOR immediate byte instruction
-    fn d_xor_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_xor_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("xor {}", format_byte(value))
     }
 
@@ -180,115 +189,172 @@ impl Gb {
     }
 
     /// Disassembles a CP immediate byte instruction
-    fn d_cp_n(&self) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn d_cp_n(&self, address: u16) -> String {
+        let value = self.read_byte(address.wrapping_add(1));
         format!("cp {}", format_byte(value))
     }
 
     /// Disassembles a JP immediate word instruction
-    fn d_jp_nn(&self) -> String {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
-        format!("jp {}", format_word(value))
+    fn d_jp_nn(&self, address: u16) -> String {
+        let value = self.read_word(address.wrapping_add(1));
+        format!("jp {}", self.format_target(value))
     }
 
     /// Disassembles a JP flag immediate word instruction
-    fn d_jp_f_nn(&self, flag: &str) -> String {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
-        format!("jp {}, {}", flag, format_word(value))
+    fn d_jp_f_nn(&self, address: u16, flag: &str) -> String {
+        let value = self.read_word(address.wrapping_add(1));
+        format!("jp {}, {}", flag, self.format_target(value))
+    }
+
+    /// Gets the target of a relative jump, which is relative to the next instruction
+    fn jr_target(&self, address: u16) -> u16 {
+        let offset = self.read_byte(address.wrapping_add(1)) as i8;
+        address.wrapping_add(2).wrapping_add_signed(offset as i16)
     }
 
     /// Disassembles a JR immediate byte instruction
-    fn d_jr_n(&self) -> String {
-        let value = ((self.cpu.pc as u32 as i32)
-            + (self.read_byte(self.cpu.pc.wrapping_add(1)) as i8 as i32))
-            as u16;
-        format!("jr {}", format_word(value))
+    fn d_jr_n(&self, address: u16) -> String {
+        format!("jr {}", self.format_target(self.jr_target(address)))
     }
 
     /// Disassembles a JR flag immediate byte instruction
-    fn d_jr_f_n(&self, flag: &str) -> String {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1)) as i8;
-        format!("jr {}, {}", flag, format_byte(value as u8))
+    fn d_jr_f_n(&self, address: u16, flag: &str) -> String {
+        format!(
+            "jr {}, {}",
+            flag,
+            self.format_target(self.jr_target(address))
+        )
     }
 
     /// Disassembles a CALL flag immediate word instruction
-    fn d_call_f_nn(&self, flag: &str) -> String {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
-        format!("call {}, {}", flag, format_word(value))
+    fn d_call_f_nn(&self, address: u16, flag: &str) -> String {
+        let value = self.read_word(address.wrapping_add(1));
+        format!("call {}, {}", flag, self.format_target(value))
     }
 
     /// Disassembles a CALL immediate word instruction
-    fn d_call_nn(&self) -> String {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
-        format!("call {}", format_word(value))
+    fn d_call_nn(&self, address: u16) -> String {
+        let value = self.read_word(address.wrapping_add(1));
+        format!("call {}", self.format_target(value))
     }
 
-    /// Disassembles the current instruction
+    /// Disassembles the instruction at PC
     pub fn disassemble(&self) -> String {
-        match self.cpu.current_instruction {
+        self.disassemble_at(self.cpu.pc)
+    }
+
+    /// Disassembles the instruction at an address, returning it along with its length
+    pub fn disassemble_with_length(&self, address: u16) -> (String, u16) {
+        let opcode = self.read_byte(address);
+        (self.disassemble_at(address), instruction_length(opcode))
+    }
+
+    /// Disassembles instructions around an address, as (address, instruction) pairs.
+    ///
+    /// Code can't be decoded backwards, so the instructions before `address` start from
+    /// the furthest point whose instructions line up with it.
+    pub fn disassemble_around(
+        &self,
+        address: u16,
+        before: usize,
+        after: usize,
+    ) -> Vec<(u16, String)> {
+        let mut start = address;
+        for distance in (1..=before as u16 * 3).rev() {
+            let candidate = address.wrapping_sub(distance);
+            let mut offset = 0;
+            let mut count = 0;
+            while offset < distance {
+                offset += instruction_length(self.read_byte(candidate.wrapping_add(offset)));
+                count += 1;
+            }
+            if offset == distance && count <= before {
+                start = candidate;
+                break;
+            }
+        }
+
+        let mut lines = Vec::with_capacity(before + after + 1);
+        let mut current = start;
+        while current != address {
+            let (text, length) = self.disassemble_with_length(current);
+            lines.push((current, text));
+            current = current.wrapping_add(length);
+        }
+        for _ in 0..=after {
+            let (text, length) = self.disassemble_with_length(current);
+            lines.push((current, text));
+            current = current.wrapping_add(length);
+        }
+        lines
+    }
+
+    /// Disassembles the instruction at an address
+    pub fn disassemble_at(&self, address: u16) -> String {
+        match self.read_byte(address) {
             0x00 => "nop".to_string(),
-            0x01 => self.d_ld_rr_nn("bc"),
+            0x01 => self.d_ld_rr_nn(address, "bc"),
             0x02 => self.d_ld_r_r("(bc)", "a"),
             0x03 => self.d_inc("bc"),
             0x04 => self.d_inc("b"),
             0x05 => self.d_dec("b"),
-            0x06 => self.d_ld_r_n("b"),
+            0x06 => self.d_ld_r_n(address, "b"),
             0x07 => "rlca".to_string(),
-            0x08 => self.d_ld_nn_sp(),
+            0x08 => self.d_ld_nn_sp(address),
             0x09 => self.d_add_hl_r("bc"),
             0x0A => self.d_ld_r_r("a", "(bc)"),
             0x0B => self.d_dec("bc"),
             0x0C => self.d_inc("c"),
             0x0D => self.d_dec("c"),
-            0x0E => self.d_ld_r_n("c"),
+            0x0E => self.d_ld_r_n(address, "c"),
             0x0F => "rrca".to_string(),
             0x10 => "stop".to_string(),
-            0x11 => self.d_ld_rr_nn("de"),
+            0x11 => self.d_ld_rr_nn(address, "de"),
             0x12 => self.d_ld_r_r("(de)", "a"),
             0x13 => self.d_inc("de"),
             0x14 => self.d_inc("d"),
             0x15 => self.d_dec("d"),
-            0x16 => self.d_ld_r_n("d"),
+            0x16 => self.d_ld_r_n(address, "d"),
             0x17 => "rla".to_string(),
-            0x18 => self.d_jr_n(),
+            0x18 => self.d_jr_n(address),
             0x19 => self.d_add_hl_r("de"),
             0x1A => self.d_ld_r_r("a", "(de)"),
             0x1B => self.d_dec("de"),
             0x1C => self.d_inc("e"),
             0x1D => self.d_dec("e"),
-            0x1E => self.d_ld_r_n("e"),
+            0x1E => self.d_ld_r_n(address, "e"),
             0x1F => "rra".to_string(),
-            0x20 => self.d_jr_f_n("nz"),
-            0x21 => self.d_ld_rr_nn("hl"),
+            0x20 => self.d_jr_f_n(address, "nz"),
+            0x21 => self.d_ld_rr_nn(address, "hl"),
             0x22 => self.d_ld_r_r("(hl+)", "a"),
             0x23 => self.d_inc("hl"),
             0x24 => self.d_inc("h"),
             0x25 => self.d_dec("h"),
-            0x26 => self.d_ld_r_n("h"),
+            0x26 => self.d_ld_r_n(address, "h"),
             0x27 => "daa".to_string(),
-            0x28 => self.d_jr_f_n("z"),
+            0x28 => self.d_jr_f_n(address, "z"),
             0x29 => self.d_add_hl_r("hl"),
             0x2A => self.d_ld_r_r("a", "(hl+)"),
             0x2B => self.d_dec("hl"),
             0x2C => self.d_inc("l"),
             0x2D => self.d_dec("l"),
-            0x2E => self.d_ld_r_n("l"),
+            0x2E => self.d_ld_r_n(address, "l"),
             0x2F => "cpl".to_string(),
-            0x30 => self.d_jr_f_n("nc"),
-            0x31 => self.d_ld_rr_nn("sp"),
+            0x30 => self.d_jr_f_n(address, "nc"),
+            0x31 => self.d_ld_rr_nn(address, "sp"),
             0x32 => self.d_ld_r_r("(hl-)", "a"),
             0x33 => self.d_inc("sp"),
             0x34 => self.d_inc("(hl)"),
             0x35 => self.d_dec("(hl)"),
-            0x36 => self.d_ld_r_n("(hl)"),
+            0x36 => self.d_ld_r_n(address, "(hl)"),
             0x37 => "scf".to_string(),
-            0x38 => self.d_jr_f_n("c"),
+            0x38 => self.d_jr_f_n(address, "c"),
             0x39 => self.d_add_hl_r("sp"),
             0x3A => self.d_ld_r_r("a", "(hl-)"),
             0x3B => self.d_dec("sp"),
             0x3C => self.d_inc("a"),
             0x3D => self.d_dec("a"),
-            0x3E => self.d_ld_r_n("a"),
+            0x3E => self.d_ld_r_n(address, "a"),
             0x3F => "ccf".to_string(),
             0x40 => self.d_ld_r_r("b", "b"),
             0x41 => self.d_ld_r_r("b", "c"),
@@ -420,56 +486,56 @@ impl Gb {
             0xBF => self.d_cp_r("a"),
             0xC0 => "ret nz".to_string(),
             0xC1 => self.d_pop_nn("bc"),
-            0xC2 => self.d_jp_f_nn("nz"),
-            0xC3 => self.d_jp_nn(),
-            0xC4 => self.d_call_f_nn("nz"),
+            0xC2 => self.d_jp_f_nn(address, "nz"),
+            0xC3 => self.d_jp_nn(address),
+            0xC4 => self.d_call_f_nn(address, "nz"),
             0xC5 => self.d_push_nn("bc"),
-            0xC6 => self.d_add_a_n(),
+            0xC6 => self.d_add_a_n(address),
             0xC7 => "rst 00h".to_string(),
             0xC8 => "ret z".to_string(),
             0xC9 => "ret".to_string(),
-            0xCA => self.d_jp_f_nn("z"),
-            0xCB => self.disassemble_cb(),
-            0xCC => self.d_call_f_nn("z"),
-            0xCD => self.d_call_nn(),
-            0xCE => self.d_adc_n(),
+            0xCA => self.d_jp_f_nn(address, "z"),
+            0xCB => self.disassemble_cb(address),
+            0xCC => self.d_call_f_nn(address, "z"),
+            0xCD => self.d_call_nn(address),
+            0xCE => self.d_adc_n(address),
             0xCF => "rst 08h".to_string(),
             0xD0 => "ret nc".to_string(),
             0xD1 => self.d_pop_nn("de"),
-            0xD2 => self.d_jp_f_nn("nc"),
-            0xD4 => self.d_call_f_nn("nc"),
+            0xD2 => self.d_jp_f_nn(address, "nc"),
+            0xD4 => self.d_call_f_nn(address, "nc"),
             0xD5 => self.d_push_nn("de"),
-            0xD6 => self.d_sub_n(),
+            0xD6 => self.d_sub_n(address),
             0xD7 => "rst 10h".to_string(),
             0xD8 => "ret c".to_string(),
             0xD9 => "reti".to_string(),
-            0xDA => self.d_jp_f_nn("c"),
-            0xDC => self.d_call_f_nn("c"),
-            0xDE => self.d_sbc_n(),
+            0xDA => self.d_jp_f_nn(address, "c"),
+            0xDC => self.d_call_f_nn(address, "c"),
+            0xDE => self.d_sbc_n(address),
             0xDF => "rst 18h".to_string(),
-            0xE0 => self.d_ldh_n_r("a"),
+            0xE0 => self.d_ldh_n_r(address, "a"),
             0xE1 => self.d_pop_nn("hl"),
             0xE2 => self.d_ld_r_r("0xff00 + c", "a"),
             0xE5 => self.d_push_nn("hl"),
-            0xE6 => self.d_and_n(),
+            0xE6 => self.d_and_n(address),
             0xE7 => "rst 20h".to_string(),
-            0xE8 => self.d_add_sp_n(),
+            0xE8 => self.d_add_sp_n(address),
             0xE9 => "jp (hl)".to_string(),
-            0xEA => self.d_ld_nn_rr("a"),
-            0xEE => self.d_xor_n(),
+            0xEA => self.d_ld_nn_rr(address, "a"),
+            0xEE => self.d_xor_n(address),
             0xEF => "rst 28h".to_string(),
-            0xF0 => self.d_ldh_r_n("a"),
+            0xF0 => self.d_ldh_r_n(address, "a"),
             0xF1 => self.d_pop_nn("af"),
             0xF2 => self.d_ld_r_r("a", "0xff00 + c"),
             0xF3 => "di".to_string(),
             0xF5 => self.d_push_nn("af"),
-            0xF6 => self.d_or_n(),
+            0xF6 => self.d_or_n(address),
             0xF7 => "rst 30h".to_string(),
-            0xF8 => self.d_ld_hl_sp_n(),
+            0xF8 => self.d_ld_hl_sp_n(address),
             0xF9 => "ld sp, hl".to_string(),
-            0xFA => self.d_ld_rr_nn("a"),
+            0xFA => self.d_ld_rr_nn(address, "a"),
             0xFB => "ei".to_string(),
-            0xFE => self.d_cp_n(),
+            0xFE => self.d_cp_n(address),
             0xFF => "rst 38h".to_string(),
             _ => "ill".to_string(),
         }
@@ -577,8 +643,8 @@ impl Gb {
     }
 
     /// Disassembles a CB prefix instruction
-    fn disassemble_cb(&self) -> String {
-        let prefix = self.read_byte(self.cpu.pc.wrapping_add(1));
+    fn disassemble_cb(&self, address: u16) -> String {
+        let prefix = self.read_byte(address.wrapping_add(1));
         match prefix >> 3 {
             0 => self.d_rlc_r(prefix),
             1 => self.d_rrc_r(prefix),
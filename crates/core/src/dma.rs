@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 use super::gb::Gb;
 
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Dma {
     pub active: bool,
     pub byte: u8,
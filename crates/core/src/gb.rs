@@ -2,15 +2,23 @@
 //
 // SPDX-License-Identifier: MIT
 
-use rand::prelude::*;
+use std::collections::VecDeque;
 
-use crate::cartridge::{GbMode, Mbc};
+use crate::cartridge::{CameraSource, GbMode, Mbc, RtcMode};
+use crate::cheats::Cheats;
 use crate::cpu::Cpu;
-use crate::joypad::{Joypad, JoypadButton};
+use crate::debugger::Debugger;
+use crate::joypad::{InputEvent, Joypad, JoypadButton};
+use crate::link::LinkPort;
+use crate::memory::Memory;
+use crate::power_on::RamPattern;
 use crate::ppu::{Ppu, PpuMode};
 use crate::serial::Serial;
 use crate::sound::Sound;
+use crate::storage::BatterySave;
+use crate::symbols::Symbols;
 use crate::timer::Timer;
+use crate::trace::Tracer;
 
 #[allow(dead_code)]
 #[derive(PartialEq, Eq)]
@@ -21,7 +29,11 @@ pub enum GbTypes {
     Sgb,
 }
 
-#[allow(dead_code)]
+/// Dots in a frame, 154 lines of 456. The PPU and the frame budget run from the base clock,
+/// so a frame is twice as many CPU cycles in double speed mode.
+pub const CYCLES_PER_FRAME: u32 = 70224;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
 pub enum SpeedMode {
     Normal,
     Double,
@@ -35,7 +47,9 @@ pub struct Gb {
     pub sound: Sound,
     pub timer: Timer,
     pub mbc: Box<dyn Mbc>,
-    pub gb_speed: u8,
+    pub link_port: Option<Box<dyn LinkPort>>,
+    // Echo bytes sent through the serial port to stdout
+    pub print_serial: bool,
     pub gb_type: GbTypes,
     pub gb_mode: GbMode,
     pub wram: Vec<u8>,
@@ -43,9 +57,21 @@ pub struct Gb {
     pub ram_bank: usize,
     pub ie_flag: u8,
     pub if_flag: u8,
-    pub boot_rom_enabled: u8,
+    // Mapped over the start of the cartridge ROM until 0xFF50 is written
+    pub boot_rom: Option<Vec<u8>>,
     pub prepare_speed_switch: bool,
     pub speed_mode: SpeedMode,
+    pub debugger: Debugger,
+    pub tracer: Option<Tracer>,
+    // Labels shown by the debugger, disassembly and traces
+    pub symbols: Symbols,
+    pub cheats: Cheats,
+    pub(crate) battery_save: BatterySave,
+    // Dots run since power on, and since the last frame ended
+    pub(crate) cycles: u64,
+    pub(crate) frame_cycles: u32,
+    // Sorted by cycle
+    pub(crate) input_queue: VecDeque<InputEvent>,
 }
 
 fn get_register_values(gb_mode: &GbMode, gb_type: &GbTypes) -> [u8; 8] {
@@ -63,8 +89,8 @@ fn get_register_values(gb_mode: &GbMode, gb_type: &GbTypes) -> [u8; 8] {
 
 // Values from the Cycle-Accurate Game Boy documentation
 // Pan Docs is not that detailed
-fn get_div_values(gb_type: &GbTypes, gb_mode: &GbMode) -> u8 {
-    let div_value = if gb_mode == &GbMode::CgbMode {
+fn get_div_values(gb_type: &GbTypes, gb_mode: &GbMode) -> u16 {
+    if gb_mode == &GbMode::CgbMode {
         0x1EA0
     } else {
         match gb_type {
@@ -72,33 +98,30 @@ fn get_div_values(gb_type: &GbTypes, gb_mode: &GbMode) -> u8 {
             GbTypes::Sgb => 0x0000,
             GbTypes::Cgb => 0x267C,
         }
-    };
-    (div_value >> 8) as u8
+    }
 }
 
 impl Gb {
     pub fn new(mbc: Box<dyn Mbc>, gb_mode: GbMode, gb_type: GbTypes) -> Self {
         let registers: [u8; 8] = get_register_values(&gb_mode, &gb_type);
-        let div: u8 = get_div_values(&gb_type, &gb_mode);
-        let mut wram: Vec<u8> = if gb_mode == GbMode::CgbMode {
+        let div: u16 = get_div_values(&gb_type, &gb_mode);
+        let wram: Vec<u8> = if gb_mode == GbMode::CgbMode {
             vec![0; 0x8000]
         } else {
             vec![0; 0x2000]
         };
-        let mut hram = vec![0; 0x7F];
-        let mut rng = rand::rng();
-        rng.fill_bytes(&mut wram);
-        rng.fill_bytes(&mut hram);
+        let hram = vec![0; 0x7F];
 
-        Self {
+        let mut gb = Self {
             cpu: Cpu::new(registers),
             ppu: Ppu::new(&gb_mode),
-            serial: Serial::new(),
+            serial: Serial::new(&gb_mode),
             joypad: Joypad::new(),
             sound: Sound::new(),
             timer: Timer::new(div),
             mbc,
-            gb_speed: 0,
+            link_port: None,
+            print_serial: true,
             gb_type,
             gb_mode,
             wram,
@@ -106,45 +129,150 @@ impl Gb {
             ram_bank: 1,
             ie_flag: 0,
             if_flag: 0xE1,
-            boot_rom_enabled: 0,
+            boot_rom: None,
             prepare_speed_switch: false,
             speed_mode: SpeedMode::Normal,
+            debugger: Debugger::default(),
+            tracer: None,
+            symbols: Symbols::default(),
+            cheats: Cheats::default(),
+            battery_save: BatterySave::default(),
+            cycles: 0,
+            frame_cycles: 0,
+            input_queue: VecDeque::new(),
+        };
+        gb.fill_power_on_ram(RamPattern::Random);
+        gb
+    }
+
+    /// Maps a boot ROM over the cartridge and puts the machine back into its power-on state,
+    /// so execution starts at 0x0000 and the boot ROM sets up the post-boot registers itself.
+    pub fn load_boot_rom(&mut self, boot_rom: Vec<u8>) -> Result<(), String> {
+        let expected_size = if self.gb_mode == GbMode::CgbMode {
+            0x900
+        } else {
+            0x100
+        };
+        if boot_rom.len() != expected_size {
+            return Err(format!(
+                "Boot ROM is {} bytes, expected {expected_size} bytes for this cartridge",
+                boot_rom.len()
+            ));
+        }
+
+        self.boot_rom = Some(boot_rom);
+        self.cpu = Cpu::new([0; 8]);
+        self.cpu.pc = 0x0000;
+        self.cpu.sp = 0x0000;
+        self.timer = Timer::new(0);
+        self.ppu.write_byte(0xFF40, 0x00);
+        self.if_flag = 0xE0;
+        Ok(())
+    }
+
+    pub fn read_boot_rom(&self, address: u16) -> Option<u8> {
+        let boot_rom = self.boot_rom.as_ref()?;
+        match address {
+            // The CGB boot ROM leaves a hole where the cartridge header is visible
+            0x0000..=0x00FF | 0x0200..=0x08FF => boot_rom.get(address as usize).copied(),
+            _ => None,
         }
     }
 
     pub fn headless_run(&mut self) {
-        while !self.cpu.is_stopped {
+        while !self.waiting_for_input() && !self.cpu.is_locked {
             self.run();
         }
     }
 
     pub fn run(&mut self) {
-        //self.debug_message();
-        self.handle_interrupt();
         self.cpu_tick();
         self.components_tick();
         self.print_serial_message();
+        self.debug_check_pc();
     }
 
+    /// Runs until the PPU finishes a frame, or for [`CYCLES_PER_FRAME`] dots while the LCD is
+    /// off, leaving a white frame on screen.
     pub fn run_one_frame(&mut self) {
-        while !self.ppu.frame_ready {
+        self.debugger.clear_hit();
+        // A stopped CPU only wakes up on joypad input, so give control back to the caller
+        // unless some is queued. Hitting a breakpoint ends the frame early too.
+        while !self.ppu.frame_ready && !self.waiting_for_input() && self.debugger.hit().is_none() {
+            self.run();
+        }
+    }
+
+    /// Runs whole instructions until at least `cycles` dots of the base clock went by, and
+    /// returns how many did. That can be more than asked for by the tail of the last
+    /// instruction, or less when the CPU stops or hits a breakpoint.
+    pub fn run_cycles(&mut self, cycles: u64) -> u64 {
+        self.debugger.clear_hit();
+        let start = self.cycles;
+        while self.cycles - start < cycles
+            && !self.waiting_for_input()
+            && self.debugger.hit().is_none()
+        {
             self.run();
         }
+        self.cycles - start
     }
 
-    pub fn get_screen_data(&mut self) -> &[[u8; 160]; 144] {
+    /// Dots of the base clock run since power on.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn get_screen_data(&mut self) -> &[[u16; 160]; 144] {
         self.ppu.get_screen()
     }
 
     pub fn components_tick(&mut self) {
         let cycles = self.cpu.pending_cycles as u32 * 4;
+        // The PPU and APU keep running from the base clock in double speed mode
+        let base_cycles = match self.speed_mode {
+            SpeedMode::Normal => cycles,
+            SpeedMode::Double => cycles / 2,
+        };
         self.dma_tick(cycles);
-        self.ppu_tick(cycles);
+        self.ppu_tick(base_cycles);
         self.timer_tick(cycles);
-        self.sound.tick(cycles);
+        self.serial_tick(cycles);
+        self.sound.tick(base_cycles);
+        self.mbc.tick(base_cycles);
+        self.autosave_tick(base_cycles);
+        self.cycles += u64::from(base_cycles);
+        self.apply_queued_input();
+        // The PPU ends frames while the LCD is on, without it they still last as long
+        self.frame_cycles += base_cycles;
+        if !self.ppu.lcd_enabled() && self.frame_cycles >= CYCLES_PER_FRAME {
+            self.frame_cycles -= CYCLES_PER_FRAME;
+            self.ppu.blank_frame();
+        }
+        if let Some(tracer) = &mut self.tracer {
+            tracer.add_cycles(cycles);
+        }
         self.cpu.pending_cycles = 0;
     }
 
+    pub fn get_speed_mode(&self) -> SpeedMode {
+        self.speed_mode
+    }
+
+    pub fn is_double_speed(&self) -> bool {
+        self.speed_mode == SpeedMode::Double
+    }
+
+    pub fn switch_speed(&mut self) {
+        self.prepare_speed_switch = false;
+        self.speed_mode = match self.speed_mode {
+            SpeedMode::Normal => SpeedMode::Double,
+            SpeedMode::Double => SpeedMode::Normal,
+        };
+        // The CPU sits idle for 2050 M-cycles while the clock settles
+        self.cpu.stall_cycles += 2050;
+    }
+
     pub fn get_audio_buffer(&self) -> Vec<f32> {
         self.sound.get_audio_buffer()
     }
@@ -154,58 +282,107 @@ impl Gb {
     }
 
     fn cpu_tick(&mut self) {
-        if self.cpu.is_halted {
+        if self.cpu.stall_cycles > 0 {
+            self.cpu.stall_cycles -= 1;
             self.cpu.pending_cycles += 1;
             return;
         }
 
+        if self.cpu.is_stopped {
+            return;
+        }
+
+        // Interrupts are checked at the end of the opcode fetch, a halted CPU keeps
+        // running fetch cycles without executing anything
+        self.cpu.bus_cycles = 0;
+        self.m_cycle();
+        if self.handle_interrupt() || self.cpu.is_halted || self.cpu.is_locked {
+            return;
+        }
+
+        if self.tracer.is_some() {
+            self.trace_instruction();
+        }
         self.cpu.current_instruction = self.read_byte(self.cpu.pc);
 
         self.decode();
-        self.cpu.pending_cycles += self.cpu.instruction_cycles;
+        // Bus accesses already ran their cycles, what is left are the internal ones at the end
+        self.cpu.pending_cycles += self
+            .cpu
+            .instruction_cycles
+            .saturating_sub(self.cpu.bus_cycles);
+    }
+
+    /// Runs the rest of the machine for one M-cycle in the middle of an instruction.
+    pub fn m_cycle(&mut self) {
+        self.cpu.bus_cycles += 1;
+        self.cpu.pending_cycles += 1;
+        self.components_tick();
     }
 
     fn print_serial_message(&mut self) {
         if let Some(message) = self.serial.get_serial_message() {
-            println!("{}", message)
+            if self.print_serial {
+                println!("{}", message)
+            }
         };
     }
 
-    fn debug_message(&self) {
-        println!(
-            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X} LY:{:02X} STAT: {:02X}",
-            self.cpu.a(),
-            self.cpu.f(),
-            self.cpu.b(),
-            self.cpu.c(),
-            self.cpu.d(),
-            self.cpu.e(),
-            self.cpu.h(),
-            self.cpu.l(),
-            self.cpu.sp,
-            self.cpu.pc,
-            self.read_byte(self.cpu.pc),
-            self.read_byte(self.cpu.pc.wrapping_add(1)),
-            self.read_byte(self.cpu.pc.wrapping_add(2)),
-            self.read_byte(self.cpu.pc.wrapping_add(3)),
-            self.ppu.get_ly(),
-            self.ppu.stat,
-        );
-    }
-
     pub fn press_button(&mut self, button: JoypadButton) {
-        self.joypad.press(button);
+        self.update_joypad(|joypad| joypad.press(button));
     }
 
     pub fn release_button(&mut self, button: JoypadButton) {
-        self.joypad.release(button);
+        self.update_joypad(|joypad| joypad.release(button));
     }
 
-    pub fn get_battery_ram(&self) -> Option<&[u8]> {
+    pub fn get_battery_ram(&self) -> Option<Vec<u8>> {
         self.mbc.get_battery_ram()
     }
 
     pub fn set_battery_ram(&mut self, data: &[u8]) {
         self.mbc.set_battery_ram(data);
+        self.mark_save_dirty();
+    }
+
+    pub fn set_rtc_mode(&mut self, mode: RtcMode) {
+        self.mbc.set_rtc_mode(mode);
+    }
+
+    /// Sets where the Pocket Camera gets its pictures from, ignored by other cartridges.
+    pub fn set_camera_source(&mut self, source: Box<dyn CameraSource>) {
+        self.mbc.set_camera_source(source);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::prelude::GbInstance;
+
+    #[test]
+    fn test_frames_with_lcd_off() {
+        // LD A,0 / LDH (LCDC),A, then an endless JR -2
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x106].copy_from_slice(&[0x3E, 0x00, 0xE0, 0x40, 0x18, 0xFE]);
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb = GbInstance::build_from_bytes(rom, None, None, options).unwrap();
+
+        gb.run_one_frame();
+        gb.get_screen_data();
+        let start = gb.cycles();
+        gb.run_one_frame();
+        assert_eq!(gb.cycles() - start, u64::from(CYCLES_PER_FRAME));
+        assert!(gb
+            .get_screen_data()
+            .iter()
+            .flatten()
+            .all(|&pixel| pixel == 0));
+
+        let ran = gb.run_cycles(1001);
+        assert!((1001..1001 + 12).contains(&ran));
     }
 }
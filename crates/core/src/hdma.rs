@@ -1,15 +1,26 @@
 // SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
 //
 // SPDX-License-Identifier: MIT
+use super::gb::{Gb, SpeedMode};
 use super::memory::Memory;
 
+#[derive(Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum HdmaMode {
+    GeneralPurpose,
+    HBlank,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Hdma {
     hdma1: u8,
     hdma2: u8,
     hdma3: u8,
     hdma4: u8,
-    hdma5: u8,
+    // Number of 16 byte blocks left to copy, minus one
+    remaining: u8,
+    mode: HdmaMode,
     enabled: bool,
+    pub pending_gdma: bool,
 }
 
 impl Hdma {
@@ -19,8 +30,10 @@ impl Hdma {
             hdma2: 0xFF,
             hdma3: 0xFF,
             hdma4: 0xFF,
-            hdma5: 0xFF,
+            remaining: 0x7F,
+            mode: HdmaMode::GeneralPurpose,
             enabled: false,
+            pending_gdma: false,
         }
     }
 
@@ -31,12 +44,43 @@ impl Hdma {
     pub fn get_hdma_destination(&self) -> u16 {
         ((self.hdma3 as u16) << 8) | (self.hdma4 as u16) | 0x8000
     }
+
+    pub fn hblank_active(&self) -> bool {
+        self.enabled && self.mode == HdmaMode::HBlank
+    }
+
+    fn advance_block(&mut self) {
+        let source = self.get_hdma_source().wrapping_add(0x10);
+        let destination = self.get_hdma_destination().wrapping_add(0x10);
+        self.hdma1 = (source >> 8) as u8;
+        self.hdma2 = source as u8;
+        self.hdma3 = ((destination >> 8) as u8) & 0x1F;
+        self.hdma4 = destination as u8;
+
+        // The transfer ends once the destination runs past the end of VRAM
+        if self.remaining == 0 || destination >= 0xA000 {
+            self.enabled = false;
+            self.remaining = 0x7F;
+        } else {
+            self.remaining -= 1;
+        }
+    }
 }
 
 impl Memory for Hdma {
-    #[allow(unused_variables)]
     fn read_byte(&self, address: u16) -> u8 {
-        0xFF // TCAGB says it always returns 0xFF when read
+        match address {
+            // TCAGB says the source and destination registers always return 0xFF when read
+            0xFF51..=0xFF54 => 0xFF,
+            0xFF55 => {
+                if self.enabled {
+                    self.remaining
+                } else {
+                    0x80 | self.remaining
+                }
+            }
+            _ => unreachable!(),
+        }
     }
 
     fn write_byte(&mut self, address: u16, value: u8) {
@@ -45,8 +89,102 @@ impl Memory for Hdma {
             0xFF52 => self.hdma2 = value & 0xF0,
             0xFF53 => self.hdma3 = value & 0x1F,
             0xFF54 => self.hdma4 = value & 0xF0,
-            0xFF55 => self.hdma5 = value,
+            0xFF55 => {
+                if self.hblank_active() && value & 0x80 == 0 {
+                    // Cancelling an HBlank transfer keeps the remaining length readable
+                    self.enabled = false;
+                    return;
+                }
+
+                self.remaining = value & 0x7F;
+                self.enabled = true;
+                if value & 0x80 != 0 {
+                    self.mode = HdmaMode::HBlank;
+                } else {
+                    self.mode = HdmaMode::GeneralPurpose;
+                    self.pending_gdma = true;
+                }
+            }
             _ => unreachable!(),
         };
     }
 }
+
+impl Gb {
+    pub fn hdma_start(&mut self) {
+        if !self.ppu.hdma.pending_gdma {
+            // HBlank DMA started while the LCD is off copies its first block right away
+            if self.ppu.hdma.hblank_active() && !self.ppu.lcd_enabled() {
+                self.hdma_transfer_block();
+            }
+            return;
+        }
+
+        self.ppu.hdma.pending_gdma = false;
+        while self.ppu.hdma.enabled {
+            self.hdma_transfer_block();
+        }
+    }
+
+    pub fn hdma_hblank(&mut self) {
+        if self.ppu.hdma.hblank_active() {
+            self.hdma_transfer_block();
+        }
+    }
+
+    fn hdma_transfer_block(&mut self) {
+        let source = self.ppu.hdma.get_hdma_source();
+        let destination = self.ppu.hdma.get_hdma_destination();
+        for i in 0..0x10 {
+            let value = self.read_byte(source.wrapping_add(i));
+            self.ppu
+                .write_vram_dma(0x8000 | (destination.wrapping_add(i) & 0x1FFF), value);
+        }
+        self.ppu.hdma.advance_block();
+
+        // The CPU is stalled for the same amount of real time in both speed modes
+        self.cpu.stall_cycles += match self.speed_mode {
+            SpeedMode::Normal => 8,
+            SpeedMode::Double => 16,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cartridge::{load_cartridge, CartridgeOptions};
+    use crate::gb::{Gb, GbTypes};
+    use crate::storage::MemoryStorage;
+
+    fn setup_gb() -> Gb {
+        let (mbc, gb_mode) = load_cartridge(
+            "../../roms/cpu_instrs.gb",
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        )
+        .unwrap();
+        Gb::new(mbc, gb_mode, GbTypes::Cgb)
+    }
+
+    #[test]
+    fn test_hdma_stops_at_end_of_vram() {
+        let mut gb = setup_gb();
+        gb.write_byte(0xFF40, 0x00);
+        gb.write_byte(0x8000, 0xAA);
+        for i in 0..0x20 {
+            gb.write_byte(0xC000 + i, 0x55);
+        }
+
+        gb.write_byte(0xFF51, 0xC0);
+        gb.write_byte(0xFF52, 0x00);
+        gb.write_byte(0xFF53, 0x1F);
+        gb.write_byte(0xFF54, 0xF0);
+        // Two blocks, only the first one fits before 0xA000
+        gb.write_byte(0xFF55, 0x01);
+
+        assert_eq!(gb.read_byte(0x9FF0), 0x55);
+        assert_eq!(gb.read_byte(0x9FFF), 0x55);
+        assert_eq!(gb.read_byte(0x8000), 0xAA);
+        assert_eq!(gb.read_byte(0xFF55), 0xFF);
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Side-effect-free access to the physical memories behind the bus, for memory viewers
+//! and other tools. Unlike [`Gb::read_byte`], every bank can be reached regardless of
+//! what is currently mapped, and nothing is ticked or logged to the debugger.
+
+use crate::gb::Gb;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryRegion {
+    Rom,
+    Vram,
+    CartridgeRam,
+    Wram,
+    Oam,
+    Hram,
+}
+
+impl MemoryRegion {
+    pub const ALL: [MemoryRegion; 6] = [
+        MemoryRegion::Rom,
+        MemoryRegion::Vram,
+        MemoryRegion::CartridgeRam,
+        MemoryRegion::Wram,
+        MemoryRegion::Oam,
+        MemoryRegion::Hram,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MemoryRegion::Rom => "ROM",
+            MemoryRegion::Vram => "VRAM",
+            MemoryRegion::CartridgeRam => "Cartridge RAM",
+            MemoryRegion::Wram => "WRAM",
+            MemoryRegion::Oam => "OAM",
+            MemoryRegion::Hram => "HRAM",
+        }
+    }
+
+    pub fn bank_size(self) -> usize {
+        match self {
+            MemoryRegion::Rom => 0x4000,
+            MemoryRegion::Vram | MemoryRegion::CartridgeRam => 0x2000,
+            MemoryRegion::Wram => 0x1000,
+            MemoryRegion::Oam => 0xA0,
+            MemoryRegion::Hram => 0x7F,
+        }
+    }
+
+    /// Address where `bank` shows up in the CPU's address space when it is mapped.
+    pub fn base_address(self, bank: usize) -> u16 {
+        match self {
+            MemoryRegion::Rom if bank == 0 => 0x0000,
+            MemoryRegion::Rom => 0x4000,
+            MemoryRegion::Vram => 0x8000,
+            MemoryRegion::CartridgeRam => 0xA000,
+            MemoryRegion::Wram if bank == 0 => 0xC000,
+            MemoryRegion::Wram => 0xD000,
+            MemoryRegion::Oam => 0xFE00,
+            MemoryRegion::Hram => 0xFF80,
+        }
+    }
+}
+
+impl Gb {
+    pub fn bank_count(&self, region: MemoryRegion) -> usize {
+        let size = match region {
+            MemoryRegion::Rom => self.mbc.rom().len(),
+            MemoryRegion::Vram => self.ppu.vram.len(),
+            MemoryRegion::CartridgeRam => self.mbc.ram().len(),
+            MemoryRegion::Wram => self.wram.len(),
+            MemoryRegion::Oam | MemoryRegion::Hram => return 1,
+        };
+        size.div_ceil(region.bank_size())
+    }
+
+    /// Reads `offset` inside `bank` of `region`. Out of range accesses read 0xFF.
+    pub fn peek(&self, region: MemoryRegion, bank: usize, offset: u16) -> u8 {
+        if offset as usize >= region.bank_size() {
+            return 0xFF;
+        }
+        let index = bank * region.bank_size() + offset as usize;
+        match region {
+            MemoryRegion::Rom => self.mbc.peek_rom(bank, offset),
+            MemoryRegion::CartridgeRam => self.mbc.peek_ram(bank, offset),
+            MemoryRegion::Vram => self.ppu.vram.get(index).copied().unwrap_or(0xFF),
+            MemoryRegion::Wram => self.wram.get(index).copied().unwrap_or(0xFF),
+            MemoryRegion::Oam => self.ppu.oam.get(index).copied().unwrap_or(0xFF),
+            MemoryRegion::Hram => self.hram.get(index).copied().unwrap_or(0xFF),
+        }
+    }
+
+    /// Writes `offset` inside `bank` of `region`. The ROM is read-only and out of range
+    /// writes are ignored.
+    pub fn poke(&mut self, region: MemoryRegion, bank: usize, offset: u16, value: u8) {
+        if offset as usize >= region.bank_size() {
+            return;
+        }
+        let index = bank * region.bank_size() + offset as usize;
+        let byte = match region {
+            MemoryRegion::Rom => return,
+            MemoryRegion::CartridgeRam => {
+                self.mbc.poke_ram(bank, offset, value);
+                return self.mark_save_dirty();
+            }
+            MemoryRegion::Vram => self.ppu.vram.get_mut(index),
+            MemoryRegion::Wram => self.wram.get_mut(index),
+            MemoryRegion::Oam => self.ppu.oam.get_mut(index),
+            MemoryRegion::Hram => self.hram.get_mut(index),
+        };
+        if let Some(byte) = byte {
+            *byte = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::prelude::GbInstance;
+
+    #[test]
+    fn test_peek_and_poke_unmapped_banks() {
+        // MBC5 with 4 ROM banks and 4 RAM banks
+        let mut rom = vec![0u8; 0x10000];
+        rom[0x147] = 0x1A;
+        rom[0x148] = 0x01;
+        rom[0x149] = 0x03;
+        rom[0x3 * 0x4000 + 0x10] = 0x42;
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb = GbInstance::build_from_bytes(rom, None, None, options).unwrap();
+
+        assert_eq!(gb.bank_count(MemoryRegion::Rom), 4);
+        assert_eq!(gb.bank_count(MemoryRegion::CartridgeRam), 4);
+        assert_eq!(gb.peek(MemoryRegion::Rom, 3, 0x10), 0x42);
+        assert_eq!(gb.peek(MemoryRegion::Rom, 4, 0x10), 0xFF);
+
+        gb.poke(MemoryRegion::CartridgeRam, 2, 0x100, 0x99);
+        assert_eq!(gb.peek(MemoryRegion::CartridgeRam, 2, 0x100), 0x99);
+        assert_eq!(gb.peek(MemoryRegion::CartridgeRam, 0, 0x100), 0x00);
+
+        gb.poke(MemoryRegion::Hram, 0, 0x05, 0x12);
+        assert_eq!(gb.read_byte(0xFF85), 0x12);
+    }
+}
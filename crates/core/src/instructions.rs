@@ -4,6 +4,7 @@
 
 use pastey::paste;
 
+use crate::cartridge::GbMode;
 use crate::gb::Gb;
 
 //  M-CYCLES
@@ -244,7 +245,7 @@ impl Gb {
             0xD0 => self.ret_cc(opcode),
             0xD1 => self.pop_de(),
             0xD2 => self.jp_cc(opcode),
-            0xD3 => self.ill(opcode),
+            0xD3 => self.ill(),
             0xD4 => self.call_cc(opcode),
             0xD5 => self.push_de(),
             0xD6 => self.sub_a_d8(),
@@ -252,32 +253,32 @@ impl Gb {
             0xD8 => self.ret_cc(opcode),
             0xD9 => self.reti(),
             0xDA => self.jp_cc(opcode),
-            0xDB => self.ill(opcode),
+            0xDB => self.ill(),
             0xDC => self.call_cc(opcode),
-            0xDD => self.ill(opcode),
+            0xDD => self.ill(),
             0xDE => self.sbc_a_d8(),
             0xDF => self.rst(opcode),
             0xE0 => self.ldh_a8_a(),
             0xE1 => self.pop_hl(),
             0xE2 => self.ld_dc_a(),
-            0xE3 => self.ill(opcode),
-            0xE4 => self.ill(opcode),
+            0xE3 => self.ill(),
+            0xE4 => self.ill(),
             0xE5 => self.push_hl(),
             0xE6 => self.and_a_d8(),
             0xE7 => self.rst(opcode),
             0xE8 => self.add_sp_r8(),
             0xE9 => self.jp_hl(),
             0xEA => self.ld_a16_a(),
-            0xEB => self.ill(opcode),
-            0xEC => self.ill(opcode),
-            0xED => self.ill(opcode),
+            0xEB => self.ill(),
+            0xEC => self.ill(),
+            0xED => self.ill(),
             0xEE => self.xor_a_d8(),
             0xEF => self.rst(opcode),
             0xF0 => self.ldh_a_a8(),
             0xF1 => self.pop_af(),
             0xF2 => self.ld_a_dc(),
             0xF3 => self.di(),
-            0xF4 => self.ill(opcode),
+            0xF4 => self.ill(),
             0xF5 => self.push_af(),
             0xF6 => self.or_a_d8(),
             0xF7 => self.rst(opcode),
@@ -285,8 +286,8 @@ impl Gb {
             0xF9 => self.ld_sp_hl(),
             0xFA => self.ld_a_da16(),
             0xFB => self.ei(),
-            0xFC => self.ill(opcode),
-            0xFD => self.ill(opcode),
+            0xFC => self.ill(),
+            0xFD => self.ill(),
             0xFE => self.cp_d8(),
             0xFF => self.rst(opcode),
             _ => unreachable!(),
@@ -308,14 +309,24 @@ impl Gb {
         self.cpu.advance_pc();
     }
 
-    fn ill(&mut self, opcode: u8) {
-        panic!("ILLEGAL OPCODE: {opcode:04X}");
+    // Illegal opcodes hang the CPU for good, not even interrupts bring it back
+    fn ill(&mut self) {
+        self.cpu.is_locked = true;
     }
 
     fn stop(&mut self) {
-        // TODO: implement this! (Or not)
-        //self.cpu.is_stopped = true;
         self.cpu.advance_pc();
+        self.timer_write(0xFF04, 0);
+
+        if self.prepare_speed_switch && self.gb_mode == GbMode::CgbMode {
+            self.switch_speed();
+            return;
+        }
+
+        // Holding a selected button keeps the hardware from entering low-power mode
+        if !self.joypad.any_selected_pressed() {
+            self.cpu.is_stopped = true;
+        }
     }
 
     fn halt(&mut self) {
@@ -329,14 +340,14 @@ impl Gb {
     }
 
     fn ld_sp_d16(&mut self) {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
+        let value = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
         self.cpu.sp = value;
         self.cpu.advance_pc();
     }
 
     fn ld_dhl_d8(&mut self) {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
-        self.write_byte(self.cpu.hl(), value);
+        let value = self.cpu_read(self.cpu.pc.wrapping_add(1));
+        self.cpu_write(self.cpu.hl(), value);
         self.cpu.advance_pc();
     }
 
@@ -375,14 +386,14 @@ impl Gb {
     }
 
     fn ld_da16_sp(&mut self) {
-        let address: u16 = self.read_word(self.cpu.pc.wrapping_add(1));
-        self.write_word(address, self.cpu.sp);
+        let address: u16 = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
+        self.cpu_write_word(address, self.cpu.sp);
         self.cpu.advance_pc();
     }
 
     fn ld_a_hli(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         self.cpu.set_a(value);
         self.cpu.set_hl(hl.wrapping_add(1));
         self.cpu.advance_pc();
@@ -390,7 +401,7 @@ impl Gb {
 
     fn ld_a_hld(&mut self) {
         let hl = self.cpu.hl();
-        let value = self.read_byte(hl);
+        let value = self.cpu_read(hl);
         self.cpu.set_a(value);
         self.cpu.set_hl(hl.wrapping_sub(1));
         self.cpu.advance_pc();
@@ -421,7 +432,7 @@ impl Gb {
     }
 
     fn jr_r8(&mut self) {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1)) as i8;
+        let value = self.cpu_read(self.cpu.pc.wrapping_add(1)) as i8;
         self.cpu.pc = self.cpu.pc.wrapping_add(2);
         self.cpu.pc = ((self.cpu.pc as u32 as i32) + (value as i32)) as u16;
     }
@@ -486,7 +497,7 @@ impl Gb {
     fn ld_dhli_a(&mut self) {
         let hl = self.cpu.hl();
         let a = self.cpu.a();
-        self.write_byte(hl, a);
+        self.cpu_write(hl, a);
         self.cpu.set_hl(hl.wrapping_add(1));
         self.cpu.advance_pc();
     }
@@ -494,16 +505,16 @@ impl Gb {
     fn ld_dhld_a(&mut self) {
         let hl = self.cpu.hl();
         let a = self.cpu.a();
-        self.write_byte(hl, a);
+        self.cpu_write(hl, a);
         self.cpu.set_hl(hl.wrapping_sub(1));
         self.cpu.advance_pc();
     }
 
     fn inc_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let v = self.read_byte(hl);
+        let v = self.cpu_read(hl);
         let value = v.wrapping_add(1);
-        self.write_byte(hl, value);
+        self.cpu_write(hl, value);
         self.cpu.set_zero(value == 0);
         self.cpu.set_negative(false);
         self.cpu.set_half_carry((v & 0x0F) + 1 > 0x0F);
@@ -512,9 +523,9 @@ impl Gb {
 
     fn dec_dhl(&mut self) {
         let hl = self.cpu.hl();
-        let v = self.read_byte(hl);
+        let v = self.cpu_read(hl);
         let value = v.wrapping_sub(1);
-        self.write_byte(hl, value);
+        self.cpu_write(hl, value);
         self.cpu.set_zero(value == 0);
         self.cpu.set_negative(true);
         self.cpu.set_half_carry((v & 0x0F) == 0);
@@ -611,13 +622,25 @@ impl Gb {
         self.cpu.advance_pc();
     }
 
+    // The stack pointer is decremented in an internal cycle before the high byte goes out
+    pub fn push_word(&mut self, value: u16) {
+        self.m_cycle();
+        self.cpu.sp = self.cpu.sp.wrapping_sub(1);
+        self.cpu_write(self.cpu.sp, (value >> 8) as u8);
+        self.cpu.sp = self.cpu.sp.wrapping_sub(1);
+        self.cpu_write(self.cpu.sp, (value & 0xFF) as u8);
+    }
+
     fn ret(&mut self) {
-        let value = self.read_word(self.cpu.sp);
+        let value = self.cpu_read_word(self.cpu.sp);
         self.cpu.sp = self.cpu.sp.wrapping_add(2);
         self.cpu.pc = value;
+        self.debug_return();
     }
 
     fn ret_cc(&mut self, opcode: u8) {
+        // Checking the condition takes a cycle of its own before the stack is read
+        self.m_cycle();
         if self.get_flag_condition(opcode) {
             self.cpu.instruction_cycles = 5;
             self.ret();
@@ -632,7 +655,7 @@ impl Gb {
     }
 
     fn jp(&mut self) {
-        let value = self.read_word(self.cpu.pc.wrapping_add(1));
+        let value = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
         self.cpu.pc = value;
     }
 
@@ -646,9 +669,11 @@ impl Gb {
     }
 
     fn call_a16(&mut self) {
-        self.cpu.sp = self.cpu.sp.wrapping_sub(2);
-        self.write_word(self.cpu.sp, self.cpu.pc.wrapping_add(3));
-        self.cpu.pc = self.read_word(self.cpu.pc.wrapping_add(1));
+        let call_site = self.cpu.pc;
+        let address = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
+        self.push_word(self.cpu.pc.wrapping_add(3));
+        self.cpu.pc = address;
+        self.debug_call(call_site, false);
     }
 
     fn call_cc(&mut self, opcode: u8) {
@@ -661,13 +686,14 @@ impl Gb {
     }
 
     fn rst(&mut self, opcode: u8) {
-        self.cpu.sp = self.cpu.sp.wrapping_sub(2);
-        self.write_word(self.cpu.sp, self.cpu.pc.wrapping_add(1));
+        let call_site = self.cpu.pc;
+        self.push_word(self.cpu.pc.wrapping_add(1));
         self.cpu.pc = (opcode as u16) ^ 0xC7;
+        self.debug_call(call_site, false);
     }
 
     fn cp_d8(&mut self) {
-        let r = self.read_byte(self.cpu.pc.wrapping_add(1));
+        let r = self.cpu_read(self.cpu.pc.wrapping_add(1));
         let a = self.cpu.a();
         self.cpu.set_zero(a == r);
         self.cpu.set_negative(true);
@@ -687,8 +713,8 @@ impl Gb {
     }
 
     fn ld_a_da16(&mut self) {
-        let address = self.read_word(self.cpu.pc.wrapping_add(1));
-        let value = self.read_byte(address);
+        let address = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
+        let value = self.cpu_read(address);
         self.cpu.set_a(value);
         self.cpu.advance_pc();
     }
@@ -700,7 +726,7 @@ impl Gb {
     }
 
     fn ld_hl_sp_r8(&mut self) {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1)) as i8 as i16 as u16;
+        let value = self.cpu_read(self.cpu.pc.wrapping_add(1)) as i8 as i16 as u16;
         let sp_r8 = self.cpu.sp.wrapping_add(value);
         self.cpu.set_hl(sp_r8);
         self.cpu.set_zero(false);
@@ -713,7 +739,7 @@ impl Gb {
     }
 
     fn add_sp_r8(&mut self) {
-        let value = self.read_byte(self.cpu.pc.wrapping_add(1)) as i8 as i16 as u16;
+        let value = self.cpu_read(self.cpu.pc.wrapping_add(1)) as i8 as i16 as u16;
         let sp_r8 = self.cpu.sp.wrapping_add(value);
         self.cpu.set_zero(false);
         self.cpu.set_negative(false);
@@ -733,7 +759,7 @@ impl Gb {
     fn ld_a_dc(&mut self) {
         let c = self.cpu.c() as u16;
         let address = 0xFF00 | c;
-        let value = self.read_byte(address);
+        let value = self.cpu_read(address);
         self.cpu.set_a(value);
         self.cpu.advance_pc();
     }
@@ -742,30 +768,30 @@ impl Gb {
         let c = self.cpu.c() as u16;
         let address = 0xFF00 | c;
         let a = self.cpu.a();
-        self.write_byte(address, a);
+        self.cpu_write(address, a);
         self.cpu.advance_pc();
     }
 
     fn ldh_a_a8(&mut self) {
-        let r = self.read_byte(self.cpu.pc.wrapping_add(1)) as u16;
+        let r = self.cpu_read(self.cpu.pc.wrapping_add(1)) as u16;
         let address = 0xFF00 | r;
-        let value = self.read_byte(address);
+        let value = self.cpu_read(address);
         self.cpu.set_a(value);
         self.cpu.advance_pc();
     }
 
     fn ldh_a8_a(&mut self) {
-        let r = self.read_byte(self.cpu.pc.wrapping_add(1)) as u16;
+        let r = self.cpu_read(self.cpu.pc.wrapping_add(1)) as u16;
         let address = 0xFF00 | r;
         let a = self.cpu.a();
-        self.write_byte(address, a);
+        self.cpu_write(address, a);
         self.cpu.advance_pc();
     }
 
     fn ld_a16_a(&mut self) {
-        let address = self.read_word(self.cpu.pc.wrapping_add(1));
+        let address = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
         let a = self.cpu.a();
-        self.write_byte(address, a);
+        self.cpu_write(address, a);
         self.cpu.advance_pc();
     }
 }
@@ -800,7 +826,7 @@ macro_rules! create_alu_ld_word_instructions {
                     }
 
                     fn [<ld_ $rr _d16>](&mut self) {
-                        let value = self.read_word(self.cpu.pc.wrapping_add(1));
+                        let value = self.cpu_read_word(self.cpu.pc.wrapping_add(1));
                         self.cpu.[<set_ $rr>](value);
                         self.cpu.advance_pc();
                     }
@@ -876,7 +902,7 @@ macro_rules! create_byte_instructions {
 
                     fn [<ld_ $r _dhl>](&mut self) {
                         let hl = self.cpu.hl();
-                        let value = self.read_byte(hl);
+                        let value = self.cpu_read(hl);
                         self.cpu.[<set_ $r>](value);
                         self.cpu.advance_pc();
                     }
@@ -884,12 +910,12 @@ macro_rules! create_byte_instructions {
                     fn [<ld_dhl_ $r>](&mut self) {
                         let value = self.cpu.$r();
                         let hl = self.cpu.hl();
-                        self.write_byte(hl, value);
+                        self.cpu_write(hl, value);
                         self.cpu.advance_pc();
                     }
 
                     fn [<ld_ $r _d8>](&mut self) {
-                        let value = self.read_byte(self.cpu.pc.wrapping_add(1));
+                        let value = self.cpu_read(self.cpu.pc.wrapping_add(1));
                         self.cpu.[<set_ $r>](value);
                         self.cpu.advance_pc();
                     }
@@ -965,7 +991,7 @@ macro_rules! create_push_pop_instructions {
             paste! {
                 $(
                     fn [<pop_ $rr>](&mut self) {
-                        let value = self.read_word(self.cpu.sp);
+                        let value = self.cpu_read_word(self.cpu.sp);
                         self.cpu.sp = self.cpu.sp.wrapping_add(2);
                         self.cpu.[<set_ $rr>](value);
                         self.cpu.advance_pc();
@@ -973,8 +999,7 @@ macro_rules! create_push_pop_instructions {
 
                     fn [<push_ $rr>](&mut self) {
                         let r = self.cpu.$rr();
-                        self.cpu.sp = self.cpu.sp.wrapping_sub(2);
-                        self.write_word(self.cpu.sp, r);
+                        self.push_word(r);
                         self.cpu.advance_pc();
                     }
                 )*
@@ -991,13 +1016,13 @@ macro_rules! create_ld_rra_instructions {
                     fn [<ld_ $rr _a>](&mut self) {
                         let a = self.cpu.a();
                         let address = self.cpu.$rr();
-                        self.write_byte(address, a);
+                        self.cpu_write(address, a);
                         self.cpu.advance_pc();
                     }
 
                     fn [<ld_a_ $rr>](&mut self) {
                         let r = self.cpu.$rr();
-                        let value = self.read_byte(r);
+                        let value = self.cpu_read(r);
                         self.cpu.set_a(value);
                         self.cpu.advance_pc();
                     }
@@ -1013,7 +1038,7 @@ macro_rules! create_alu_d8_instructions {
             paste! {
                 $(
                     fn [<$alu _a_d8>](&mut self) {
-                        let d8 = self.read_byte(self.cpu.pc.wrapping_add(1));
+                        let d8 = self.cpu_read(self.cpu.pc.wrapping_add(1));
                         self.[<alu_ $alu>](d8);
                     }
                 )*
@@ -1029,7 +1054,7 @@ macro_rules! create_alu_dhl_instructions {
                 $(
                     fn [<$alu _dhl>](&mut self) {
                         let hl = self.cpu.hl();
-                        let r = self.read_byte(hl);
+                        let r = self.cpu_read(hl);
                         self.[<alu_ $alu>](r);
                     }
                 )*
@@ -1047,13 +1072,19 @@ create_alu_dhl_instructions!(or, xor, and, add, adc, sub, sbc, cp);
 
 #[cfg(test)]
 mod test {
-    use crate::cartridge::load_cartridge;
+    use crate::cartridge::{load_cartridge, CartridgeOptions};
     use crate::gb::{Gb, GbTypes};
+    use crate::storage::MemoryStorage;
 
     use super::*;
 
     fn setup_gb() -> Gb {
-        let (mbc, gb_mode) = load_cartridge("../../roms/cpu_instrs.gb").unwrap();
+        let (mbc, gb_mode) = load_cartridge(
+            "../../roms/cpu_instrs.gb",
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        )
+        .unwrap();
         let gb_type = GbTypes::Dmg;
         Gb::new(mbc, gb_mode, gb_type)
     }
@@ -1203,4 +1234,19 @@ mod test {
 
     test_ld_instructions!(a, b, c, d, e, h, l);
     test_ld_dhl_instructions!(a, b, c, d, e);
+
+    #[test]
+    fn test_illegal_opcode_locks_cpu() {
+        let mut gb = setup_gb();
+        gb.write_byte(0xC000, 0xD3);
+        gb.cpu.pc = 0xC000;
+        gb.ie_flag = 0x1F;
+        gb.if_flag = 0x1F;
+        gb.cpu.interrupt_master = true;
+        for _ in 0..1000 {
+            gb.run();
+        }
+        assert!(gb.cpu.is_locked);
+        assert_eq!(gb.cpu.pc, 0xC000);
+    }
 }
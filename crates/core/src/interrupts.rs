@@ -10,7 +10,6 @@ pub enum Interrupt {
     Timer = 0x04,
     Serial = 0x08,
     Joypad = 0x10,
-    Invalid,
 }
 
 impl Gb {
@@ -18,17 +17,23 @@ impl Gb {
         self.if_flag |= interrupt as u8;
     }
 
-    pub fn handle_interrupt(&mut self) {
+    /// Checked at the end of each opcode fetch, returns true when an interrupt was
+    /// dispatched in place of the fetched instruction.
+    pub fn handle_interrupt(&mut self) -> bool {
+        if self.cpu.is_locked {
+            return false;
+        }
+
         if self.cpu.ime_toggle {
             self.cpu.ime_toggle = false;
             self.cpu.interrupt_master = true;
-            return;
+            return false;
         }
 
         if self.cpu.d_ime_toggle {
             self.cpu.d_ime_toggle = false;
             self.cpu.interrupt_master = false;
-            return;
+            return false;
         }
 
         let pending = self.ie_flag & self.if_flag & 0x1F;
@@ -38,33 +43,48 @@ impl Gb {
         }
 
         if !self.cpu.interrupt_master || pending == 0 {
-            return;
+            return false;
         }
 
+        // The discarded fetch and an idle cycle, pushing PC takes two more and the jump to
+        // the vector the last one
         self.cpu.interrupt_master = false;
-        self.cpu.sp = self.cpu.sp.wrapping_sub(2);
-        self.write_word(self.cpu.sp, self.cpu.pc);
-        self.cpu.pending_cycles += 5;
-        let interrupt_source = self.get_interrupt_source();
-        let address = self.go_interrupt(&interrupt_source);
+        let call_site = self.cpu.pc;
+        self.m_cycle();
+        self.cpu.sp = self.cpu.sp.wrapping_sub(1);
+        self.cpu_write(self.cpu.sp, (call_site >> 8) as u8);
+        // The vector is only picked now, so pushing the high byte into IE can cancel the
+        // interrupt, and with nothing left pending the CPU jumps to 0x0000
+        let address = match self.get_interrupt_source() {
+            Some(interrupt_source) => {
+                let address = self.go_interrupt(&interrupt_source);
+                self.if_flag &= !(interrupt_source as u8);
+                address
+            }
+            None => 0x0000,
+        };
+        self.cpu.sp = self.cpu.sp.wrapping_sub(1);
+        self.cpu_write(self.cpu.sp, call_site as u8);
+        self.cpu.pending_cycles += 1;
         self.cpu.pc = address;
-        self.if_flag &= !(interrupt_source as u8);
+        self.debug_call(call_site, true);
+        true
     }
 
-    fn get_interrupt_source(&mut self) -> Interrupt {
+    fn get_interrupt_source(&mut self) -> Option<Interrupt> {
         let pending = self.ie_flag & self.if_flag & 0x1F;
         if pending & (Interrupt::Vblank as u8) != 0 {
-            Interrupt::Vblank
+            Some(Interrupt::Vblank)
         } else if pending & (Interrupt::LcdStat as u8) != 0 {
-            Interrupt::LcdStat
+            Some(Interrupt::LcdStat)
         } else if pending & (Interrupt::Timer as u8) != 0 {
-            Interrupt::Timer
+            Some(Interrupt::Timer)
         } else if pending & (Interrupt::Serial as u8) != 0 {
-            Interrupt::Serial
+            Some(Interrupt::Serial)
         } else if pending & (Interrupt::Joypad as u8) != 0 {
-            Interrupt::Joypad
+            Some(Interrupt::Joypad)
         } else {
-            Interrupt::Invalid
+            None
         }
     }
 
@@ -75,7 +95,50 @@ impl Gb {
             Interrupt::Timer => 0x50,
             Interrupt::Serial => 0x58,
             Interrupt::Joypad => 0x60,
-            Interrupt::Invalid => panic!("Invalid interrupt called!"),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::cartridge::{load_cartridge, CartridgeOptions};
+    use crate::gb::{Gb, GbTypes};
+    use crate::storage::MemoryStorage;
+
+    fn setup_gb() -> Gb {
+        let (mbc, gb_mode) = load_cartridge(
+            "../../roms/cpu_instrs.gb",
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        )
+        .unwrap();
+        Gb::new(mbc, gb_mode, GbTypes::Dmg)
+    }
+
+    fn dispatch_with_sp_at_ie(pc: u16) -> Gb {
+        let mut gb = setup_gb();
+        gb.cpu.pc = pc;
+        gb.cpu.sp = 0x0000;
+        gb.cpu.interrupt_master = true;
+        gb.ie_flag = 0x01;
+        gb.if_flag = 0x01;
+        assert!(gb.handle_interrupt());
+        gb
+    }
+
+    #[test]
+    fn test_push_into_ie_cancels_interrupt() {
+        // The high byte of PC leaves only the STAT interrupt enabled
+        let gb = dispatch_with_sp_at_ie(0x0234);
+        assert_eq!(gb.cpu.pc, 0x0000);
+        assert_eq!(gb.ie_flag, 0x02);
+        assert_eq!(gb.if_flag & 0x1F, 0x01);
+        assert_eq!(gb.cpu.sp, 0xFFFE);
+        assert_eq!(gb.read_byte(0xFFFE), 0x34);
+
+        // Still enabled after the push
+        let gb = dispatch_with_sp_at_ie(0x0134);
+        assert_eq!(gb.cpu.pc, 0x0040);
+        assert_eq!(gb.if_flag & 0x1F, 0x00);
+    }
+}
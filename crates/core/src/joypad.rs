@@ -3,7 +3,10 @@
 // SPDX-License-Identifier: MIT
 
 use super::memory::Memory;
+use crate::gb::Gb;
+use crate::interrupts::Interrupt;
 
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Joypad {
     // P1/JOYP register (0xFF00)
     // Bit 7-6: Not used
@@ -16,6 +19,9 @@ pub struct Joypad {
     data: u8,
     button_states: u8,
     dpad_states: u8,
+    // Set whenever the CPU reads P1, used to count lag frames
+    #[serde(skip)]
+    pub(crate) polled: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -30,12 +36,21 @@ pub enum JoypadButton {
     Select,
 }
 
+/// A button change applied once [`Gb::cycles`] reaches `cycle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub cycle: u64,
+    pub button: JoypadButton,
+    pub pressed: bool,
+}
+
 impl Joypad {
     pub fn new() -> Self {
         Self {
             data: 0xCF,         // Initial state: no buttons selected or pressed
             button_states: 0xF, // All buttons unpressed
             dpad_states: 0xF,   // All d-pad unpressed
+            polled: false,
         }
     }
 
@@ -71,6 +86,15 @@ impl Joypad {
         self.update_joyp();
     }
 
+    pub fn any_selected_pressed(&self) -> bool {
+        self.data & 0x0F != 0x0F
+    }
+
+    // The P10-P13 input lines, low while a selected button is pressed
+    fn lines(&self) -> u8 {
+        self.data & 0x0F
+    }
+
     fn update_joyp(&mut self) {
         // Keep the upper bits (4-5) which select button type
         let selection = self.data & 0x30;
@@ -122,3 +146,111 @@ impl Memory for Joypad {
         }
     }
 }
+
+impl Gb {
+    /// Presses or releases `button` once `cycle` dots went by since power on, see
+    /// [`Gb::cycles`]. Events are applied in order, those already due on the next M-cycle.
+    pub fn queue_input(&mut self, cycle: u64, button: JoypadButton, pressed: bool) {
+        let index = self
+            .input_queue
+            .partition_point(|event| event.cycle <= cycle);
+        self.input_queue.insert(
+            index,
+            InputEvent {
+                cycle,
+                button,
+                pressed,
+            },
+        );
+    }
+
+    pub fn clear_input_queue(&mut self) {
+        self.input_queue.clear();
+    }
+
+    /// Whether the CPU is in STOP with no queued input left that could wake it up.
+    pub fn waiting_for_input(&self) -> bool {
+        self.cpu.is_stopped && self.input_queue.is_empty()
+    }
+
+    /// Whether the game read the joypad since the last call.
+    pub fn take_joypad_polled(&mut self) -> bool {
+        std::mem::take(&mut self.joypad.polled)
+    }
+
+    pub fn joypad_write(&mut self, value: u8) {
+        self.update_joypad(|joypad| joypad.write_byte(0xFF00, value));
+    }
+
+    pub(crate) fn apply_queued_input(&mut self) {
+        while let Some(&event) = self.input_queue.front() {
+            // The clock doesn't run in STOP, so later changes are applied right away
+            if event.cycle > self.cycles && !self.cpu.is_stopped {
+                break;
+            }
+            self.input_queue.pop_front();
+            if event.pressed {
+                self.press_button(event.button);
+            } else {
+                self.release_button(event.button);
+            }
+        }
+    }
+
+    // Any selected line going low requests the interrupt and wakes the CPU from STOP
+    pub(crate) fn update_joypad(&mut self, update: impl FnOnce(&mut Joypad)) {
+        let lines = self.joypad.lines();
+        update(&mut self.joypad);
+        if lines & !self.joypad.lines() != 0 {
+            self.request_interrupt(Interrupt::Joypad);
+            self.cpu.is_stopped = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::prelude::GbInstance;
+
+    #[test]
+    fn test_queued_press_wakes_from_stop() {
+        // Select the action buttons, STOP, then an endless JR -2
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x108].copy_from_slice(&[0x3E, 0x10, 0xE0, 0x00, 0x10, 0x00, 0x18, 0xFE]);
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb = GbInstance::build_from_bytes(rom, None, None, options).unwrap();
+        gb.if_flag = 0;
+
+        gb.run_cycles(100);
+        assert!(gb.waiting_for_input());
+        // Directions aren't selected, so this one doesn't count
+        gb.queue_input(0, JoypadButton::Up, true);
+        gb.queue_input(0, JoypadButton::A, true);
+        gb.run();
+        assert!(!gb.cpu.is_stopped);
+        assert_eq!(
+            gb.if_flag & Interrupt::Joypad as u8,
+            Interrupt::Joypad as u8
+        );
+
+        gb.if_flag = 0;
+        let release = gb.cycles() + 1000;
+        gb.queue_input(release, JoypadButton::A, false);
+        gb.queue_input(release + 1000, JoypadButton::A, true);
+        gb.run_cycles(900);
+        assert_eq!(gb.read_byte(0xFF00) & 0x01, 0);
+        gb.run_cycles(200);
+        assert_eq!(gb.read_byte(0xFF00) & 0x01, 0x01);
+        assert_eq!(gb.if_flag, 0);
+        gb.run_cycles(1000);
+        assert_eq!(gb.read_byte(0xFF00) & 0x01, 0);
+        assert_eq!(
+            gb.if_flag & Interrupt::Joypad as u8,
+            Interrupt::Joypad as u8
+        );
+    }
+}
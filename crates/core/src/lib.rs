@@ -4,19 +4,31 @@
 
 pub mod cartridge;
 mod cb_prefix;
+pub mod cheats;
 mod cpu;
+pub mod debugger;
 mod disasm;
 mod dma;
 pub mod gb;
 mod hdma;
+pub mod inspect;
 mod instructions;
 mod interrupts;
 pub mod joypad;
+pub mod link;
 mod mbcs;
 mod memory;
 mod memorybus;
+pub mod movie;
+pub mod power_on;
 mod ppu;
+pub mod ppu_viewer;
 pub mod prelude;
+pub mod rewind;
+mod savestate;
 mod serial;
 mod sound;
+pub mod storage;
+pub mod symbols;
 mod timer;
+pub mod trace;
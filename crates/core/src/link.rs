@@ -0,0 +1,325 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use super::gb::Gb;
+
+/// One end of a link cable.
+///
+/// The transfer is exchanged a byte at a time: the machine driving the clock calls
+/// [`LinkPort::exchange`] once its 8 bits have been shifted out, and the other machine
+/// picks the byte up through [`LinkPort::receive`] on its next serial tick.
+pub trait LinkPort {
+    /// Publishes the byte the other side will get when it clocks a transfer.
+    fn set_outgoing(&mut self, data: u8);
+
+    /// Sends a byte as the clock master and returns the byte the other side had in SB, or
+    /// 0xFF if nothing is connected. `None` means the reply hasn't arrived yet: the transfer
+    /// stays pending and this is called again with the same byte on a later tick.
+    fn exchange(&mut self, data: u8) -> Option<u8>;
+
+    /// Returns the next byte clocked into this machine by the other side.
+    fn receive(&mut self) -> Option<u8>;
+}
+
+#[derive(Default)]
+struct LocalCable {
+    outgoing: [u8; 2],
+    incoming: [VecDeque<u8>; 2],
+}
+
+/// Connects two `Gb` instances living in the same process.
+pub struct LocalLink {
+    cable: Arc<Mutex<LocalCable>>,
+    side: usize,
+}
+
+impl LocalLink {
+    pub fn pair() -> (LocalLink, LocalLink) {
+        let cable = Arc::new(Mutex::new(LocalCable {
+            outgoing: [0xFF; 2],
+            ..Default::default()
+        }));
+        (
+            LocalLink {
+                cable: cable.clone(),
+                side: 0,
+            },
+            LocalLink { cable, side: 1 },
+        )
+    }
+}
+
+impl LinkPort for LocalLink {
+    fn set_outgoing(&mut self, data: u8) {
+        self.cable.lock().unwrap().outgoing[self.side] = data;
+    }
+
+    fn exchange(&mut self, data: u8) -> Option<u8> {
+        let mut cable = self.cable.lock().unwrap();
+        let other = 1 - self.side;
+        cable.incoming[other].push_back(data);
+        Some(cable.outgoing[other])
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        self.cable.lock().unwrap().incoming[self.side].pop_front()
+    }
+}
+
+/// A cable with nothing on the other end that keeps every byte the machine sends, for test
+/// ROMs that report their results through the serial port. Clones share the same bytes.
+#[derive(Clone, Default)]
+pub struct SerialCapture {
+    data: Arc<Mutex<Vec<u8>>>,
+}
+
+impl SerialCapture {
+    /// The bytes sent so far.
+    pub fn data(&self) -> Vec<u8> {
+        self.data.lock().unwrap().clone()
+    }
+}
+
+impl LinkPort for SerialCapture {
+    fn set_outgoing(&mut self, _data: u8) {}
+
+    fn exchange(&mut self, data: u8) -> Option<u8> {
+        self.data.lock().unwrap().push(data);
+        // Nothing on the other end, the line stays high
+        Some(0xFF)
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use tcp::TcpLink;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod tcp {
+    use std::collections::VecDeque;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+    use std::sync::mpsc::{channel, Receiver, TryRecvError};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::LinkPort;
+
+    const TRANSFER: u8 = 0;
+    const REPLY: u8 = 1;
+    // A peer that takes longer than this to answer is treated as disconnected
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[derive(Default)]
+    struct Shared {
+        outgoing: u8,
+        incoming: VecDeque<u8>,
+    }
+
+    /// Link cable over a TCP socket. Each message is a kind byte followed by the data byte;
+    /// transfers from the other side are answered right away by a background thread with
+    /// the last published SB value, so neither emulator waits on the other's frame loop.
+    pub struct TcpLink {
+        stream: TcpStream,
+        shared: Arc<Mutex<Shared>>,
+        replies: Receiver<u8>,
+        // When the transfer waiting for its reply was sent
+        sent_at: Option<Instant>,
+    }
+
+    impl TcpLink {
+        /// Waits for the other emulator to connect to `address`.
+        pub fn listen(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+            let listener = TcpListener::bind(address)?;
+            let (stream, _) = listener.accept()?;
+            Self::from_stream(stream)
+        }
+
+        pub fn connect(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+            Self::from_stream(TcpStream::connect(address)?)
+        }
+
+        pub(crate) fn from_stream(stream: TcpStream) -> std::io::Result<Self> {
+            stream.set_nodelay(true)?;
+            let shared = Arc::new(Mutex::new(Shared {
+                outgoing: 0xFF,
+                ..Default::default()
+            }));
+            let (reply_sender, replies) = channel();
+
+            let mut reader = stream.try_clone()?;
+            let mut writer = stream.try_clone()?;
+            let thread_shared = shared.clone();
+            std::thread::spawn(move || {
+                let mut message = [0u8; 2];
+                while reader.read_exact(&mut message).is_ok() {
+                    match message[0] {
+                        TRANSFER => {
+                            let reply = {
+                                let mut shared = thread_shared.lock().unwrap();
+                                shared.incoming.push_back(message[1]);
+                                shared.outgoing
+                            };
+                            if writer.write_all(&[REPLY, reply]).is_err() {
+                                break;
+                            }
+                        }
+                        REPLY => {
+                            if reply_sender.send(message[1]).is_err() {
+                                break;
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+            });
+
+            Ok(Self {
+                stream,
+                shared,
+                replies,
+                sent_at: None,
+            })
+        }
+    }
+
+    impl LinkPort for TcpLink {
+        fn set_outgoing(&mut self, data: u8) {
+            self.shared.lock().unwrap().outgoing = data;
+        }
+
+        fn exchange(&mut self, data: u8) -> Option<u8> {
+            let sent_at = match self.sent_at {
+                Some(sent_at) => sent_at,
+                None => {
+                    // A reply that came in after its transfer timed out belongs to no one
+                    while self.replies.try_recv().is_ok() {}
+                    if self.stream.write_all(&[TRANSFER, data]).is_err() {
+                        return Some(0xFF);
+                    }
+                    *self.sent_at.insert(Instant::now())
+                }
+            };
+            let reply = match self.replies.try_recv() {
+                Ok(reply) => reply,
+                Err(TryRecvError::Empty) if sent_at.elapsed() < REPLY_TIMEOUT => return None,
+                Err(_) => 0xFF,
+            };
+            self.sent_at = None;
+            Some(reply)
+        }
+
+        fn receive(&mut self) -> Option<u8> {
+            self.shared.lock().unwrap().incoming.pop_front()
+        }
+    }
+
+    impl Drop for TcpLink {
+        fn drop(&mut self) {
+            let _ = self.stream.shutdown(std::net::Shutdown::Both);
+        }
+    }
+}
+
+impl Gb {
+    pub fn connect_link(&mut self, mut link: Box<dyn LinkPort>) {
+        link.set_outgoing(self.read_byte(0xFF01));
+        self.link_port = Some(link);
+    }
+
+    pub fn disconnect_link(&mut self) {
+        self.link_port = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::{load_cartridge, CartridgeOptions};
+    use crate::gb::GbTypes;
+    use crate::storage::MemoryStorage;
+
+    fn setup_gb() -> Gb {
+        let (mbc, gb_mode) = load_cartridge(
+            "../../roms/cpu_instrs.gb",
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        )
+        .unwrap();
+        Gb::new(mbc, gb_mode, GbTypes::Cgb)
+    }
+
+    #[test]
+    fn test_local_link_exchanges_bytes() {
+        let (master_end, slave_end) = LocalLink::pair();
+        let mut master = setup_gb();
+        let mut slave = setup_gb();
+        master.connect_link(Box::new(master_end));
+        slave.connect_link(Box::new(slave_end));
+        master.if_flag = 0;
+        slave.if_flag = 0;
+
+        slave.write_byte(0xFF01, 0x42);
+        slave.write_byte(0xFF02, 0x80);
+        master.write_byte(0xFF01, 0x99);
+        master.write_byte(0xFF02, 0x81);
+
+        master.serial_tick(8 * 512 - 1);
+        assert_eq!(master.read_byte(0xFF02) & 0x80, 0x80);
+        master.serial_tick(1);
+        slave.serial_tick(4);
+
+        assert_eq!(master.read_byte(0xFF01), 0x42);
+        assert_eq!(slave.read_byte(0xFF01), 0x99);
+        assert_eq!(master.read_byte(0xFF02) & 0x80, 0);
+        assert_eq!(slave.read_byte(0xFF02) & 0x80, 0);
+        assert_eq!(master.if_flag & 0x08, 0x08);
+        assert_eq!(slave.if_flag & 0x08, 0x08);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_tcp_link_exchanges_bytes() {
+        use std::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let mut master = setup_gb();
+        let mut slave = setup_gb();
+        master.connect_link(Box::new(TcpLink::from_stream(client).unwrap()));
+        slave.connect_link(Box::new(TcpLink::from_stream(server).unwrap()));
+        master.if_flag = 0;
+        slave.if_flag = 0;
+
+        slave.write_byte(0xFF01, 0x42);
+        slave.write_byte(0xFF02, 0x80);
+        master.write_byte(0xFF01, 0x99);
+        master.write_byte(0xFF02, 0x81);
+
+        // The reply comes back over the socket, so the transfer finishes on some later tick
+        master.serial_tick(8 * 512);
+        for _ in 0..1000 {
+            if master.read_byte(0xFF02) & 0x80 == 0 && slave.read_byte(0xFF02) & 0x80 == 0 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            master.serial_tick(4);
+            slave.serial_tick(4);
+        }
+
+        assert_eq!(master.read_byte(0xFF01), 0x42);
+        assert_eq!(slave.read_byte(0xFF01), 0x99);
+        assert_eq!(master.read_byte(0xFF02) & 0x80, 0);
+        assert_eq!(slave.read_byte(0xFF02) & 0x80, 0);
+        assert_eq!(master.if_flag & 0x08, 0x08);
+        assert_eq!(slave.if_flag & 0x08, 0x08);
+    }
+}
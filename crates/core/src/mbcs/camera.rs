@@ -0,0 +1,325 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use crate::cartridge::{CameraSource, CartridgeHeader, Mbc, CAMERA_HEIGHT, CAMERA_WIDTH};
+use crate::power_on::SplitMix64;
+use crate::savestate::{decode_state, encode_state, restore_ram};
+
+// Sensor registers, mapped at 0xA000 when bit 4 of the RAM bank is set
+const REG_SHOOT: usize = 0x00;
+const REG_GAIN: usize = 0x01;
+const REG_EXPOSURE_HIGH: usize = 0x02;
+const REG_EXPOSURE_LOW: usize = 0x03;
+const REG_EDGE: usize = 0x04;
+const REG_DITHER_START: usize = 0x06;
+const REGISTER_COUNT: usize = 0x36;
+
+// The processed picture is written as 2bpp tiles at 0xA100 in RAM bank 0
+const IMAGE_ADDRESS: usize = 0x0100;
+
+// Capture duration in base clock cycles, the exposure adds to it
+const CAPTURE_CYCLES: u32 = 129_792;
+const CAPTURE_CYCLES_NO_N: u32 = 2048;
+
+const GAINS: [f32; 32] = [
+    0.880_939, 0.914_915, 0.945_75, 0.973_976, 1.0, 1.024_141, 1.046_654, 1.067_743, 1.087_579,
+    1.124_031, 1.156_891, 1.186_804, 1.214_256, 1.239_621, 1.274_384, 1.315_732, 1.352_519,
+    1.385_651, 1.415_79, 1.443_431, 1.468_957, 1.492_67, 1.514_809, 1.535_57, 1.555_116, 1.573_58,
+    1.591_076, 1.607_701, 1.623_537, 1.638_655, 1.653_118, 1.666_981,
+];
+const EDGE_RATIOS: [f32; 8] = [0.5, 0.75, 1.0, 1.25, 2.0, 3.0, 4.0, 5.0];
+
+// Shows static, like a camera with nothing plugged into it. The same static every run, so
+// the camera doesn't break deterministic runs.
+struct NoiseSource(SplitMix64);
+
+impl CameraSource for NoiseSource {
+    fn capture(&mut self, image: &mut [u8; CAMERA_WIDTH * CAMERA_HEIGHT]) {
+        self.0.fill_bytes(image);
+    }
+}
+
+/// The Game Boy Camera cartridge. Pictures come from a [`CameraSource`] and go through the
+/// same gain, exposure, edge enhancement and dithering steps the sensor applies.
+pub struct PocketCamera {
+    rom_data: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    rom_bank: usize,
+    ram_bank: usize,
+    registers: [u8; REGISTER_COUNT],
+    // Base clock cycles until the capture in progress is done
+    capture_cycles: u32,
+    source: Box<dyn CameraSource>,
+}
+
+impl PocketCamera {
+    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader) -> Self {
+        let ram_size = header.ram_size as usize;
+        let ram = vec![0; ram_size];
+
+        Self {
+            rom_data,
+            ram,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            registers: [0; REGISTER_COUNT],
+            capture_cycles: 0,
+            source: Box::new(NoiseSource(SplitMix64::new(0))),
+        }
+    }
+
+    fn registers_selected(&self) -> bool {
+        self.ram_bank & 0x10 != 0
+    }
+
+    fn start_capture(&mut self) {
+        let exposure = ((self.registers[REG_EXPOSURE_HIGH] as u32) << 8)
+            | self.registers[REG_EXPOSURE_LOW] as u32;
+        let no_n = self.registers[REG_GAIN] & 0x80 == 0;
+        self.capture_cycles =
+            CAPTURE_CYCLES + if no_n { CAPTURE_CYCLES_NO_N } else { 0 } + exposure * 64;
+    }
+
+    fn finish_capture(&mut self) {
+        self.registers[REG_SHOOT] &= !0x01;
+
+        let mut sensor = [0; CAMERA_WIDTH * CAMERA_HEIGHT];
+        self.source.capture(&mut sensor);
+
+        let gain = GAINS[(self.registers[REG_GAIN] & 0x1F) as usize];
+        let exposure = (((self.registers[REG_EXPOSURE_HIGH] as u32) << 8)
+            | self.registers[REG_EXPOSURE_LOW] as u32) as f32
+            / 0x1000 as f32;
+        let pixel = |x: isize, y: isize| {
+            let x = x.clamp(0, CAMERA_WIDTH as isize - 1) as usize;
+            let y = y.clamp(0, CAMERA_HEIGHT as isize - 1) as usize;
+            sensor[y * CAMERA_WIDTH + x] as f32 * gain * exposure
+        };
+        let edge_enhance = self.registers[REG_GAIN] & 0xE0 == 0xE0;
+        let edge_ratio = EDGE_RATIOS[((self.registers[REG_EDGE] >> 4) & 0x07) as usize];
+
+        for y in 0..CAMERA_HEIGHT {
+            for x in 0..CAMERA_WIDTH {
+                let (sx, sy) = (x as isize, y as isize);
+                let mut value = pixel(sx, sy);
+                if edge_enhance {
+                    value += 4.0 * value * edge_ratio
+                        - (pixel(sx - 1, sy)
+                            + pixel(sx + 1, sy)
+                            + pixel(sx, sy - 1)
+                            + pixel(sx, sy + 1))
+                            * edge_ratio;
+                }
+
+                // Each position in a 4x4 grid has its own three thresholds
+                let matrix = REG_DITHER_START + ((x & 3) + (y & 3) * 4) * 3;
+                let thresholds = &self.registers[matrix..matrix + 3];
+                let color = match thresholds.iter().position(|&t| value < t as f32) {
+                    Some(i) => 3 - i as u8,
+                    None => 0,
+                };
+
+                let tile = (y / 8) * (CAMERA_WIDTH / 8) + x / 8;
+                let address = IMAGE_ADDRESS + tile * 16 + (y % 8) * 2;
+                let bit = 7 - (x % 8);
+                if let Some(row) = self.ram.get_mut(address..address + 2) {
+                    row[0] = (row[0] & !(1 << bit)) | ((color & 1) << bit);
+                    row[1] = (row[1] & !(1 << bit)) | ((color >> 1) << bit);
+                }
+            }
+        }
+    }
+}
+
+impl Mbc for PocketCamera {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = if address < 0x4000 { 0 } else { self.rom_bank };
+        let addr = (bank << 14) | (address as usize & 0x3FFF);
+        self.rom_data.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => {
+                self.ram_enabled = value & 0x0F == 0x0A;
+            }
+            // Bank 0 can be mapped at 0x4000 on this mapper
+            0x2000..=0x3FFF => {
+                let bank = (value & 0x3F) as usize;
+                self.rom_bank = bank % (self.rom_data.len() >> 14).max(1);
+            }
+            0x4000..=0x5FFF => self.ram_bank = (value & 0x1F) as usize,
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if self.registers_selected() {
+            // Only the capture busy flag can be read back
+            return match address as usize & 0x7F {
+                REG_SHOOT => self.registers[REG_SHOOT] & 0x07,
+                _ => 0x00,
+            };
+        }
+        // Reading the RAM doesn't need it to be enabled, but not while the sensor is busy
+        if self.capture_cycles > 0 {
+            return 0x00;
+        }
+        let addr = ((self.ram_bank & 0x0F) << 13) | (address as usize & 0x1FFF);
+        self.ram.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if self.registers_selected() {
+            let register = address as usize & 0x7F;
+            if register == REG_SHOOT {
+                let start = value & 0x01 != 0 && self.capture_cycles == 0;
+                self.registers[REG_SHOOT] = value & 0x07;
+                if start {
+                    self.start_capture();
+                } else if value & 0x01 == 0 {
+                    self.capture_cycles = 0;
+                }
+            } else if let Some(reg) = self.registers.get_mut(register) {
+                *reg = value;
+            }
+            return;
+        }
+        if !self.ram_enabled {
+            return;
+        }
+        let addr = ((self.ram_bank & 0x0F) << 13) | (address as usize & 0x1FFF);
+        if let Some(cell) = self.ram.get_mut(addr) {
+            *cell = value;
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        !self.ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
+        if !self.ram.is_empty() {
+            Some(self.ram.clone())
+        } else {
+            None
+        }
+    }
+
+    fn set_battery_ram(&mut self, data: &[u8]) {
+        let len = self.ram.len().min(data.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        if self.capture_cycles > 0 {
+            self.capture_cycles = self.capture_cycles.saturating_sub(cycles);
+            if self.capture_cycles == 0 {
+                self.finish_capture();
+            }
+        }
+    }
+
+    fn set_camera_source(&mut self, source: Box<dyn CameraSource>) {
+        self.source = source;
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.ram_enabled,
+            self.rom_bank,
+            self.ram_bank,
+            self.registers.to_vec(),
+            self.capture_cycles,
+        );
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, (_, _, _, Vec<u8>, _)) = decode_state(data)?;
+        if registers.3.len() != REGISTER_COUNT {
+            return Err("Corrupted save state: unexpected camera registers".to_string());
+        }
+        restore_ram(&mut self.ram, &saved_ram)?;
+        let sensor_registers;
+        (
+            self.ram_enabled,
+            self.rom_bank,
+            self.ram_bank,
+            sensor_registers,
+            self.capture_cycles,
+        ) = registers;
+        self.registers.copy_from_slice(&sensor_registers);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup_camera() -> PocketCamera {
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = 0xFC;
+        rom[0x149] = 0x04;
+        rom[0x14D] = (0x134..=0x14C).fold(0u8, |x, i| x.wrapping_sub(rom[i]).wrapping_sub(1));
+        let header = CartridgeHeader::new(&rom).unwrap();
+        PocketCamera::new(rom, header)
+    }
+
+    #[test]
+    fn test_camera_capture_uses_source() {
+        let mut camera = setup_camera();
+        // Left half black, right half white
+        camera.set_camera_source(Box::new(
+            |image: &mut [u8; CAMERA_WIDTH * CAMERA_HEIGHT]| {
+                for (i, pixel) in image.iter_mut().enumerate() {
+                    *pixel = if i % CAMERA_WIDTH < CAMERA_WIDTH / 2 {
+                        0
+                    } else {
+                        255
+                    };
+                }
+            },
+        ));
+
+        camera.write_rom(0x4000, 0x10);
+        camera.write_ram(0xA001, 0x80 | 0x04);
+        camera.write_ram(0xA002, 0x10);
+        camera.write_ram(0xA003, 0x00);
+        for register in 0..48 {
+            camera.write_ram(0xA006 + register, 0x40 + register as u8);
+        }
+        camera.write_ram(0xA000, 0x01);
+        assert_eq!(camera.read_ram(0xA000) & 0x01, 0x01);
+
+        camera.tick(CAPTURE_CYCLES + 0x1000 * 64);
+        assert_eq!(camera.read_ram(0xA000) & 0x01, 0x00);
+
+        camera.write_rom(0x4000, 0x00);
+        // First tile is black, the last tile of the first row is white
+        assert_eq!(camera.read_ram(0xA100), 0xFF);
+        assert_eq!(camera.read_ram(0xA101), 0xFF);
+        assert_eq!(camera.read_ram(0xA100 + 15 * 16), 0x00);
+        assert_eq!(camera.read_ram(0xA101 + 15 * 16), 0x00);
+    }
+}
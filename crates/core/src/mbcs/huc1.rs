@@ -0,0 +1,175 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
+
+// Writing this to 0x0000-0x1FFF maps the infrared port over the RAM
+const IR_SELECT: u8 = 0x0E;
+
+pub struct HuC1 {
+    rom_data: Vec<u8>,
+    ram: Vec<u8>,
+    rom_bank: usize,
+    ram_bank: usize,
+    ir_selected: bool,
+    ir_led: bool,
+}
+
+impl HuC1 {
+    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader) -> Self {
+        let ram_size = header.ram_size as usize;
+        let ram = vec![0; ram_size];
+
+        Self {
+            rom_data,
+            ram,
+            rom_bank: 1,
+            ram_bank: 0,
+            ir_selected: false,
+            ir_led: false,
+        }
+    }
+}
+
+impl Mbc for HuC1 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = if address < 0x4000 { 0 } else { self.rom_bank };
+        let addr = (bank << 14) | (address as usize & 0x3FFF);
+        self.rom_data.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            // Unlike other mappers the RAM is always enabled, this only picks RAM or IR
+            0x0000..=0x1FFF => {
+                self.ir_selected = value & 0x0F == IR_SELECT;
+            }
+            0x2000..=0x3FFF => {
+                let bank = ((value & 0x3F) as usize).max(1);
+                let bank_count = (self.rom_data.len() >> 14).max(1);
+                self.rom_bank = bank & (bank_count - 1);
+            }
+            0x4000..=0x5FFF => self.ram_bank = (value & 0x03) as usize,
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if self.ir_selected {
+            // No other device is pointing its LED at us, so no light is ever seen
+            return 0xC0;
+        }
+        let addr = (self.ram_bank << 13) | (address as usize & 0x1FFF);
+        self.ram.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if self.ir_selected {
+            self.ir_led = value & 0x01 != 0;
+            return;
+        }
+        let addr = (self.ram_bank << 13) | (address as usize & 0x1FFF);
+        if let Some(cell) = self.ram.get_mut(addr) {
+            *cell = value;
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        !self.ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
+        if !self.ram.is_empty() {
+            Some(self.ram.clone())
+        } else {
+            None
+        }
+    }
+
+    fn set_battery_ram(&mut self, data: &[u8]) {
+        let len = self.ram.len().min(data.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (self.rom_bank, self.ram_bank, self.ir_selected, self.ir_led);
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (self.rom_bank, self.ram_bank, self.ir_selected, self.ir_led) = registers;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 8 ROM banks, each ending with its own number, and 4 RAM banks
+    fn setup_huc1() -> HuC1 {
+        let mut rom = vec![0; 8 * 0x4000];
+        for bank in 0..8 {
+            rom[bank * 0x4000 + 0x3FFF] = bank as u8;
+        }
+        rom[0x147] = 0xFF;
+        rom[0x148] = 0x02;
+        rom[0x149] = 0x03;
+        rom[0x14D] = (0x134..=0x14C).fold(0u8, |x, i| x.wrapping_sub(rom[i]).wrapping_sub(1));
+        let header = CartridgeHeader::new(&rom).unwrap();
+        HuC1::new(rom, header)
+    }
+
+    #[test]
+    fn test_huc1_rom_banking() {
+        let mut huc1 = setup_huc1();
+        assert_eq!(huc1.read_rom(0x7FFF), 1);
+        huc1.write_rom(0x2000, 0x05);
+        assert_eq!(huc1.read_rom(0x7FFF), 5);
+        assert_eq!(huc1.read_rom(0x3FFF), 0);
+        // Bank 0 maps bank 1, and banks past the end wrap around
+        huc1.write_rom(0x2000, 0x00);
+        assert_eq!(huc1.read_rom(0x7FFF), 1);
+        huc1.write_rom(0x2000, 0x0B);
+        assert_eq!(huc1.read_rom(0x7FFF), 3);
+    }
+
+    #[test]
+    fn test_huc1_ram_and_ir_share_the_ram_area() {
+        let mut huc1 = setup_huc1();
+        huc1.write_rom(0x4000, 0x02);
+        huc1.write_ram(0xA000, 0x42);
+        huc1.write_rom(0x4000, 0x00);
+        assert_eq!(huc1.read_ram(0xA000), 0x00);
+        huc1.write_rom(0x4000, 0x02);
+        assert_eq!(huc1.read_ram(0xA000), 0x42);
+
+        huc1.write_rom(0x0000, IR_SELECT);
+        assert_eq!(huc1.read_ram(0xA000), 0xC0);
+        huc1.write_ram(0xA000, 0x01);
+        assert!(huc1.ir_led);
+
+        huc1.write_rom(0x0000, 0x0A);
+        assert_eq!(huc1.read_ram(0xA000), 0x42);
+    }
+}
@@ -0,0 +1,381 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use super::rtc::{host_timestamp, RtcClock};
+use crate::cartridge::{CartridgeHeader, Mbc, RtcMode};
+use crate::savestate::{decode_state, encode_state, restore_ram};
+
+// Values written to 0x0000-0x1FFF select what is mapped at 0xA000-0xBFFF
+const MODE_RAM_READ: u8 = 0x0;
+const MODE_RAM: u8 = 0xA;
+const MODE_RTC_COMMAND: u8 = 0xB;
+const MODE_RTC_RESPONSE: u8 = 0xC;
+const MODE_RTC_SEMAPHORE: u8 = 0xD;
+const MODE_IR: u8 = 0xE;
+
+const MINUTES_PER_DAY: u64 = 1440;
+
+/// Footer SameBoy appends to HuC3 saves: a 64-bit UNIX timestamp followed by the minutes,
+/// days, alarm minutes and alarm days as 16-bit values and the alarm enable flag.
+const HUC3_FOOTER_SIZE: usize = 17;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct HuC3Clock {
+    // Minutes since midnight and days, the chip has no finer resolution
+    minutes: u16,
+    days: u16,
+    seconds: u8,
+    alarm_minutes: u16,
+    alarm_days: u16,
+    alarm_enabled: bool,
+}
+
+impl HuC3Clock {
+    fn advance(&mut self, seconds: u64) {
+        let seconds = self.seconds as u64 + seconds;
+        self.seconds = (seconds % 60) as u8;
+        let minutes = self.minutes as u64 + seconds / 60;
+        self.minutes = (minutes % MINUTES_PER_DAY) as u16;
+        self.days = self.days.wrapping_add((minutes / MINUTES_PER_DAY) as u16) & 0x0FFF;
+    }
+}
+
+/// Hudson's HuC3 mapper, with an RTC driven through a nibble wide command interface and an
+/// infrared port.
+pub struct HuC3 {
+    rom_data: Vec<u8>,
+    ram: Vec<u8>,
+    rom_bank: usize,
+    ram_bank: usize,
+    mode: u8,
+    // RTC memory address used by the read and write commands
+    access_index: u8,
+    access_flags: u8,
+    read_value: u8,
+    rtc: HuC3Clock,
+    clock: RtcClock,
+    ir_led: bool,
+}
+
+impl HuC3 {
+    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader) -> Self {
+        let ram_size = header.ram_size as usize;
+        Self {
+            rom_data,
+            ram: vec![0; ram_size],
+            rom_bank: 1,
+            ram_bank: 0,
+            mode: MODE_RAM_READ,
+            access_index: 0,
+            access_flags: 0,
+            read_value: 0,
+            rtc: HuC3Clock {
+                minutes: 0,
+                days: 0,
+                seconds: 0,
+                alarm_minutes: 0,
+                alarm_days: 0,
+                alarm_enabled: false,
+            },
+            clock: RtcClock::new(),
+            ir_led: false,
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        let mut rtc = self.rtc.clone();
+        let mut clock = self.clock.clone();
+        rtc.advance(clock.elapsed());
+
+        let mut data = self.ram.clone();
+        data.extend_from_slice(&host_timestamp().to_le_bytes());
+        for value in [rtc.minutes, rtc.days, rtc.alarm_minutes, rtc.alarm_days] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(rtc.alarm_enabled as u8);
+        data
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) -> bool {
+        let ram_size = self.ram.len();
+        if data.len() != ram_size && data.len() != ram_size + HUC3_FOOTER_SIZE {
+            return false;
+        }
+        self.ram.copy_from_slice(&data[..ram_size]);
+
+        if let Some(footer) = data.get(ram_size..).filter(|f| !f.is_empty()) {
+            let word = |i: usize| u16::from_le_bytes([footer[8 + i * 2], footer[9 + i * 2]]);
+            self.rtc.minutes = word(0) % MINUTES_PER_DAY as u16;
+            self.rtc.days = word(1) & 0x0FFF;
+            self.rtc.alarm_minutes = word(2);
+            self.rtc.alarm_days = word(3);
+            self.rtc.alarm_enabled = footer[16] & 0x01 != 0;
+            self.rtc.seconds = 0;
+            self.clock
+                .resume_from(u64::from_le_bytes(footer[0..8].try_into().unwrap()));
+            self.sync();
+        }
+        true
+    }
+
+    fn sync(&mut self) {
+        let seconds = self.clock.elapsed();
+        self.rtc.advance(seconds);
+    }
+
+    fn run_command(&mut self, value: u8) {
+        self.sync();
+        let argument = value & 0x0F;
+        match value >> 4 {
+            // Read the nibble at the current address and move to the next one
+            0x1 => {
+                self.read_value = self.read_nibble(self.access_index);
+                self.access_index = self.access_index.wrapping_add(1);
+            }
+            // Write a nibble, 0x3 also moves to the next address
+            0x2 | 0x3 => {
+                self.write_nibble(self.access_index, argument);
+                if value >> 4 == 0x3 {
+                    self.access_index = self.access_index.wrapping_add(1);
+                }
+            }
+            0x4 => self.access_index = (self.access_index & 0xF0) | argument,
+            0x5 => self.access_index = (self.access_index & 0x0F) | (argument << 4),
+            0x6 => self.access_flags = argument,
+            _ => {}
+        }
+    }
+
+    // RTC memory holds the minutes at 0x00-0x02, the days at 0x03-0x06 and the alarm
+    // at 0x58-0x5F, one nibble per address
+    fn read_nibble(&self, index: u8) -> u8 {
+        let nibble = |value: u16, shift: u8| ((value >> (shift * 4)) & 0x0F) as u8;
+        match index {
+            0x00..=0x02 => nibble(self.rtc.minutes, index),
+            0x03..=0x06 => nibble(self.rtc.days, index - 0x03),
+            0x58..=0x5A => nibble(self.rtc.alarm_minutes, index - 0x58),
+            0x5B..=0x5E => nibble(self.rtc.alarm_days, index - 0x5B),
+            0x5F => self.rtc.alarm_enabled as u8,
+            _ => 0,
+        }
+    }
+
+    fn write_nibble(&mut self, index: u8, value: u8) {
+        let set = |target: &mut u16, shift: u8| {
+            *target = (*target & !(0x0F << (shift * 4))) | ((value as u16) << (shift * 4));
+        };
+        match index {
+            0x00..=0x02 => {
+                set(&mut self.rtc.minutes, index);
+                self.rtc.seconds = 0;
+                self.clock.reset_divider();
+            }
+            0x03..=0x06 => set(&mut self.rtc.days, index - 0x03),
+            0x58..=0x5A => set(&mut self.rtc.alarm_minutes, index - 0x58),
+            0x5B..=0x5E => set(&mut self.rtc.alarm_days, index - 0x5B),
+            0x5F => self.rtc.alarm_enabled = value & 0x01 != 0,
+            _ => {}
+        }
+    }
+}
+
+impl Mbc for HuC3 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = if address < 0x4000 { 0 } else { self.rom_bank };
+        let addr = (bank << 14) | (address as usize & 0x3FFF);
+        self.rom_data.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => {
+                self.mode = value & 0x0F;
+            }
+            0x2000..=0x3FFF => {
+                let bank = (value & 0x7F) as usize;
+                let max_bank = (self.rom_data.len() >> 14).saturating_sub(1);
+                self.rom_bank = bank.max(1).min(max_bank);
+            }
+            0x4000..=0x5FFF => self.ram_bank = (value & 0x03) as usize,
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.mode {
+            MODE_RAM_READ | MODE_RAM => {
+                let addr = (self.ram_bank << 13) | (address as usize & 0x1FFF);
+                self.ram.get(addr).copied().unwrap_or(0xFF)
+            }
+            MODE_RTC_RESPONSE if self.access_flags == 0x2 => 0x01,
+            MODE_RTC_RESPONSE => self.read_value,
+            // The RTC is always ready for the next command
+            MODE_RTC_SEMAPHORE => 0x01,
+            // No other device is pointing its LED at us
+            MODE_IR => 0xC0,
+            _ => 0x01,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        match self.mode {
+            MODE_RAM => {
+                let addr = (self.ram_bank << 13) | (address as usize & 0x1FFF);
+                if let Some(cell) = self.ram.get_mut(addr) {
+                    *cell = value;
+                }
+            }
+            MODE_RTC_COMMAND => self.run_command(value),
+            MODE_IR => self.ir_led = value & 0x01 != 0,
+            _ => {}
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        true
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
+        Some(self.save_data())
+    }
+
+    fn set_battery_ram(&mut self, data: &[u8]) {
+        if !self.load_save_data(data) {
+            let len = self.ram.len().min(data.len());
+            self.ram[..len].copy_from_slice(&data[..len]);
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        let seconds = self.clock.tick(cycles);
+        self.rtc.advance(seconds);
+    }
+
+    fn set_rtc_mode(&mut self, mode: RtcMode) {
+        let seconds = self.clock.set_mode(mode);
+        self.rtc.advance(seconds);
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.rom_bank,
+            self.ram_bank,
+            self.mode,
+            [self.access_index, self.access_flags, self.read_value],
+            &self.rtc,
+            &self.clock,
+            self.ir_led,
+        );
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, (_, _, _, _, _, RtcClock, _)) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        let clock;
+        (
+            self.rom_bank,
+            self.ram_bank,
+            self.mode,
+            [self.access_index, self.access_flags, self.read_value],
+            self.rtc,
+            clock,
+            self.ir_led,
+        ) = registers;
+        self.clock.restore(clock);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup_huc3() -> HuC3 {
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = 0xFE;
+        rom[0x149] = 0x03;
+        rom[0x14D] = (0x134..=0x14C).fold(0u8, |x, i| x.wrapping_sub(rom[i]).wrapping_sub(1));
+        let header = CartridgeHeader::new(&rom).unwrap();
+        let mut huc3 = HuC3::new(rom, header);
+        huc3.set_rtc_mode(RtcMode::Emulated);
+        huc3
+    }
+
+    fn command(huc3: &mut HuC3, value: u8) {
+        huc3.write_rom(0x0000, MODE_RTC_COMMAND);
+        huc3.write_ram(0xA000, value);
+    }
+
+    fn set_address(huc3: &mut HuC3, index: u8) {
+        command(huc3, 0x40 | (index & 0x0F));
+        command(huc3, 0x50 | (index >> 4));
+    }
+
+    fn write_nibbles(huc3: &mut HuC3, index: u8, nibbles: &[u8]) {
+        set_address(huc3, index);
+        for nibble in nibbles {
+            command(huc3, 0x30 | nibble);
+        }
+    }
+
+    fn read_nibbles(huc3: &mut HuC3, index: u8, count: usize) -> Vec<u8> {
+        set_address(huc3, index);
+        (0..count)
+            .map(|_| {
+                command(huc3, 0x10);
+                huc3.write_rom(0x0000, MODE_RTC_RESPONSE);
+                huc3.read_ram(0xA000)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_huc3_rtc_commands() {
+        let mut huc3 = setup_huc3();
+        // 23:59 on day 2
+        write_nibbles(&mut huc3, 0x00, &[0xF, 0x9, 0x5, 0x2, 0x0, 0x0, 0x0]);
+        assert_eq!(
+            read_nibbles(&mut huc3, 0x00, 7),
+            [0xF, 0x9, 0x5, 0x2, 0, 0, 0]
+        );
+
+        huc3.tick(4_194_304 * 60);
+        assert_eq!(read_nibbles(&mut huc3, 0x00, 7), [0, 0, 0, 0x3, 0, 0, 0]);
+
+        write_nibbles(&mut huc3, 0x58, &[0x4, 0x2]);
+        write_nibbles(&mut huc3, 0x5F, &[0x1]);
+        assert_eq!(read_nibbles(&mut huc3, 0x58, 2), [0x4, 0x2]);
+        assert!(huc3.rtc.alarm_enabled);
+
+        huc3.write_rom(0x0000, MODE_RTC_SEMAPHORE);
+        assert_eq!(huc3.read_ram(0xA000), 0x01);
+    }
+
+    #[test]
+    fn test_huc3_ram_needs_write_mode() {
+        let mut huc3 = setup_huc3();
+        huc3.write_rom(0x0000, MODE_RAM);
+        huc3.write_ram(0xA000, 0x42);
+        huc3.write_rom(0x0000, MODE_RAM_READ);
+        huc3.write_ram(0xA000, 0x24);
+        assert_eq!(huc3.read_ram(0xA000), 0x42);
+    }
+}
@@ -2,9 +2,8 @@
 //
 // SPDX-License-Identifier: MIT
 
-use rand::prelude::*;
-
 use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
 
 pub struct Mbc1 {
     header: CartridgeHeader,
@@ -14,7 +13,6 @@ pub struct Mbc1 {
     banking_mode: bool,
     current_rom_bank: usize,
     current_ram_bank: usize,
-    save_path: std::path::PathBuf,
     has_battery: bool,
 }
 
@@ -24,23 +22,13 @@ impl Mbc1 {
         header: CartridgeHeader,
         has_ram: bool,
         has_battery: bool,
-        save_path: std::path::PathBuf,
     ) -> Self {
-        let mut external_ram: Vec<u8>;
-        if has_ram && header.ram_size > 0 {
-            if has_battery && save_path.exists() {
-                external_ram = std::fs::read(&save_path).unwrap_or_else(|_| vec![0; header.ram_size as usize]);
-                if external_ram.len() != header.ram_size as usize {
-                    external_ram.resize(header.ram_size as usize, 0);
-                }
-            } else {
-                external_ram = vec![0; header.ram_size as usize];
-                let mut rng = rand::rng();
-                rng.fill_bytes(&mut external_ram);
-            }
+        // Cleared like the other mappers do, so runs without a save are reproducible
+        let external_ram: Vec<u8> = if has_ram && header.ram_size > 0 {
+            vec![0; header.ram_size as usize]
         } else {
-            external_ram = Vec::new();
-        }
+            Vec::new()
+        };
 
         Self {
             header,
@@ -50,20 +38,53 @@ impl Mbc1 {
             banking_mode: false,
             current_rom_bank: 1,
             current_ram_bank: 0,
-            save_path,
             has_battery,
         }
     }
+
+    fn ram_address(&self, address: u16) -> usize {
+        let bank = if self.banking_mode {
+            self.current_ram_bank
+        } else {
+            0
+        };
+        // Carts with less than 32 KiB of RAM ignore the upper bank bits
+        let bank_mask = (self.external_ram.len() / 0x2000).max(1) - 1;
+        ((bank & bank_mask) * 0x2000) | ((address & 0x1FFF) as usize)
+    }
 }
 
 impl Mbc for Mbc1 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.current_rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.external_ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.external_ram
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
-        let bank = if address < 0x4000 {
-            0
-        } else {
+        let bank = if address >= 0x4000 {
             self.current_rom_bank
+        } else if self.banking_mode {
+            // The upper bank bits also switch the first bank in RAM banking mode
+            self.current_rom_bank & 0x60
+        } else {
+            0
         };
-        self.rom_data[(bank * 0x4000) | ((address as usize) & 0x3FFF)]
+        let bank = bank & ((self.rom_data.len() >> 14).max(1) - 1);
+        self.rom_data
+            .get((bank * 0x4000) | ((address as usize) & 0x3FFF))
+            .copied()
+            .unwrap_or(0xFF)
     }
 
     fn read_ram(&self, address: u16) -> u8 {
@@ -71,34 +92,27 @@ impl Mbc for Mbc1 {
             return 0xFF;
         }
 
-        let bank = if self.banking_mode {
-            self.current_ram_bank
-        } else {
-            0
-        };
-        self.external_ram[(bank * 0x2000) | ((address & 0x1FFF) as usize)]
+        self.external_ram[self.ram_address(address)]
     }
 
     fn write_rom(&mut self, address: u16, value: u8) {
         match address {
             0x0000..=0x1FFF => {
-                let was_enabled = self.ram_enabled;
-                self.ram_enabled = value == 0x0A;
-                if was_enabled && !self.ram_enabled && self.has_battery && !self.external_ram.is_empty() {
-                    let _ = std::fs::write(&self.save_path, &self.external_ram);
-                }
+                self.ram_enabled = value & 0x0F == 0x0A;
             }
             0x2000..=0x3FFF => {
-                let r: usize = if value & 0x1F == 0 { 1 } else { value as usize };
+                let r: usize = if value & 0x1F == 0 {
+                    1
+                } else {
+                    (value & 0x1F) as usize
+                };
                 self.current_rom_bank = (self.current_rom_bank & 0x60) | r;
             }
+            // One register for both, the mode picks which of them it applies to
             0x4000..=0x5FFF => {
-                if !self.banking_mode {
-                    self.current_rom_bank =
-                        self.current_rom_bank & 0x1F | (((value as usize) & 0x03) << 5);
-                } else {
-                    self.current_ram_bank = (value as usize) & 0x03;
-                }
+                self.current_rom_bank =
+                    self.current_rom_bank & 0x1F | (((value as usize) & 0x03) << 5);
+                self.current_ram_bank = (value as usize) & 0x03;
             }
             0x6000..=0x7FFF => {
                 self.banking_mode = (value & 0x01) == 0x01;
@@ -112,17 +126,17 @@ impl Mbc for Mbc1 {
             return;
         }
 
-        let bank = if self.banking_mode {
-            self.current_ram_bank
-        } else {
-            0
-        };
-        self.external_ram[(bank * 0x2000) | ((address & 0x1FFF) as usize)] = value;
+        let address = self.ram_address(address);
+        self.external_ram[address] = value;
     }
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
+    fn has_battery(&self) -> bool {
+        self.has_battery && !self.external_ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
         if self.has_battery && !self.external_ram.is_empty() {
-            Some(&self.external_ram)
+            Some(self.external_ram.clone())
         } else {
             None
         }
@@ -134,12 +148,52 @@ impl Mbc for Mbc1 {
             self.external_ram[..len].copy_from_slice(&data[..len]);
         }
     }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.ram_enabled,
+            self.banking_mode,
+            self.current_rom_bank,
+            self.current_ram_bank,
+        );
+        encode_state(&(&self.external_ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.external_ram, &saved_ram)?;
+        (
+            self.ram_enabled,
+            self.banking_mode,
+            self.current_rom_bank,
+            self.current_ram_bank,
+        ) = registers;
+        Ok(())
+    }
 }
 
-impl Drop for Mbc1 {
-    fn drop(&mut self) {
-        if self.has_battery && !self.external_ram.is_empty() {
-            let _ = std::fs::write(&self.save_path, &self.external_ram);
-        }
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup_mbc1() -> Mbc1 {
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x02;
+        rom[0x14D] = (0x134..=0x14C).fold(0u8, |x, i| x.wrapping_sub(rom[i]).wrapping_sub(1));
+        let header = CartridgeHeader::new(&rom).unwrap();
+        Mbc1::new(rom, header, true, true)
+    }
+
+    #[test]
+    fn test_ram_bank_wraps_to_ram_size() {
+        let mut mbc1 = setup_mbc1();
+        mbc1.write_rom(0x0000, 0x0A);
+        mbc1.write_ram(0xA000, 0x42);
+        mbc1.write_rom(0x6000, 0x01);
+        mbc1.write_rom(0x4000, 0x03);
+        assert_eq!(mbc1.read_ram(0xA000), 0x42);
+        mbc1.write_ram(0xBFFF, 0x24);
+        assert_eq!(mbc1.external_ram[0x1FFF], 0x24);
     }
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
 
 pub struct Mbc2 {
     rom_data: Vec<u8>,
@@ -13,7 +14,6 @@ pub struct Mbc2 {
     rom_bank: usize,
     has_ram: bool,
     has_battery: bool,
-    save_path: std::path::PathBuf,
 }
 
 impl Mbc2 {
@@ -22,16 +22,8 @@ impl Mbc2 {
         header: CartridgeHeader,
         has_battery: bool,
         has_ram: bool,
-        save_path: std::path::PathBuf,
     ) -> Self {
-        let mut ram = [0xFF; 0x200];
-        if has_ram && has_battery && save_path.exists() {
-            if let Ok(data) = std::fs::read(&save_path) {
-                if data.len() == 0x200 {
-                    ram.copy_from_slice(&data);
-                }
-            }
-        }
+        let ram = [0xFF; 0x200];
 
         Self {
             rom_data,
@@ -41,12 +33,27 @@ impl Mbc2 {
             rom_bank: 1,
             has_ram,
             has_battery,
-            save_path,
         }
     }
 }
 
 impl Mbc for Mbc2 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
         match address {
             0x0000..=0x3FFF => self.rom_data[address as usize],
@@ -70,11 +77,7 @@ impl Mbc for Mbc2 {
                 let is_ram_enable = (address & 0x0100) == 0;
                 if is_ram_enable {
                     // RAM Enable (bit 8 = 0)
-                    let was_enabled = self.ram_enabled;
                     self.ram_enabled = (value & 0x0F) == 0x0A;
-                    if was_enabled && !self.ram_enabled && self.has_battery {
-                        let _ = std::fs::write(&self.save_path, &self.ram);
-                    }
                 } else {
                     // ROM Bank Number (bit 8 = 1)
                     let mut bank = value & 0x0F;
@@ -119,9 +122,13 @@ impl Mbc for Mbc2 {
         }
     }
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
+    fn has_battery(&self) -> bool {
+        self.has_battery && self.has_ram
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
         if self.has_battery && self.has_ram {
-            Some(&self.ram)
+            Some(self.ram.to_vec())
         } else {
             None
         }
@@ -133,12 +140,16 @@ impl Mbc for Mbc2 {
             self.ram[..len].copy_from_slice(&data[..len]);
         }
     }
-}
 
-impl Drop for Mbc2 {
-    fn drop(&mut self) {
-        if self.has_battery {
-            let _ = std::fs::write(&self.save_path, &self.ram);
-        }
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (self.ram_enabled, self.rom_bank);
+        encode_state(&(&self.ram[..], registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (self.ram_enabled, self.rom_bank) = registers;
+        Ok(())
     }
 }
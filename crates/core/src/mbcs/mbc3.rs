@@ -2,7 +2,9 @@
 //
 // SPDX-License-Identifier: MIT
 
-use crate::cartridge::{CartridgeHeader, Mbc};
+use super::rtc::{Rtc, RTC_FOOTER_SIZE};
+use crate::cartridge::{CartridgeHeader, Mbc, RtcMode};
+use crate::savestate::{decode_state, encode_state, restore_ram};
 
 pub struct Mbc3 {
     rom_data: Vec<u8>,
@@ -14,10 +16,7 @@ pub struct Mbc3 {
     has_ram: bool,
     has_timer: bool,
     has_battery: bool,
-    rtc_registers: [u8; 5], // Seconds, Minutes, Hours, Days Low, Days High/Control
-    rtc_latch: u8,
-    rtc_latched: bool,
-    save_path: std::path::PathBuf,
+    rtc: Rtc,
 }
 
 impl Mbc3 {
@@ -27,37 +26,66 @@ impl Mbc3 {
         ram: bool,
         timer: bool,
         battery: bool,
-        save_path: std::path::PathBuf,
     ) -> Self {
         let ram_size = if ram { header.ram_size as usize } else { 0 };
-        let mut external_ram = vec![0; ram_size];
-        if ram && battery && save_path.exists() {
-            if let Ok(data) = std::fs::read(&save_path) {
-                if data.len() == ram_size {
-                    external_ram = data;
-                }
-            }
-        }
-
         Self {
             rom_data,
             header,
             ram_enabled: false,
             rom_bank: 1,
             ram_bank: 0,
-            ram: external_ram,
+            ram: vec![0; ram_size],
             has_ram: ram,
             has_timer: timer,
             has_battery: battery,
-            rtc_registers: [0; 5],
-            rtc_latch: 0xFF,
-            rtc_latched: false,
-            save_path,
+            rtc: Rtc::new(),
+        }
+    }
+
+    fn has_save(&self) -> bool {
+        self.has_battery && (!self.ram.is_empty() || self.has_timer)
+    }
+
+    // Battery RAM followed by the RTC footer on cartridges with a timer
+    fn save_data(&self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        if self.has_timer {
+            data.extend_from_slice(&self.rtc.to_footer());
         }
+        data
+    }
+
+    // Saves without a footer, or with one from another emulator, load the RAM all the same
+    fn load_save_data(&mut self, data: &[u8]) -> bool {
+        let ram_size = self.ram.len();
+        if data.len() < ram_size || data.len() > ram_size + RTC_FOOTER_SIZE {
+            return false;
+        }
+        self.ram.copy_from_slice(&data[..ram_size]);
+        if self.has_timer {
+            self.rtc.load_footer(&data[ram_size..]);
+        }
+        true
     }
 }
 
 impl Mbc for Mbc3 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
         match address {
             0x0000..=0x3FFF => {
@@ -77,11 +105,7 @@ impl Mbc for Mbc3 {
         match address {
             // RAM Enable
             0x0000..=0x1FFF => {
-                let was_enabled = self.ram_enabled;
                 self.ram_enabled = (value & 0x0F) == 0x0A;
-                if was_enabled && !self.ram_enabled && self.has_battery && !self.ram.is_empty() {
-                    let _ = std::fs::write(&self.save_path, &self.ram);
-                }
             }
             // ROM Bank Number
             0x2000..=0x3FFF => {
@@ -97,16 +121,7 @@ impl Mbc for Mbc3 {
                 }
             }
             // Latch Clock Data
-            0x6000..=0x7FFF => {
-                if self.has_timer && self.rtc_latch == 0 && value == 1 {
-                    self.rtc_latched = !self.rtc_latched;
-                    if !self.rtc_latched {
-                        // Simplified RTC update - just increment seconds for now
-                        self.rtc_registers[0] = self.rtc_registers[0].wrapping_add(1);
-                    }
-                }
-                self.rtc_latch = value;
-            }
+            0x6000..=0x7FFF if self.has_timer => self.rtc.write_latch(value),
             _ => {}
         }
     }
@@ -119,7 +134,7 @@ impl Mbc for Mbc3 {
         if address >= 0xA000 && address <= 0xBFFF {
             if self.has_timer && self.ram_bank >= 0x08 {
                 // Fast path for RTC register read
-                return self.rtc_registers[self.ram_bank - 0x08];
+                return self.rtc.read(self.ram_bank - 0x08);
             }
             if self.has_ram && self.ram_bank <= 0x03 {
                 // Fast path for RAM read
@@ -138,7 +153,7 @@ impl Mbc for Mbc3 {
         if address >= 0xA000 && address <= 0xBFFF {
             if self.has_timer && self.ram_bank >= 0x08 {
                 // Fast path for RTC register write
-                self.rtc_registers[self.ram_bank - 0x08] = value;
+                self.rtc.write(self.ram_bank - 0x08, value);
                 return;
             }
             if self.has_ram && self.ram_bank <= 0x03 {
@@ -151,26 +166,46 @@ impl Mbc for Mbc3 {
         }
     }
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
-        if self.has_battery && !self.ram.is_empty() {
-            Some(&self.ram)
+    fn has_battery(&self) -> bool {
+        self.has_save()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
+        if self.has_save() {
+            Some(self.save_data())
         } else {
             None
         }
     }
 
     fn set_battery_ram(&mut self, data: &[u8]) {
-        if self.has_battery && !self.ram.is_empty() {
+        if self.has_save() && !self.load_save_data(data) {
             let len = self.ram.len().min(data.len());
             self.ram[..len].copy_from_slice(&data[..len]);
         }
     }
-}
 
-impl Drop for Mbc3 {
-    fn drop(&mut self) {
-        if self.has_battery && !self.ram.is_empty() {
-            let _ = std::fs::write(&self.save_path, &self.ram);
+    fn tick(&mut self, cycles: u32) {
+        if self.has_timer {
+            self.rtc.tick(cycles);
         }
     }
+
+    fn set_rtc_mode(&mut self, mode: RtcMode) {
+        self.rtc.set_mode(mode);
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (self.ram_enabled, self.rom_bank, self.ram_bank, &self.rtc);
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, (_, _, _, Rtc)) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        let rtc;
+        (self.ram_enabled, self.rom_bank, self.ram_bank, rtc) = registers;
+        self.rtc.restore(rtc);
+        Ok(())
+    }
 }
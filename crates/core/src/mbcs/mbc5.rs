@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
 
 pub struct Mbc3 {
     header: CartridgeHeader,
@@ -20,7 +21,6 @@ pub struct Mbc5 {
     has_battery: bool,
     has_rumble: bool,
     rumble_active: bool,
-    save_path: std::path::PathBuf,
 }
 
 impl Mbc5 {
@@ -30,17 +30,9 @@ impl Mbc5 {
         ram: bool,
         battery: bool,
         rumble: bool,
-        save_path: std::path::PathBuf,
     ) -> Self {
         let ram_size = if ram { header.ram_size as usize } else { 0 };
-        let mut external_ram = vec![0; ram_size];
-        if ram && battery && save_path.exists() {
-            if let Ok(data) = std::fs::read(&save_path) {
-                if data.len() == ram_size {
-                    external_ram = data;
-                }
-            }
-        }
+        let external_ram = vec![0; ram_size];
         Self {
             rom_data,
             header,
@@ -52,12 +44,27 @@ impl Mbc5 {
             has_battery: battery,
             has_rumble: rumble,
             rumble_active: false,
-            save_path,
         }
     }
 }
 
 impl Mbc for Mbc5 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
         match address {
             0x0000..=0x3FFF => self.rom_data[address as usize],
@@ -78,11 +85,7 @@ impl Mbc for Mbc5 {
         match address {
             // RAM Enable
             0x0000..=0x1FFF => {
-                let was_enabled = self.ram_enabled;
                 self.ram_enabled = (value & 0x0F) == 0x0A;
-                if was_enabled && !self.ram_enabled && self.has_battery && !self.ram.is_empty() {
-                    let _ = std::fs::write(&self.save_path, &self.ram);
-                }
             }
             // ROM Bank Number (Lower 8 bits)
             0x2000..=0x2FFF => {
@@ -145,9 +148,13 @@ impl Mbc for Mbc5 {
         }
     }
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
+    fn has_battery(&self) -> bool {
+        self.has_battery && !self.ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
         if self.has_battery && !self.ram.is_empty() {
-            Some(&self.ram)
+            Some(self.ram.clone())
         } else {
             None
         }
@@ -159,12 +166,26 @@ impl Mbc for Mbc5 {
             self.ram[..len].copy_from_slice(&data[..len]);
         }
     }
-}
 
-impl Drop for Mbc5 {
-    fn drop(&mut self) {
-        if self.has_battery && !self.ram.is_empty() {
-            let _ = std::fs::write(&self.save_path, &self.ram);
-        }
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.ram_enabled,
+            self.rom_bank,
+            self.ram_bank,
+            self.rumble_active,
+        );
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (
+            self.ram_enabled,
+            self.rom_bank,
+            self.ram_bank,
+            self.rumble_active,
+        ) = registers;
+        Ok(())
     }
 }
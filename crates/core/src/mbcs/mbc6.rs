@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
 
 pub struct Mbc6 {
     rom_data: Vec<u8>,
@@ -20,19 +21,11 @@ pub struct Mbc6 {
     bank_b_mapping: usize, // 0x6000-0x7FFF
     flash_mode: bool,
     flash_command: u8,
-    save_path: std::path::PathBuf,
 }
 
 impl Mbc6 {
-    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader, save_path: std::path::PathBuf) -> Self {
-        let mut ram = vec![0; 0x4000]; // 128Kb of RAM
-        if save_path.exists() {
-            if let Ok(data) = std::fs::read(&save_path) {
-                if data.len() == 0x4000 {
-                    ram = data;
-                }
-            }
-        }
+    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader) -> Self {
+        let ram = vec![0; 0x4000]; // 128Kb of RAM
         Self {
             rom_data,
             header,
@@ -46,7 +39,6 @@ impl Mbc6 {
             bank_b_mapping: 0x6000,
             flash_mode: false,
             flash_command: 0,
-            save_path,
         }
     }
 
@@ -76,6 +68,22 @@ impl Mbc6 {
 }
 
 impl Mbc for Mbc6 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank_a
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
         match address {
             0x0000..=0x3FFF => self.rom_data[address as usize],
@@ -126,11 +134,7 @@ impl Mbc for Mbc6 {
         match address {
             // RAM Enable
             0x0000..=0x1FFF => {
-                let was_enabled = self.ram_enabled;
                 self.ram_enabled = (value & 0x0F) == 0x0A;
-                if was_enabled && !self.ram_enabled {
-                    let _ = std::fs::write(&self.save_path, &self.ram);
-                }
             }
             // ROM Bank A Number
             0x2000..=0x2FFF => {
@@ -209,9 +213,13 @@ impl Mbc for Mbc6 {
         }
     }
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
+    fn has_battery(&self) -> bool {
+        !self.ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
         if !self.ram.is_empty() {
-            Some(&self.ram)
+            Some(self.ram.clone())
         } else {
             None
         }
@@ -223,10 +231,36 @@ impl Mbc for Mbc6 {
             self.ram[..len].copy_from_slice(&data[..len]);
         }
     }
-}
 
-impl Drop for Mbc6 {
-    fn drop(&mut self) {
-        let _ = std::fs::write(&self.save_path, &self.ram);
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.ram_enabled,
+            self.rom_bank_a,
+            self.rom_bank_b,
+            self.ram_bank_a,
+            self.ram_bank_b,
+            self.bank_a_mapping,
+            self.bank_b_mapping,
+            self.flash_mode,
+            self.flash_command,
+        );
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (
+            self.ram_enabled,
+            self.rom_bank_a,
+            self.rom_bank_b,
+            self.ram_bank_a,
+            self.ram_bank_b,
+            self.bank_a_mapping,
+            self.bank_b_mapping,
+            self.flash_mode,
+            self.flash_command,
+        ) = registers;
+        Ok(())
     }
 }
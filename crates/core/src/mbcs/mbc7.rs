@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
 
 // MBC7 EEPROM Commands
 const EEPROM_EWDS: u16 = 0x00; // Disable writes
@@ -34,10 +35,9 @@ pub struct Mbc7 {
     accel_x: i16,
     accel_y: i16,
     accel_enabled: bool,
-    save_path: std::path::PathBuf,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, serde::Serialize, serde::Deserialize)]
 enum EepromState {
     Ready,
     Command,
@@ -47,15 +47,8 @@ enum EepromState {
 }
 
 impl Mbc7 {
-    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader, save_path: std::path::PathBuf) -> Self {
-        let mut ram = vec![0; 256]; // 256 bytes of EEPROM
-        if save_path.exists() {
-            if let Ok(data) = std::fs::read(&save_path) {
-                if data.len() == 256 {
-                    ram = data;
-                }
-            }
-        }
+    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader) -> Self {
+        let ram = vec![0; 256]; // 256 bytes of EEPROM
         Self {
             rom_data,
             header,
@@ -75,7 +68,6 @@ impl Mbc7 {
             accel_x: 0x8000u16 as i16,
             accel_y: 0x8000u16 as i16,
             accel_enabled: false,
-            save_path,
         }
     }
 
@@ -177,6 +169,22 @@ impl Mbc7 {
 }
 
 impl Mbc for Mbc7 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
         match address {
             0x0000..=0x3FFF => self.rom_data[address as usize],
@@ -202,11 +210,7 @@ impl Mbc for Mbc7 {
                 }
             }
             0x4000..=0x5FFF => {
-                let was_enabled = self.ram_enabled;
                 self.ram_enabled = (value & 0x0F) == 0x0A;
-                if was_enabled && !self.ram_enabled {
-                    let _ = std::fs::write(&self.save_path, &self.ram);
-                }
             }
             _ => {}
         }
@@ -263,9 +267,13 @@ impl Mbc for Mbc7 {
         }
     }
 
-    fn get_battery_ram(&self) -> Option<&[u8]> {
+    fn has_battery(&self) -> bool {
+        !self.ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
         if !self.ram.is_empty() {
-            Some(&self.ram)
+            Some(self.ram.clone())
         } else {
             None
         }
@@ -277,10 +285,48 @@ impl Mbc for Mbc7 {
             self.ram[..len].copy_from_slice(&data[..len]);
         }
     }
-}
 
-impl Drop for Mbc7 {
-    fn drop(&mut self) {
-        let _ = std::fs::write(&self.save_path, &self.ram);
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.rom_bank,
+            self.ram_enabled,
+            self.eeprom_write_enabled,
+            self.eeprom_cs,
+            self.eeprom_sk,
+            self.eeprom_di,
+            self.eeprom_do,
+            &self.eeprom_state,
+            self.eeprom_command,
+            self.eeprom_address,
+            self.eeprom_data,
+            self.eeprom_bit_counter,
+            self.accel_x,
+            self.accel_y,
+            self.accel_enabled,
+        );
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (
+            self.rom_bank,
+            self.ram_enabled,
+            self.eeprom_write_enabled,
+            self.eeprom_cs,
+            self.eeprom_sk,
+            self.eeprom_di,
+            self.eeprom_do,
+            self.eeprom_state,
+            self.eeprom_command,
+            self.eeprom_address,
+            self.eeprom_data,
+            self.eeprom_bit_counter,
+            self.accel_x,
+            self.accel_y,
+            self.accel_enabled,
+        ) = registers;
+        Ok(())
     }
 }
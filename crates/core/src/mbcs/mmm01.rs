@@ -0,0 +1,294 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
+
+/// MMM01 multicart mapper. It starts unmapped, with the menu in the last 32 KiB of the ROM
+/// visible, and the menu then picks the game by setting the upper bank bits and the masks
+/// of the bits the game itself is allowed to change, before locking them in place.
+pub struct Mmm01 {
+    rom_data: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    has_battery: bool,
+    // Set once the menu has chosen a game, the outer bank bits can't change anymore
+    mapped: bool,
+    rom_bank_low: u8,
+    rom_bank_mid: u8,
+    rom_bank_high: u8,
+    ram_bank_low: u8,
+    ram_bank_high: u8,
+    // Bits set in the masks are fixed by the menu and ignored when the game writes them
+    rom_bank_mask: u8,
+    ram_bank_mask: u8,
+    mbc1_mode: bool,
+    mbc1_mode_locked: bool,
+    // The RAM bank bits select the upper ROM bits, like on a large MBC1
+    multiplex: bool,
+}
+
+impl Mmm01 {
+    pub fn new(rom_data: Vec<u8>, header: CartridgeHeader, ram: bool, battery: bool) -> Self {
+        let ram_size = if ram { header.ram_size as usize } else { 0 };
+        let external_ram = vec![0; ram_size];
+
+        Self {
+            rom_data,
+            ram: external_ram,
+            ram_enabled: false,
+            has_battery: battery,
+            mapped: false,
+            rom_bank_low: 0,
+            rom_bank_mid: 0,
+            rom_bank_high: 0,
+            ram_bank_low: 0,
+            ram_bank_high: 0,
+            rom_bank_mask: 0,
+            ram_bank_mask: 0,
+            mbc1_mode: false,
+            mbc1_mode_locked: false,
+            multiplex: false,
+        }
+    }
+
+    // Banks mapped at 0x0000 and 0x4000
+    fn rom_banks(&self) -> (usize, usize) {
+        let bank_count = (self.rom_data.len() >> 14).max(2);
+        if !self.mapped {
+            return (bank_count - 2, bank_count - 1);
+        }
+
+        let high = (self.rom_bank_high as usize) << 7;
+        let (upper, upper0) = if self.multiplex {
+            let upper = (self.ram_bank_low as usize) << 5;
+            (upper, if self.mbc1_mode { upper } else { 0 })
+        } else {
+            let upper = (self.rom_bank_mid as usize) << 5;
+            (upper, upper)
+        };
+
+        let fixed_low = (self.rom_bank_low & (self.rom_bank_mask << 1)) as usize;
+        let bank0 = fixed_low | upper0 | high;
+        let mut bank = self.rom_bank_low as usize | upper | high;
+        if bank == bank0 {
+            bank += 1;
+        }
+        (bank0 % bank_count, bank % bank_count)
+    }
+
+    fn ram_address(&self, address: u16) -> Option<usize> {
+        if self.ram.is_empty() {
+            return None;
+        }
+        let bank = if self.multiplex {
+            self.rom_bank_mid | (self.ram_bank_high << 2)
+        } else {
+            self.ram_bank_low | (self.ram_bank_high << 2)
+        };
+        let addr = ((bank as usize) << 13) | (address as usize & 0x1FFF);
+        Some(addr % self.ram.len())
+    }
+}
+
+impl Mbc for Mmm01 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_banks().1
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn read_rom(&self, address: u16) -> u8 {
+        let (bank0, bank) = self.rom_banks();
+        let bank = if address < 0x4000 { bank0 } else { bank };
+        let addr = (bank << 14) | (address as usize & 0x3FFF);
+        self.rom_data.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => {
+                self.ram_enabled = (value & 0x0F) == 0x0A;
+                if !self.mapped {
+                    self.ram_bank_mask = (value >> 4) & 0x03;
+                    self.mapped = value & 0x40 != 0;
+                }
+            }
+            0x2000..=0x3FFF => {
+                if !self.mapped {
+                    self.rom_bank_mid = (value >> 5) & 0x03;
+                }
+                let fixed = self.rom_bank_mask << 1;
+                self.rom_bank_low = ((self.rom_bank_low & fixed) | (value & !fixed)) & 0x1F;
+            }
+            0x4000..=0x5FFF => {
+                let fixed = self.ram_bank_mask;
+                self.ram_bank_low = ((self.ram_bank_low & fixed) | (value & !fixed)) & 0x03;
+                if !self.mapped {
+                    self.ram_bank_high = (value >> 2) & 0x03;
+                    self.rom_bank_high = (value >> 4) & 0x03;
+                    self.mbc1_mode_locked = value & 0x40 != 0;
+                }
+            }
+            0x6000..=0x7FFF => {
+                if !self.mbc1_mode_locked {
+                    self.mbc1_mode = value & 0x01 != 0;
+                }
+                if !self.mapped {
+                    self.rom_bank_mask = (value >> 2) & 0x0F;
+                    self.multiplex = value & 0x40 != 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        self.ram_address(address)
+            .map_or(0xFF, |addr| self.ram[addr])
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        if let Some(addr) = self.ram_address(address) {
+            self.ram[addr] = value;
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        self.has_battery && !self.ram.is_empty()
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
+        if self.has_battery && !self.ram.is_empty() {
+            Some(self.ram.clone())
+        } else {
+            None
+        }
+    }
+
+    fn set_battery_ram(&mut self, data: &[u8]) {
+        if self.has_battery && !self.ram.is_empty() {
+            let len = self.ram.len().min(data.len());
+            self.ram[..len].copy_from_slice(&data[..len]);
+        }
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (
+            self.ram_enabled,
+            self.mapped,
+            [
+                self.rom_bank_low,
+                self.rom_bank_mid,
+                self.rom_bank_high,
+                self.ram_bank_low,
+                self.ram_bank_high,
+                self.rom_bank_mask,
+                self.ram_bank_mask,
+            ],
+            self.mbc1_mode,
+            self.mbc1_mode_locked,
+            self.multiplex,
+        );
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (
+            self.ram_enabled,
+            self.mapped,
+            [
+                self.rom_bank_low,
+                self.rom_bank_mid,
+                self.rom_bank_high,
+                self.ram_bank_low,
+                self.ram_bank_high,
+                self.rom_bank_mask,
+                self.ram_bank_mask,
+            ],
+            self.mbc1_mode,
+            self.mbc1_mode_locked,
+            self.multiplex,
+        ) = registers;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 64 ROM banks, each ending with its own number
+    fn setup_mmm01() -> Mmm01 {
+        let mut rom = vec![0; 64 * 0x4000];
+        for bank in 0..64 {
+            rom[bank * 0x4000 + 0x3FFF] = bank as u8;
+        }
+        rom[0x147] = 0x0B;
+        rom[0x148] = 0x05;
+        rom[0x14D] = (0x134..=0x14C).fold(0u8, |x, i| x.wrapping_sub(rom[i]).wrapping_sub(1));
+        let header = CartridgeHeader::new(&rom).unwrap();
+        Mmm01::new(rom, header, false, false)
+    }
+
+    #[test]
+    fn test_mmm01_maps_the_menu_then_the_game() {
+        let mut mmm01 = setup_mmm01();
+        // The menu in the last 32 KiB stays visible until a game is mapped
+        assert_eq!(mmm01.read_rom(0x3FFF), 62);
+        assert_eq!(mmm01.read_rom(0x7FFF), 63);
+        mmm01.write_rom(0x2000, 0x22);
+        assert_eq!(mmm01.read_rom(0x3FFF), 62);
+        assert_eq!(mmm01.read_rom(0x7FFF), 63);
+
+        // Maps the game at banks 32-63
+        mmm01.write_rom(0x0000, 0x40);
+        assert_eq!(mmm01.read_rom(0x3FFF), 32);
+        assert_eq!(mmm01.read_rom(0x7FFF), 34);
+
+        // The game only switches banks inside its own 32
+        mmm01.write_rom(0x2000, 0x05);
+        assert_eq!(mmm01.read_rom(0x7FFF), 37);
+        mmm01.write_rom(0x2000, 0x00);
+        assert_eq!(mmm01.read_rom(0x7FFF), 33);
+        mmm01.write_rom(0x0000, 0x00);
+        assert_eq!(mmm01.read_rom(0x3FFF), 32);
+    }
+
+    #[test]
+    fn test_mmm01_mask_fixes_game_bank_bits() {
+        let mut mmm01 = setup_mmm01();
+        mmm01.write_rom(0x2000, 0x04);
+        // Bit 2 of the game bank number belongs to the menu
+        mmm01.write_rom(0x6000, 0x02 << 2);
+        mmm01.write_rom(0x0000, 0x40);
+        assert_eq!(mmm01.read_rom(0x3FFF), 4);
+        assert_eq!(mmm01.read_rom(0x7FFF), 5);
+
+        mmm01.write_rom(0x2000, 0x03);
+        assert_eq!(mmm01.read_rom(0x7FFF), 7);
+        // The mask can't be changed once mapped
+        mmm01.write_rom(0x6000, 0x00);
+        mmm01.write_rom(0x2000, 0x01);
+        assert_eq!(mmm01.read_rom(0x7FFF), 5);
+    }
+}
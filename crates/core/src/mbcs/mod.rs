@@ -1,51 +1,71 @@
 // SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
 //
 // SPDX-License-Identifier: MIT
+mod camera;
+mod huc1;
+mod huc3;
 mod mbc1;
 mod mbc2;
 mod mbc3;
 mod mbc5;
 mod mbc6;
 mod mbc7;
+mod mmm01;
 mod romonly;
+mod rtc;
+mod tama5;
 
+use camera::PocketCamera;
+use huc1::HuC1;
+use huc3::HuC3;
 use mbc1::Mbc1;
 use mbc2::Mbc2;
 use mbc3::Mbc3;
 use mbc5::Mbc5;
 use mbc6::Mbc6;
 use mbc7::Mbc7;
+use mmm01::Mmm01;
 use romonly::RomOnly;
+use tama5::Tama5;
 
-use super::cartridge::{CartridgeHeader, GbMode, MBCTypes, Mbc};
+use super::cartridge::{CartridgeError, CartridgeHeader, GbMode, MBCTypes, Mbc};
+use super::storage::SaveStorage;
 
 pub fn get_mbc(
-    rom_data: Vec<u8>,
+    mut rom_data: Vec<u8>,
     header: CartridgeHeader,
-    save_path: std::path::PathBuf,
-) -> (Box<dyn Mbc>, GbMode) {
+    storage: &mut dyn SaveStorage,
+) -> Result<(Box<dyn Mbc>, GbMode), CartridgeError> {
+    // Both CGB-enhanced (0x80) and CGB-only (0xC0) cartridges run in CGB mode
     let gb_mode = match header.cgb_flag {
-        0x80 => GbMode::NonCgbMode,
-        0xC0 => GbMode::CgbMode,
+        0x80 | 0xC0 => GbMode::CgbMode,
         _ => GbMode::DmgMode,
     };
 
-    match header.mbc_type {
+    // Truncated dumps and tiny homebrew still get the full 32 KiB the bus expects, reading
+    // back as open bus
+    if rom_data.len() < 0x8000 {
+        rom_data.resize(0x8000, 0xFF);
+    }
+
+    let mut mbc: (Box<dyn Mbc>, GbMode) = match header.mbc_type {
         MBCTypes::RomOnly => (Box::new(RomOnly::new(rom_data, header)), gb_mode),
-        MBCTypes::Mbc1 { ram, battery } => (
-            Box::new(Mbc1::new(rom_data, header, ram, battery, save_path)),
-            gb_mode,
-        ),
-        MBCTypes::Mbc2 { battery, ram } => (
-            Box::new(Mbc2::new(rom_data, header, battery, ram, save_path)),
+        MBCTypes::Mbc1 { ram, battery } => {
+            (Box::new(Mbc1::new(rom_data, header, ram, battery)), gb_mode)
+        }
+        MBCTypes::Mmm01 { ram, battery } => (
+            Box::new(Mmm01::new(rom_data, header, ram, battery)),
             gb_mode,
         ),
+        MBCTypes::Mbc2 { battery, ram } => {
+            (Box::new(Mbc2::new(rom_data, header, battery, ram)), gb_mode)
+        }
         MBCTypes::Mbc3 {
             ram,
             timer,
             battery,
         } => (
-            Box::new(Mbc3::new(rom_data, header, ram, timer, battery, save_path)),
+            Box::new(Mbc3::new(rom_data, header, ram, timer, battery)),
             gb_mode,
         ),
         MBCTypes::Mbc5 {
@@ -53,19 +73,21 @@ pub fn get_mbc(
             battery,
             rumble,
         } => (
-            Box::new(Mbc5::new(
-                rom_data, header, ram, battery, rumble, save_path,
-            )),
-            gb_mode,
-        ),
-        MBCTypes::Mbc6 => (
-            Box::new(Mbc6::new(rom_data, header, save_path)),
-            gb_mode,
-        ),
-        MBCTypes::Mbc7 => (
-            Box::new(Mbc7::new(rom_data, header, save_path)),
+            Box::new(Mbc5::new(rom_data, header, ram, battery, rumble)),
             gb_mode,
         ),
-        _ => unimplemented!(),
+        MBCTypes::Mbc6 => (Box::new(Mbc6::new(rom_data, header)), gb_mode),
+        MBCTypes::Mbc7 => (Box::new(Mbc7::new(rom_data, header)), gb_mode),
+        MBCTypes::PocketCamera => (Box::new(PocketCamera::new(rom_data, header)), gb_mode),
+        MBCTypes::Tama5 => (Box::new(Tama5::new(rom_data, header)), gb_mode),
+        MBCTypes::HuC3 => (Box::new(HuC3::new(rom_data, header)), gb_mode),
+        MBCTypes::HuC1 => (Box::new(HuC1::new(rom_data, header)), gb_mode),
+        MBCTypes::Unknown(mbc_type) => return Err(CartridgeError::UnsupportedMapper(mbc_type)),
+    };
+
+    // Battery RAM, followed by the RTC state on cartridges with a clock
+    if let Some(data) = storage.load().map_err(CartridgeError::Save)? {
+        mbc.0.set_battery_ram(&data);
     }
+    Ok(mbc)
 }
@@ -17,6 +17,10 @@ impl RomOnly {
 
 #[allow(unused_variables)]
 impl Mbc for RomOnly {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
     fn read_rom(&self, address: u16) -> u8 {
         self.rom_data[address as usize]
     }
@@ -0,0 +1,381 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::cartridge::RtcMode;
+
+// The RTC crystal runs independently of the CGB double speed mode
+const CYCLES_PER_SECOND: u32 = 4194304;
+
+const SECONDS: usize = 0;
+const MINUTES: usize = 1;
+const HOURS: usize = 2;
+const DAYS_LOW: usize = 3;
+const DAYS_HIGH: usize = 4;
+
+const DAY_HIGH_BIT: u8 = 0x01;
+const HALT_BIT: u8 = 0x40;
+const DAY_CARRY_BIT: u8 = 0x80;
+
+// Registers are stored with their unused bits cleared
+const REGISTER_MASKS: [u8; 5] = [0x3F, 0x3F, 0x1F, 0xFF, 0xC1];
+
+/// Size of the RTC footer VBA and BGB append to the battery RAM in `.sav` files:
+/// the live and latched registers as ten little-endian u32s followed by a 64-bit
+/// UNIX timestamp. Some older saves only store 32 bits of timestamp.
+pub const RTC_FOOTER_SIZE: usize = 48;
+const RTC_FOOTER_SIZE_32: usize = 44;
+
+fn host_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Current host time as a UNIX timestamp in seconds.
+pub fn host_timestamp() -> u64 {
+    host_time_millis() / 1000
+}
+
+/// Measures the time passing for a cartridge clock, either in emulated cycles or on the
+/// host clock depending on the configured [`RtcMode`].
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct RtcClock {
+    // Emulated cycles into the current second
+    cycles: u32,
+    // Host time in milliseconds the clock was last brought up to date
+    last_sync: u64,
+    #[serde(skip)]
+    mode: RtcMode,
+}
+
+impl RtcClock {
+    pub fn new() -> Self {
+        Self {
+            cycles: 0,
+            last_sync: host_time_millis(),
+            mode: RtcMode::default(),
+        }
+    }
+
+    /// Switches the time source, returning the seconds that passed on the previous one.
+    pub fn set_mode(&mut self, mode: RtcMode) -> u64 {
+        if mode == self.mode {
+            return 0;
+        }
+        let seconds = self.elapsed();
+        self.mode = mode;
+        self.last_sync = self.sync_time();
+        seconds
+    }
+
+    // Only the host clock keeps track of when it was last synced, so emulated runs don't
+    // carry the host time around in their save states
+    fn sync_time(&self) -> u64 {
+        match self.mode {
+            RtcMode::HostClock => host_time_millis(),
+            RtcMode::Emulated => 0,
+        }
+    }
+
+    /// Takes over the state of a save state while keeping the configured mode.
+    pub fn restore(&mut self, saved: RtcClock) {
+        *self = Self {
+            mode: self.mode,
+            ..saved
+        };
+    }
+
+    /// Returns the whole seconds completed by `cycles` more emulated cycles.
+    pub fn tick(&mut self, cycles: u32) -> u64 {
+        if self.mode != RtcMode::Emulated {
+            return 0;
+        }
+        self.cycles += cycles;
+        let seconds = self.cycles / CYCLES_PER_SECOND;
+        self.cycles %= CYCLES_PER_SECOND;
+        seconds as u64
+    }
+
+    /// Returns the whole seconds that passed on the host clock since the last call, the
+    /// remainder is carried over so no time is lost.
+    pub fn elapsed(&mut self) -> u64 {
+        if self.mode != RtcMode::HostClock {
+            return 0;
+        }
+        let now = host_time_millis();
+        if now < self.last_sync {
+            // The host clock went backwards
+            self.last_sync = now;
+            return 0;
+        }
+        let seconds = (now - self.last_sync) / 1000;
+        self.last_sync += seconds * 1000;
+        seconds
+    }
+
+    /// Restarts the current second, as happens when the game writes the seconds counter.
+    pub fn reset_divider(&mut self) {
+        self.cycles = 0;
+        self.last_sync = self.sync_time();
+    }
+
+    /// Continues from a clock saved at `timestamp`. Only the host clock counts the time
+    /// that passed since then.
+    pub fn resume_from(&mut self, timestamp: u64) {
+        self.cycles = 0;
+        self.last_sync = match self.mode {
+            RtcMode::HostClock => timestamp.saturating_mul(1000),
+            RtcMode::Emulated => 0,
+        };
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Rtc {
+    // Seconds, Minutes, Hours, Days Low, Days High/Control
+    registers: [u8; 5],
+    latched: [u8; 5],
+    // Latching happens when 0x01 is written right after 0x00
+    latch_armed: bool,
+    clock: RtcClock,
+}
+
+impl Rtc {
+    pub fn new() -> Self {
+        Self {
+            registers: [0; 5],
+            latched: [0; 5],
+            latch_armed: false,
+            clock: RtcClock::new(),
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: RtcMode) {
+        let seconds = self.clock.set_mode(mode);
+        self.advance_unless_halted(seconds);
+    }
+
+    /// Takes over the registers of a save state while keeping the configured mode.
+    pub fn restore(&mut self, saved: Rtc) {
+        self.registers = saved.registers;
+        self.latched = saved.latched;
+        self.latch_armed = saved.latch_armed;
+        self.clock.restore(saved.clock);
+    }
+
+    pub fn tick(&mut self, cycles: u32) {
+        if !self.is_halted() {
+            let seconds = self.clock.tick(cycles);
+            self.advance(seconds);
+        }
+    }
+
+    pub fn read(&self, register: usize) -> u8 {
+        self.latched[register]
+    }
+
+    pub fn write(&mut self, register: usize, value: u8) {
+        self.sync();
+        let value = value & REGISTER_MASKS[register];
+        if register == SECONDS {
+            // Writing the seconds resets the divider feeding them
+            self.clock.reset_divider();
+        }
+        self.registers[register] = value;
+        self.latched[register] = value;
+    }
+
+    pub fn write_latch(&mut self, value: u8) {
+        if self.latch_armed && value == 0x01 {
+            self.sync();
+            self.latched = self.registers;
+        }
+        self.latch_armed = value == 0x00;
+    }
+
+    /// Serializes the clock in the VBA/BGB `.sav` footer format.
+    pub fn to_footer(&self) -> Vec<u8> {
+        let mut rtc = self.clone();
+        rtc.sync();
+
+        let mut footer = Vec::with_capacity(RTC_FOOTER_SIZE);
+        for register in rtc.registers.iter().chain(rtc.latched.iter()) {
+            footer.extend_from_slice(&(*register as u32).to_le_bytes());
+        }
+        footer.extend_from_slice(&host_timestamp().to_le_bytes());
+        footer
+    }
+
+    /// Restores the clock from a VBA/BGB `.sav` footer. When following the host clock, the
+    /// time that passed since the save was written is added on top.
+    pub fn load_footer(&mut self, footer: &[u8]) -> bool {
+        let timestamp = match footer.len() {
+            RTC_FOOTER_SIZE => u64::from_le_bytes(footer[40..48].try_into().unwrap()),
+            RTC_FOOTER_SIZE_32 => u32::from_le_bytes(footer[40..44].try_into().unwrap()) as u64,
+            _ => return false,
+        };
+
+        let value = |i: usize| footer[i * 4] & REGISTER_MASKS[i % 5];
+        for i in 0..5 {
+            self.registers[i] = value(i);
+            self.latched[i] = value(i + 5);
+        }
+        self.clock.resume_from(timestamp);
+        self.sync();
+        true
+    }
+
+    fn is_halted(&self) -> bool {
+        self.registers[DAYS_HIGH] & HALT_BIT != 0
+    }
+
+    fn sync(&mut self) {
+        let seconds = self.clock.elapsed();
+        self.advance_unless_halted(seconds);
+    }
+
+    // Time still passes for the clock source while halted, it just isn't counted
+    fn advance_unless_halted(&mut self, seconds: u64) {
+        if !self.is_halted() {
+            self.advance(seconds);
+        }
+    }
+
+    fn advance(&mut self, mut seconds: u64) {
+        // Out of range values written by the game count up to their bit limit and wrap
+        // without carrying, so step through those one second at a time
+        while seconds > 0
+            && (self.registers[SECONDS] >= 60
+                || self.registers[MINUTES] >= 60
+                || self.registers[HOURS] >= 24)
+        {
+            self.step_second();
+            seconds -= 1;
+        }
+        if seconds == 0 {
+            return;
+        }
+
+        let days = ((self.registers[DAYS_HIGH] & DAY_HIGH_BIT) as u64) << 8
+            | self.registers[DAYS_LOW] as u64;
+        let total = seconds
+            + self.registers[SECONDS] as u64
+            + self.registers[MINUTES] as u64 * 60
+            + self.registers[HOURS] as u64 * 3600
+            + days * 86400;
+
+        let days = total / 86400;
+        self.registers[SECONDS] = (total % 60) as u8;
+        self.registers[MINUTES] = (total / 60 % 60) as u8;
+        self.registers[HOURS] = (total / 3600 % 24) as u8;
+        self.set_days(days);
+    }
+
+    fn step_second(&mut self) {
+        let next = |value: u8, limit: u8, mask: u8| {
+            let value = value.wrapping_add(1) & mask;
+            if value == limit {
+                (0, true)
+            } else {
+                (value, false)
+            }
+        };
+
+        let (seconds, carry) = next(self.registers[SECONDS], 60, 0x3F);
+        self.registers[SECONDS] = seconds;
+        if !carry {
+            return;
+        }
+        let (minutes, carry) = next(self.registers[MINUTES], 60, 0x3F);
+        self.registers[MINUTES] = minutes;
+        if !carry {
+            return;
+        }
+        let (hours, carry) = next(self.registers[HOURS], 24, 0x1F);
+        self.registers[HOURS] = hours;
+        if carry {
+            let days = ((self.registers[DAYS_HIGH] & DAY_HIGH_BIT) as u64) << 8
+                | self.registers[DAYS_LOW] as u64;
+            self.set_days(days + 1);
+        }
+    }
+
+    // The day counter is 9 bits wide, the carry flag stays set until the game clears it
+    fn set_days(&mut self, days: u64) {
+        if days > 0x1FF {
+            self.registers[DAYS_HIGH] |= DAY_CARRY_BIT;
+        }
+        let days = days & 0x1FF;
+        self.registers[DAYS_LOW] = days as u8;
+        self.registers[DAYS_HIGH] = (self.registers[DAYS_HIGH] & !DAY_HIGH_BIT) | (days >> 8) as u8;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn emulated_rtc() -> Rtc {
+        let mut rtc = Rtc::new();
+        rtc.set_mode(RtcMode::Emulated);
+        rtc
+    }
+
+    fn latch(rtc: &mut Rtc) -> [u8; 5] {
+        rtc.write_latch(0);
+        rtc.write_latch(1);
+        [0, 1, 2, 3, 4].map(|i| rtc.read(i))
+    }
+
+    #[test]
+    fn test_rtc_rolls_over_into_day_carry() {
+        let mut rtc = emulated_rtc();
+        rtc.write(SECONDS, 59);
+        rtc.write(MINUTES, 59);
+        rtc.write(HOURS, 23);
+        rtc.write(DAYS_LOW, 0xFF);
+        rtc.write(DAYS_HIGH, 0x01);
+
+        rtc.tick(CYCLES_PER_SECOND - 1);
+        assert_eq!(latch(&mut rtc), [59, 59, 23, 0xFF, 0x01]);
+        rtc.tick(1);
+        assert_eq!(latch(&mut rtc), [0, 0, 0, 0, DAY_CARRY_BIT]);
+    }
+
+    #[test]
+    fn test_rtc_halt_and_invalid_values() {
+        let mut rtc = emulated_rtc();
+        rtc.write(DAYS_HIGH, HALT_BIT);
+        rtc.tick(CYCLES_PER_SECOND * 10);
+        assert_eq!(latch(&mut rtc), [0, 0, 0, 0, HALT_BIT]);
+
+        // 63 wraps back to 0 without incrementing the minutes
+        rtc.write(SECONDS, 63);
+        rtc.write(DAYS_HIGH, 0);
+        rtc.tick(CYCLES_PER_SECOND * 2);
+        assert_eq!(latch(&mut rtc), [1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_rtc_footer_roundtrip() {
+        let mut rtc = emulated_rtc();
+        rtc.write(MINUTES, 42);
+        rtc.write(DAYS_HIGH, 0x01);
+        rtc.write(HOURS, 5);
+        latch(&mut rtc);
+        rtc.tick(CYCLES_PER_SECOND);
+
+        let footer = rtc.to_footer();
+        assert_eq!(footer.len(), RTC_FOOTER_SIZE);
+
+        let mut loaded = emulated_rtc();
+        assert!(loaded.load_footer(&footer));
+        assert_eq!(loaded.registers, [1, 42, 5, 0, 0x01]);
+        assert_eq!(loaded.latched, [0, 42, 5, 0, 0x01]);
+        assert!(!loaded.load_footer(&footer[..40]));
+    }
+}
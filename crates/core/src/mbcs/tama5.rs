@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use crate::cartridge::{CartridgeHeader, Mbc};
+use crate::savestate::{decode_state, encode_state, restore_ram};
+
+// Registers selected by writing to 0xA001, data is written a nibble at a time to 0xA000
+const BANK_LOW: usize = 0x0;
+const BANK_HIGH: usize = 0x1;
+const WRITE_LOW: usize = 0x4;
+const WRITE_HIGH: usize = 0x5;
+const ADDRESS_HIGH: usize = 0x6;
+const ADDRESS_LOW: usize = 0x7;
+const ACTIVE: usize = 0xA;
+const READ_LOW: usize = 0xC;
+const READ_HIGH: usize = 0xD;
+
+// Operation encoded in the upper bits of the high address register
+const OP_RAM_WRITE: u8 = 0x0;
+const OP_RAM_READ: u8 = 0x1;
+
+const RAM_SIZE: usize = 0x20;
+
+/// Bandai TAMA5, used by Game de Hakken!! Tamagotchi 3. The game talks to the mapper
+/// through two registers in the RAM area, and reaches its 32 bytes of RAM one nibble at
+/// a time. The TAMA6 clock chip is not emulated, its commands are ignored.
+pub struct Tama5 {
+    rom_data: Vec<u8>,
+    ram: Vec<u8>,
+    registers: [u8; 8],
+    selected: usize,
+    rom_bank: usize,
+}
+
+impl Tama5 {
+    pub fn new(rom_data: Vec<u8>, _header: CartridgeHeader) -> Self {
+        let ram = vec![0; RAM_SIZE];
+
+        Self {
+            rom_data,
+            ram,
+            registers: [0; 8],
+            selected: 0,
+            rom_bank: 1,
+        }
+    }
+
+    fn ram_address(&self) -> usize {
+        (((self.registers[ADDRESS_HIGH] & 0x01) << 4) | self.registers[ADDRESS_LOW]) as usize
+    }
+
+    fn operation(&self) -> u8 {
+        self.registers[ADDRESS_HIGH] >> 1
+    }
+}
+
+impl Mbc for Tama5 {
+    fn rom(&self) -> &[u8] {
+        &self.rom_data
+    }
+
+    fn rom_bank(&self) -> usize {
+        self.rom_bank
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = if address < 0x4000 { 0 } else { self.rom_bank };
+        let addr = (bank << 14) | (address as usize & 0x3FFF);
+        self.rom_data.get(addr).copied().unwrap_or(0xFF)
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if address & 0x1FFF > 1 || address & 1 != 0 {
+            return 0xFF;
+        }
+        match self.selected {
+            // Tells the game the mapper is ready to take commands
+            ACTIVE => 0xF1,
+            READ_LOW | READ_HIGH => {
+                let value = if self.operation() == OP_RAM_READ {
+                    self.ram[self.ram_address()]
+                } else {
+                    0
+                };
+                let nibble = if self.selected == READ_HIGH {
+                    value >> 4
+                } else {
+                    value & 0x0F
+                };
+                0xF0 | nibble
+            }
+            _ => 0xF1,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if address & 0x1FFF > 1 {
+            return;
+        }
+        if address & 1 != 0 {
+            self.selected = (value & 0x0F) as usize;
+            return;
+        }
+        let Some(register) = self.registers.get_mut(self.selected) else {
+            return;
+        };
+        *register = value & 0x0F;
+
+        match self.selected {
+            BANK_LOW | BANK_HIGH => {
+                let bank =
+                    (self.registers[BANK_LOW] | ((self.registers[BANK_HIGH] & 0x01) << 4)) as usize;
+                self.rom_bank = bank % (self.rom_data.len() >> 14).max(1);
+            }
+            // Writing the low address runs the operation selected in the high address
+            ADDRESS_LOW if self.operation() == OP_RAM_WRITE => {
+                let address = self.ram_address();
+                self.ram[address] = (self.registers[WRITE_HIGH] << 4) | self.registers[WRITE_LOW];
+            }
+            _ => {}
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        true
+    }
+
+    fn get_battery_ram(&self) -> Option<Vec<u8>> {
+        Some(self.ram.clone())
+    }
+
+    fn set_battery_ram(&mut self, data: &[u8]) {
+        let len = self.ram.len().min(data.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let registers = (self.registers, self.selected, self.rom_bank);
+        encode_state(&(&self.ram, registers))
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let (saved_ram, registers): (Vec<u8>, _) = decode_state(data)?;
+        restore_ram(&mut self.ram, &saved_ram)?;
+        (self.registers, self.selected, self.rom_bank) = registers;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 32 ROM banks, each ending with its own number
+    fn setup_tama5() -> Tama5 {
+        let mut rom = vec![0; 32 * 0x4000];
+        for bank in 0..32 {
+            rom[bank * 0x4000 + 0x3FFF] = bank as u8;
+        }
+        rom[0x147] = 0xFD;
+        rom[0x148] = 0x04;
+        rom[0x14D] = (0x134..=0x14C).fold(0u8, |x, i| x.wrapping_sub(rom[i]).wrapping_sub(1));
+        let header = CartridgeHeader::new(&rom).unwrap();
+        Tama5::new(rom, header)
+    }
+
+    fn write_register(tama5: &mut Tama5, register: usize, value: u8) {
+        tama5.write_ram(0xA001, register as u8);
+        tama5.write_ram(0xA000, value);
+    }
+
+    fn read_register(tama5: &mut Tama5, register: usize) -> u8 {
+        tama5.write_ram(0xA001, register as u8);
+        tama5.read_ram(0xA000)
+    }
+
+    #[test]
+    fn test_tama5_rom_bank_registers() {
+        let mut tama5 = setup_tama5();
+        assert_eq!(read_register(&mut tama5, ACTIVE), 0xF1);
+        write_register(&mut tama5, BANK_LOW, 0x3);
+        assert_eq!(tama5.read_rom(0x7FFF), 3);
+        write_register(&mut tama5, BANK_HIGH, 0x1);
+        assert_eq!(tama5.read_rom(0x7FFF), 0x13);
+        assert_eq!(tama5.read_rom(0x3FFF), 0);
+    }
+
+    #[test]
+    fn test_tama5_ram_through_registers() {
+        let mut tama5 = setup_tama5();
+        write_register(&mut tama5, WRITE_LOW, 0x5);
+        write_register(&mut tama5, WRITE_HIGH, 0xA);
+        write_register(&mut tama5, ADDRESS_HIGH, (OP_RAM_WRITE << 1) | 0x1);
+        write_register(&mut tama5, ADDRESS_LOW, 0x2);
+        assert_eq!(tama5.ram[0x12], 0xA5);
+
+        write_register(&mut tama5, ADDRESS_HIGH, (OP_RAM_READ << 1) | 0x1);
+        write_register(&mut tama5, ADDRESS_LOW, 0x2);
+        assert_eq!(read_register(&mut tama5, READ_LOW), 0xF5);
+        assert_eq!(read_register(&mut tama5, READ_HIGH), 0xFA);
+    }
+}
@@ -3,13 +3,17 @@
 // SPDX-License-Identifier: MIT
 
 use super::cartridge::GbMode;
-use super::gb::{Gb, GbTypes};
+use super::debugger::BreakpointKind;
+use super::gb::Gb;
 use super::memory::Memory;
 
 impl Gb {
     pub fn read_byte(&self, address: u16) -> u8 {
         match address {
-            0x0000..=0x7FFF => self.mbc.read_rom(address),
+            0x0000..=0x7FFF => self.read_boot_rom(address).unwrap_or_else(|| {
+                // Game Genie codes sit between the cartridge and the bus
+                self.cheats.patch_rom(address, self.mbc.read_rom(address))
+            }),
             0x8000..=0x9FFF => self.ppu.read_byte(address),
             0xA000..=0xBFFF => self.mbc.read_ram(address),
             0xC000..=0xCFFF | 0xE000..=0xEFFF => self.wram[(address & 0x0FFF) as usize],
@@ -25,12 +29,16 @@ impl Gb {
             0xFF10..=0xFF26 => self.sound.read_byte(address),
             0xFF30..=0xFF3F => self.sound.read_byte(address),
             0xFF40..=0xFF4B => self.ppu.read_byte(address),
-            0xFF4D => self.gb_speed,
+            0xFF4D if self.gb_mode == GbMode::CgbMode => {
+                let speed = if self.is_double_speed() { 0x80 } else { 0x00 };
+                0x7E | speed | self.prepare_speed_switch as u8
+            }
+            0xFF4D => 0xFF,
             0xFF4F => self.ppu.read_byte(address),
-            0xFF50 => self.boot_rom_enabled,
+            0xFF50 => 0xFF,
             0xFF51..=0xFF55 => self.ppu.read_byte(address),
             0xFF56 => 0xFF, // INFRARED COMMS, NOT IMPLEMENTED HERE
-            0xFF68..=0xFF6B => self.ppu.read_byte(address),
+            0xFF68..=0xFF6C => self.ppu.read_byte(address),
             0xFF70 => self.ram_bank as u8,
             0xFF80..=0xFFFE => self.hram[address as usize & 0x007F],
             0xFFFF => self.ie_flag & 0x1F,
@@ -42,7 +50,10 @@ impl Gb {
         match address {
             0x0000..=0x7FFF => self.mbc.write_rom(address, value),
             0x8000..=0x9FFF => self.ppu.write_byte(address, value),
-            0xA000..=0xBFFF => self.mbc.write_ram(address, value),
+            0xA000..=0xBFFF => {
+                self.mbc.write_ram(address, value);
+                self.mark_save_dirty();
+            }
             0xC000..=0xCFFF | 0xE000..=0xEFFF => {
                 self.wram[(address & 0x0FFF) as usize] = value;
             }
@@ -51,23 +62,30 @@ impl Gb {
             }
             0xFE00..=0xFE9F => self.ppu.write_byte(address, value),
             0xFEA0..=0xFEFF => {} // PROHIBITED AREA
-            0xFF00 => self.joypad.write_byte(address, value),
-            0xFF01..=0xFF02 => self.serial.write_byte(address, value),
-            0xFF04..=0xFF07 => self.timer.write_byte(address, value),
+            0xFF00 => self.joypad_write(value),
+            0xFF01..=0xFF02 => self.serial_write(address, value),
+            0xFF04..=0xFF07 => self.timer_write(address, value),
             0xFF0F => self.if_flag = value & 0x1F,
             0xFF10..=0xFF27 => self.sound.write_byte(address, value),
             0xFF30..=0xFF3F => self.sound.write_byte(address, value),
             0xFF40..=0xFF4B => self.ppu.write_byte(address, value),
-            0xFF4D => {
-                self.gb_speed = value;
-                if self.gb_type == GbTypes::Cgb {
-                    self.prepare_speed_switch = value & 1 == 1;
-                }
+            0xFF4D if self.gb_mode == GbMode::CgbMode => {
+                self.prepare_speed_switch = value & 1 == 1;
             }
             0xFF4F => self.ppu.write_byte(address, value),
-            0xFF50 => self.boot_rom_enabled = value,
-            0xFF51..=0xFF55 => self.ppu.write_byte(address, value),
-            0xFF68..=0xFF69 => self.ppu.write_byte(address, value),
+            0xFF50 => {
+                // Once unmapped the boot ROM stays gone until the next power cycle
+                if value != 0 {
+                    self.boot_rom = None;
+                }
+            }
+            0xFF51..=0xFF55 => {
+                self.ppu.write_byte(address, value);
+                if address == 0xFF55 {
+                    self.hdma_start();
+                }
+            }
+            0xFF68..=0xFF6C => self.ppu.write_byte(address, value),
             0xFF70 => {
                 if self.gb_mode == GbMode::CgbMode {
                     self.ram_bank = match value & 0x7 {
@@ -93,4 +111,33 @@ impl Gb {
         self.write_byte(address, (value & 0xFF) as u8);
         self.write_byte(address.wrapping_add(1), (value >> 8) as u8);
     }
+
+    // Accesses made by the CPU take one M-cycle each, and the rest of the machine runs
+    // through that cycle before the access happens
+    pub fn cpu_read(&mut self, address: u16) -> u8 {
+        self.m_cycle();
+        let value = self.read_byte(address);
+        if address == 0xFF00 {
+            self.joypad.polled = true;
+        }
+        self.debug_access(BreakpointKind::Read, address, value);
+        value
+    }
+
+    pub fn cpu_write(&mut self, address: u16, value: u8) {
+        self.m_cycle();
+        self.debug_access(BreakpointKind::Write, address, value);
+        self.write_byte(address, value);
+    }
+
+    pub fn cpu_read_word(&mut self, address: u16) -> u16 {
+        let low = self.cpu_read(address) as u16;
+        let high = self.cpu_read(address.wrapping_add(1)) as u16;
+        low | (high << 8)
+    }
+
+    pub fn cpu_write_word(&mut self, address: u16, value: u16) {
+        self.cpu_write(address, (value & 0xFF) as u8);
+        self.cpu_write(address.wrapping_add(1), (value >> 8) as u8);
+    }
 }
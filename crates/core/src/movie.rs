@@ -0,0 +1,305 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Input movies: the buttons held on every frame of a run, replayed to reproduce it exactly.
+//!
+//! A movie starts either at power on, with WRAM and HRAM filled from [`Movie::seed`] and no
+//! battery save, or from the save state it carries. Playback has to run the same way the
+//! recording did: RTC on [`RtcMode::Emulated`](crate::cartridge::RtcMode::Emulated), no
+//! cheats, and input only changing between frames.
+
+use crate::gb::Gb;
+use crate::joypad::JoypadButton;
+use crate::savestate::{decode_state, encode_state, fnv1a};
+
+const MOVIE_MAGIC: &[u8; 4] = b"SGBM";
+const MOVIE_VERSION: u32 = 1;
+
+// Bit of each button in a frame of input
+const BUTTONS: [JoypadButton; 8] = [
+    JoypadButton::A,
+    JoypadButton::B,
+    JoypadButton::Select,
+    JoypadButton::Start,
+    JoypadButton::Right,
+    JoypadButton::Left,
+    JoypadButton::Up,
+    JoypadButton::Down,
+];
+
+/// Packs the held buttons into a frame of input.
+pub fn buttons_to_input(held: impl IntoIterator<Item = JoypadButton>) -> u8 {
+    held.into_iter().fold(0, |input, button| {
+        input | 1 << BUTTONS.iter().position(|&b| b == button).unwrap()
+    })
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Movie {
+    rom_hash: u64,
+    /// Seed the power-on RAM pattern was generated from
+    pub seed: u64,
+    /// The save state the movie starts from, instead of power on
+    pub start_state: Option<Vec<u8>>,
+    /// How many times recording went back to an earlier frame
+    pub rerecords: u32,
+    // Buttons held on each frame, see `buttons_to_input`
+    inputs: Vec<u8>,
+}
+
+impl Movie {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 8 || &data[0..4] != MOVIE_MAGIC {
+            return Err("Not a SturdyGB movie".to_string());
+        }
+        let version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if version != MOVIE_VERSION {
+            return Err(format!(
+                "Unsupported movie version {version} (expected {MOVIE_VERSION})"
+            ));
+        }
+        decode_state(&data[8..]).map_err(|_| "Corrupted movie".to_string())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::from(*MOVIE_MAGIC);
+        data.extend_from_slice(&MOVIE_VERSION.to_le_bytes());
+        data.extend_from_slice(&encode_state(self));
+        data
+    }
+
+    /// Length in frames.
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovieMode {
+    Recording,
+    Playing,
+    /// Playback reached the end in read-only mode, the player has control again
+    Finished,
+}
+
+/// A movie being recorded or played back, driven one frame at a time by the frontend.
+pub struct MovieSession {
+    movie: Movie,
+    mode: MovieMode,
+    /// Read-only playback never changes the movie. Otherwise loading an earlier state cuts
+    /// the movie there and recording carries on, and so does reaching the end.
+    pub read_only: bool,
+    frame: usize,
+    // Whether each frame so far went by without the game reading the joypad
+    lag: Vec<bool>,
+}
+
+impl MovieSession {
+    /// Starts recording from where `gb` is now. Unless `from_state` is set it must have just
+    /// been powered on with `RamPattern::Seeded(seed)`, and with no battery save.
+    pub fn record(gb: &Gb, seed: u64, from_state: bool) -> Self {
+        let movie = Movie {
+            rom_hash: gb.rom_hash(),
+            seed,
+            start_state: from_state.then(|| gb.save_state()),
+            rerecords: 0,
+            inputs: Vec::new(),
+        };
+        Self {
+            movie,
+            mode: MovieMode::Recording,
+            read_only: false,
+            frame: 0,
+            lag: Vec::new(),
+        }
+    }
+
+    /// Plays `movie` back on `gb`, loading its start state if it has one. Otherwise `gb` must
+    /// have just been powered on the way [`MovieSession::record`] expects.
+    pub fn play(movie: Movie, gb: &mut Gb, read_only: bool) -> Result<Self, String> {
+        if movie.rom_hash != gb.rom_hash() {
+            return Err("Movie was recorded with a different ROM".to_string());
+        }
+        if let Some(state) = &movie.start_state {
+            gb.load_state(state)?;
+        }
+        let mode = if !movie.is_empty() {
+            MovieMode::Playing
+        } else if read_only {
+            MovieMode::Finished
+        } else {
+            MovieMode::Recording
+        };
+        Ok(Self {
+            movie,
+            mode,
+            read_only,
+            frame: 0,
+            lag: Vec::new(),
+        })
+    }
+
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    pub fn mode(&self) -> MovieMode {
+        self.mode
+    }
+
+    /// Frames run since the movie started.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Frames in which the game never read the joypad.
+    pub fn lag_frames(&self) -> usize {
+        self.lag.iter().filter(|&&lag| lag).count()
+    }
+
+    /// Called before running each frame with the buttons the player is holding. Recording
+    /// stores them, playback replaces them with the movie's.
+    pub fn start_frame(&mut self, gb: &mut Gb, held: u8) {
+        let input = match self.mode {
+            MovieMode::Recording => {
+                self.movie.inputs.push(held);
+                held
+            }
+            MovieMode::Playing => self.movie.inputs[self.frame],
+            MovieMode::Finished => held,
+        };
+        for (bit, &button) in BUTTONS.iter().enumerate() {
+            if input & 1 << bit != 0 {
+                gb.press_button(button);
+            } else {
+                gb.release_button(button);
+            }
+        }
+        // Whatever the game read before this frame doesn't count
+        gb.take_joypad_polled();
+    }
+
+    /// Called once the frame ran.
+    pub fn end_frame(&mut self, gb: &mut Gb) {
+        if self.mode == MovieMode::Finished {
+            return;
+        }
+        self.lag.push(!gb.take_joypad_polled());
+        self.frame += 1;
+        if self.mode == MovieMode::Playing && self.frame >= self.movie.len() {
+            self.mode = if self.read_only {
+                MovieMode::Finished
+            } else {
+                MovieMode::Recording
+            };
+        }
+    }
+
+    /// Goes back to `frame` after a save state taken at that point of this movie was loaded.
+    pub fn seek(&mut self, frame: usize) -> Result<(), String> {
+        if frame > self.movie.len() {
+            return Err(format!(
+                "Frame {frame} is past the end of the movie ({} frames)",
+                self.movie.len()
+            ));
+        }
+        self.frame = frame;
+        self.lag.truncate(frame);
+        if self.read_only {
+            self.mode = if frame < self.movie.len() {
+                MovieMode::Playing
+            } else {
+                MovieMode::Finished
+            };
+        } else {
+            self.movie.inputs.truncate(frame);
+            self.movie.rerecords += 1;
+            self.mode = MovieMode::Recording;
+        }
+        Ok(())
+    }
+}
+
+impl Gb {
+    /// A hash of the cartridge ROM, used to check movies are played on the game they were
+    /// recorded with.
+    pub fn rom_hash(&self) -> u64 {
+        fnv1a(self.mbc.rom())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::power_on::RamPattern;
+    use crate::prelude::GbInstance;
+
+    fn power_on() -> Gb {
+        let rom = std::fs::read("../../roms/cpu_instrs.gb").unwrap();
+        let mut gb = GbInstance::default()
+            .ram_pattern(RamPattern::Seeded(7))
+            .load_bytes(rom, None, None, CartridgeOptions::default())
+            .unwrap();
+        gb.print_serial = false;
+        gb
+    }
+
+    fn run_frame(session: &mut MovieSession, gb: &mut Gb, held: u8) {
+        session.start_frame(gb, held);
+        gb.run_one_frame();
+        gb.get_screen_data();
+        session.end_frame(gb);
+    }
+
+    #[test]
+    fn test_playback_matches_recording() {
+        let mut gb = power_on();
+        let mut session = MovieSession::record(&gb, 7, false);
+        for frame in 0..20 {
+            let held = if frame % 3 == 0 {
+                buttons_to_input([JoypadButton::Start, JoypadButton::Up])
+            } else {
+                0
+            };
+            run_frame(&mut session, &mut gb, held);
+        }
+        let expected = gb.state_hash();
+        let movie = Movie::from_bytes(&session.movie().to_bytes()).unwrap();
+        assert_eq!(movie.len(), 20);
+
+        let mut gb = power_on();
+        let mut session = MovieSession::play(movie, &mut gb, true).unwrap();
+        for _ in 0..20 {
+            // Ignored while playing back
+            run_frame(&mut session, &mut gb, 0xFF);
+        }
+        assert_eq!(session.mode(), MovieMode::Finished);
+        assert_eq!(gb.state_hash(), expected);
+    }
+
+    #[test]
+    fn test_seek_branches_in_read_write_mode() {
+        let mut gb = power_on();
+        let mut session = MovieSession::record(&gb, 7, false);
+        for _ in 0..10 {
+            run_frame(&mut session, &mut gb, 0);
+        }
+        session.seek(4).unwrap();
+        assert_eq!(session.mode(), MovieMode::Recording);
+        assert_eq!(session.movie().len(), 4);
+        assert_eq!(session.movie().rerecords, 1);
+        assert!(session.seek(5).is_err());
+
+        session.read_only = true;
+        run_frame(&mut session, &mut gb, 0);
+        session.seek(2).unwrap();
+        assert_eq!(session.mode(), MovieMode::Playing);
+        assert_eq!(session.movie().len(), 5);
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! What WRAM and HRAM hold at power on.
+//!
+//! Real units come up with whatever the RAM cells settle on, which some games read before
+//! writing. Anything other than [`RamPattern::Random`] makes that reproducible: the same ROM
+//! fed the same input then runs bit-identically. [`GbInstance`](crate::prelude::GbInstance)
+//! also switches the RTC to [`RtcMode::Emulated`](crate::cartridge::RtcMode::Emulated) for
+//! those, so it doesn't follow the host clock.
+
+use rand::prelude::*;
+
+use crate::cartridge::GbMode;
+use crate::gb::Gb;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RamPattern {
+    /// Different on every run, like on real hardware
+    #[default]
+    Random,
+    Zeros,
+    Ones,
+    /// Stripes of 8 bytes of 0xFF and 8 of 0x00 on CGB, noise from a fixed seed on DMG. Each
+    /// unit differs a bit, this is the general shape they come up with.
+    Hardware,
+    /// Noise from the given seed
+    Seeded(u64),
+}
+
+/// A seed for [`RamPattern::Seeded`], different every time.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+const DMG_NOISE_SEED: u64 = 0x5354_5552_4459_4742;
+
+/// SplitMix64, kept here rather than taken from `rand` so a seed gives the same bytes no
+/// matter which version of it is used.
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let value = self.next().to_le_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+    }
+}
+
+impl Gb {
+    /// Refills WRAM and HRAM as if the machine was just turned on.
+    pub(crate) fn fill_power_on_ram(&mut self, pattern: RamPattern) {
+        let seed = match pattern {
+            RamPattern::Random => {
+                let mut rng = rand::rng();
+                rng.fill_bytes(&mut self.wram);
+                rng.fill_bytes(&mut self.hram);
+                return;
+            }
+            RamPattern::Zeros | RamPattern::Ones => {
+                let value = if pattern == RamPattern::Ones { 0xFF } else { 0 };
+                self.wram.fill(value);
+                self.hram.fill(value);
+                return;
+            }
+            RamPattern::Hardware if self.gb_mode == GbMode::CgbMode => {
+                for ram in [&mut self.wram, &mut self.hram] {
+                    for (address, byte) in ram.iter_mut().enumerate() {
+                        *byte = if address & 8 == 0 { 0xFF } else { 0x00 };
+                    }
+                }
+                return;
+            }
+            RamPattern::Hardware => DMG_NOISE_SEED,
+            RamPattern::Seeded(seed) => seed,
+        };
+        let mut rng = SplitMix64::new(seed);
+        rng.fill_bytes(&mut self.wram);
+        rng.fill_bytes(&mut self.hram);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::prelude::GbInstance;
+
+    fn run(pattern: RamPattern) -> u64 {
+        run_rom(&std::fs::read("../../roms/cpu_instrs.gb").unwrap(), pattern)
+    }
+
+    fn run_rom(rom: &[u8], pattern: RamPattern) -> u64 {
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb = GbInstance::default()
+            .ram_pattern(pattern)
+            .load_bytes(rom.to_vec(), None, None, options)
+            .unwrap();
+        gb.print_serial = false;
+        for _ in 0..10 {
+            gb.run_one_frame();
+            gb.get_screen_data();
+        }
+        gb.state_hash()
+    }
+
+    #[test]
+    fn test_seeded_runs_are_identical() {
+        assert_eq!(run(RamPattern::Seeded(1)), run(RamPattern::Seeded(1)));
+        assert_ne!(run(RamPattern::Seeded(1)), run(RamPattern::Seeded(2)));
+        assert_eq!(run(RamPattern::Hardware), run(RamPattern::Hardware));
+
+        // MBC3 with RTC, RAM and battery, whose clock would otherwise follow the host
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = 0x10;
+        rom[0x149] = 0x02;
+        let first = run_rom(&rom, RamPattern::Seeded(1));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(run_rom(&rom, RamPattern::Seeded(1)), first);
+    }
+}
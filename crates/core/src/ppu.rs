@@ -8,9 +8,13 @@ use super::gb::Gb;
 use super::hdma::Hdma;
 use super::interrupts::Interrupt;
 use super::memory::Memory;
+use serde::de::Error;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_big_array::BigArray;
 use std::collections::VecDeque;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
 pub enum PpuMode {
     HBlank = 0,
     VBlank = 1,
@@ -18,7 +22,7 @@ pub enum PpuMode {
     Transferring = 3,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, serde::Serialize, serde::Deserialize)]
 struct LineSprite {
     y: u8,
     x: u8,
@@ -27,19 +31,22 @@ struct LineSprite {
     oam_index: u8,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, serde::Serialize, serde::Deserialize)]
 struct BgPixel {
     color: u8,
+    palette: u8,
+    priority: bool,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, serde::Serialize, serde::Deserialize)]
 struct SpritePixel {
     color: u8,
     palette: u8,
     behind_bg: bool,
+    oam_index: u8,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 enum FetcherStep {
     GetTile,
     GetTileDataLow,
@@ -47,26 +54,33 @@ enum FetcherStep {
     Push,
 }
 
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Ppu {
-    lcdc: u8,
+    pub(crate) lcdc: u8,
     pub stat: u8,
-    scy: u8,
-    scx: u8,
+    pub(crate) scy: u8,
+    pub(crate) scx: u8,
     ly: u8,
     lyc: u8,
-    bgp: u8,
-    obp0: u8,
-    obp1: u8,
-    wy: u8,
-    wx: u8,
+    pub(crate) bgp: u8,
+    pub(crate) obp0: u8,
+    pub(crate) obp1: u8,
+    pub(crate) wy: u8,
+    pub(crate) wx: u8,
     key1: u8,
     vbk: u8,
     bcps: u8,
-    bcpd: u8,
     ocps: u8,
-    ocpd: u8,
+    opri: u8,
     svbk: u8,
+    // CGB palette RAM, 8 palettes of 4 RGB555 colors each (little endian)
+    #[serde(with = "BigArray")]
+    pub(crate) bg_palette_ram: [u8; 64],
+    #[serde(with = "BigArray")]
+    pub(crate) obj_palette_ram: [u8; 64],
+    pub(crate) cgb_mode: bool,
     pub vram: Vec<u8>,
+    #[serde(with = "BigArray")]
     pub oam: [u8; 0xA0],
     pub oam_corruption_bug: bool,
     pub mode: PpuMode,
@@ -75,7 +89,19 @@ pub struct Ppu {
     pub mode_clock: u32,
     line_clock: u32,
     pub frame_ready: bool,
-    screen: [[u8; 160]; 144],
+    #[serde(
+        serialize_with = "serialize_screen",
+        deserialize_with = "deserialize_screen"
+    )]
+    screen: Box<[[u16; 160]; 144]>,
+    // The last finished frame, what `get_screen` returns while `screen` is being drawn
+    #[serde(
+        serialize_with = "serialize_screen",
+        deserialize_with = "deserialize_screen"
+    )]
+    front_screen: Box<[[u16; 160]; 144]>,
+    // The LCD shows nothing for the first frame after it's turned on
+    skip_frame: bool,
     bg_fifo: VecDeque<BgPixel>,
     sprite_fifo: VecDeque<Option<SpritePixel>>,
     line_sprites: Vec<LineSprite>,
@@ -86,6 +112,7 @@ pub struct Ppu {
     fetcher_map_x: usize,
     fetcher_using_window: bool,
     fetcher_tile_number: u8,
+    fetcher_tile_attributes: u8,
     fetcher_tile_data_low: u8,
     fetcher_tile_data_high: u8,
     fetch_x: i16,
@@ -96,6 +123,36 @@ pub struct Ppu {
     window_rendering_this_line: bool,
 }
 
+fn blank_screen() -> Box<[[u16; 160]; 144]> {
+    Box::new([[0; 160]; 144])
+}
+
+// Serde only handles arrays up to 32 elements, so the frame is stored as a flat list of pixels
+fn serialize_screen<S: Serializer>(
+    screen: &[[u16; 160]; 144],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(160 * 144))?;
+    for pixel in screen.iter().flatten() {
+        seq.serialize_element(pixel)?;
+    }
+    seq.end()
+}
+
+fn deserialize_screen<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Box<[[u16; 160]; 144]>, D::Error> {
+    let pixels = Vec::<u16>::deserialize(deserializer)?;
+    if pixels.len() != 160 * 144 {
+        return Err(D::Error::invalid_length(pixels.len(), &"160x144 pixels"));
+    }
+    let mut screen = Box::new([[0; 160]; 144]);
+    for (line, row) in screen.iter_mut().zip(pixels.chunks_exact(160)) {
+        line.copy_from_slice(row);
+    }
+    Ok(screen)
+}
+
 impl Ppu {
     pub fn new(gb_mode: &GbMode) -> Self {
         let vram: Vec<u8> = match gb_mode {
@@ -116,12 +173,14 @@ impl Ppu {
             wy: 0,
             wx: 0,
             key1: 0xFF,
-            vbk: 0xFF,
-            bcps: 0xFF,
-            bcpd: 0xFF,
-            ocps: 0xFF,
-            ocpd: 0xFF,
+            vbk: 0,
+            bcps: 0,
+            ocps: 0,
+            opri: 0,
             svbk: 0xFF,
+            bg_palette_ram: [0xFF; 64],
+            obj_palette_ram: [0xFF; 64],
+            cgb_mode: *gb_mode == GbMode::CgbMode,
             vram,
             oam,
             oam_corruption_bug: false,
@@ -131,7 +190,9 @@ impl Ppu {
             hdma: Hdma::new(),
             mode_clock: 0,
             line_clock: 0,
-            screen: [[0; 160]; 144],
+            screen: blank_screen(),
+            front_screen: blank_screen(),
+            skip_frame: false,
             bg_fifo: VecDeque::with_capacity(16),
             sprite_fifo: VecDeque::with_capacity(16),
             line_sprites: Vec::with_capacity(10),
@@ -142,6 +203,7 @@ impl Ppu {
             fetcher_map_x: 0,
             fetcher_using_window: false,
             fetcher_tile_number: 0,
+            fetcher_tile_attributes: 0,
             fetcher_tile_data_low: 0,
             fetcher_tile_data_high: 0,
             fetch_x: 0,
@@ -153,6 +215,21 @@ impl Ppu {
         }
     }
 
+    /// Checks a PPU decoded from a save state fits this one, before any of it is loaded.
+    pub(crate) fn check_state(&self, saved: &Ppu) -> Result<(), String> {
+        if saved.cgb_mode != self.cgb_mode || saved.vram.len() != self.vram.len() {
+            return Err("Corrupted save state: unexpected VRAM size".to_string());
+        }
+        if saved.vbk > 1
+            || saved.line_sprites.len() > 10
+            || saved.oam_scan_index > 40
+            || saved.next_sprite_index > saved.line_sprites.len()
+        {
+            return Err("Corrupted save state: invalid PPU registers".to_string());
+        }
+        Ok(())
+    }
+
     pub fn get_ppu_mode(&self) -> PpuMode {
         let mode = self.stat & 0x03;
         match mode {
@@ -182,6 +259,10 @@ impl Ppu {
         coincidence && !previous && self.stat & 0x40 != 0
     }
 
+    pub fn lcd_enabled(&self) -> bool {
+        self.lcdc & 0x80 != 0
+    }
+
     fn bg_enabled(&self) -> bool {
         self.lcdc & 0x01 != 0
     }
@@ -203,7 +284,11 @@ impl Ppu {
     }
 
     fn window_enabled_for_line(&self) -> bool {
-        self.bg_enabled() && self.lcdc & 0x20 != 0 && self.ly >= self.wy && self.wx <= 166
+        // On CGB, LCDC bit 0 is the BG/window master priority and doesn't hide the window
+        (self.cgb_mode || self.bg_enabled())
+            && self.lcdc & 0x20 != 0
+            && self.ly >= self.wy
+            && self.wx <= 166
     }
 
     fn window_start_x(&self) -> i16 {
@@ -228,6 +313,7 @@ impl Ppu {
             (self.scx as usize >> 3) & 0x1F
         };
         self.fetcher_tile_number = 0;
+        self.fetcher_tile_attributes = 0;
         self.fetcher_tile_data_low = 0;
         self.fetcher_tile_data_high = 0;
     }
@@ -262,6 +348,7 @@ impl Ppu {
         self.reset_fetcher(false);
         self.set_mode(PpuMode::SearchingOAM);
         let _ = self.check_lyc();
+        self.skip_frame = true;
     }
 
     fn start_oam_search(&mut self) {
@@ -352,19 +439,36 @@ impl Ppu {
         }
     }
 
-    fn read_bg_tile_number(&self) -> u8 {
+    fn bg_tile_map_addr(&self) -> usize {
         let tile_map_base = self.tile_map_base(self.fetcher_using_window);
-        let tile_addr = tile_map_base + self.fetcher_tile_y() * 32 + (self.fetcher_map_x & 0x1F);
-        self.vram[tile_addr & 0x1FFF]
+        (tile_map_base + self.fetcher_tile_y() * 32 + (self.fetcher_map_x & 0x1F)) & 0x1FFF
+    }
+
+    fn read_bg_tile_number(&self) -> u8 {
+        self.vram[self.bg_tile_map_addr()]
     }
 
-    fn read_bg_tile_row_addr(&self, tile_number: u8) -> usize {
-        let tile_row = self.fetcher_pixel_y() * 2;
+    fn read_bg_tile_attributes(&self) -> u8 {
+        if self.cgb_mode {
+            self.vram[0x2000 | self.bg_tile_map_addr()]
+        } else {
+            0
+        }
+    }
+
+    fn read_bg_tile_row_addr(&self, tile_number: u8, attributes: u8) -> usize {
+        let pixel_y = if attributes & 0x40 != 0 {
+            7 - self.fetcher_pixel_y()
+        } else {
+            self.fetcher_pixel_y()
+        };
+        let tile_row = pixel_y * 2;
+        let bank = ((attributes as usize >> 3) & 1) * 0x2000;
         if self.lcdc & 0x10 != 0 {
-            ((tile_number as usize) * 16 + tile_row) & 0x1FFF
+            bank | (((tile_number as usize) * 16 + tile_row) & 0x1FFF)
         } else {
             let signed_tile = tile_number as i8 as i16;
-            (0x1000i32 + i32::from(signed_tile) * 16 + tile_row as i32) as usize & 0x1FFF
+            bank | ((0x1000i32 + i32::from(signed_tile) * 16 + tile_row as i32) as usize & 0x1FFF)
         }
     }
 
@@ -372,15 +476,22 @@ impl Ppu {
         match self.fetcher_step {
             FetcherStep::Push => {
                 if self.bg_fifo.len() <= 8 {
-                    let bg_enabled = self.bg_enabled();
-                    for bit in (0..8).rev() {
+                    let bg_enabled = self.cgb_mode || self.bg_enabled();
+                    let attributes = self.fetcher_tile_attributes;
+                    let flip_x = attributes & 0x20 != 0;
+                    for x in 0..8 {
+                        let bit = if flip_x { x } else { 7 - x };
                         let color = if bg_enabled {
                             (((self.fetcher_tile_data_high >> bit) & 1) << 1)
                                 | ((self.fetcher_tile_data_low >> bit) & 1)
                         } else {
                             0
                         };
-                        self.bg_fifo.push_back(BgPixel { color });
+                        self.bg_fifo.push_back(BgPixel {
+                            color,
+                            palette: attributes & 0x07,
+                            priority: attributes & 0x80 != 0,
+                        });
                     }
                     while self.sprite_fifo.len() < self.bg_fifo.len() {
                         self.sprite_fifo.push_back(None);
@@ -399,16 +510,23 @@ impl Ppu {
                 match self.fetcher_step {
                     FetcherStep::GetTile => {
                         self.fetcher_tile_number = self.read_bg_tile_number();
+                        self.fetcher_tile_attributes = self.read_bg_tile_attributes();
                         self.fetcher_step = FetcherStep::GetTileDataLow;
                     }
                     FetcherStep::GetTileDataLow => {
-                        let row_addr = self.read_bg_tile_row_addr(self.fetcher_tile_number);
+                        let row_addr = self.read_bg_tile_row_addr(
+                            self.fetcher_tile_number,
+                            self.fetcher_tile_attributes,
+                        );
                         self.fetcher_tile_data_low = self.vram[row_addr];
                         self.fetcher_step = FetcherStep::GetTileDataHigh;
                     }
                     FetcherStep::GetTileDataHigh => {
-                        let row_addr = self.read_bg_tile_row_addr(self.fetcher_tile_number);
-                        self.fetcher_tile_data_high = self.vram[(row_addr + 1) & 0x1FFF];
+                        let row_addr = self.read_bg_tile_row_addr(
+                            self.fetcher_tile_number,
+                            self.fetcher_tile_attributes,
+                        );
+                        self.fetcher_tile_data_high = self.vram[row_addr + 1];
                         self.fetcher_step = FetcherStep::Push;
                     }
                     FetcherStep::Push => {}
@@ -424,10 +542,13 @@ impl Ppu {
         let flip_x = sprite.attributes & 0x20 != 0;
         let flip_y = sprite.attributes & 0x40 != 0;
         let behind_bg = sprite.attributes & 0x80 != 0;
-        let palette = if sprite.attributes & 0x10 != 0 {
-            self.obp1
+        let (palette, bank) = if self.cgb_mode {
+            (
+                sprite.attributes & 0x07,
+                ((sprite.attributes as usize >> 3) & 1) * 0x2000,
+            )
         } else {
-            self.obp0
+            ((sprite.attributes >> 4) & 1, 0)
         };
         let mut row = if flip_y {
             sprite_height - 1 - line
@@ -444,10 +565,12 @@ impl Ppu {
             row &= 0x07;
         }
 
-        let row_addr = ((tile_number as usize) * 16 + row * 2) & 0x1FFF;
+        let row_addr = bank | (((tile_number as usize) * 16 + row * 2) & 0x1FFF);
         let tile_data_low = self.vram[row_addr];
-        let tile_data_high = self.vram[(row_addr + 1) & 0x1FFF];
+        let tile_data_high = self.vram[row_addr + 1];
         let left_edge = i16::from(sprite.x) - 8;
+        // CGB resolves overlapping sprites by OAM index unless OPRI asks for DMG ordering
+        let oam_priority = self.cgb_mode && self.opri & 0x01 == 0;
 
         for x in 0..8 {
             let screen_x = left_edge + x as i16;
@@ -465,24 +588,56 @@ impl Ppu {
             if self.sprite_fifo.len() <= queue_index {
                 self.sprite_fifo.resize(queue_index + 1, None);
             }
-            if self.sprite_fifo[queue_index].is_none() {
+            let replace = match self.sprite_fifo[queue_index] {
+                None => true,
+                Some(existing) => oam_priority && sprite.oam_index < existing.oam_index,
+            };
+            if replace {
                 self.sprite_fifo[queue_index] = Some(SpritePixel {
                     color,
                     palette,
                     behind_bg,
+                    oam_index: sprite.oam_index,
                 });
             }
         }
     }
 
-    fn resolve_pixel(&self, bg_pixel: BgPixel, sprite_pixel: Option<SpritePixel>) -> u8 {
+    fn resolve_pixel(&self, bg_pixel: BgPixel, sprite_pixel: Option<SpritePixel>) -> u16 {
+        if self.cgb_mode {
+            return self.resolve_cgb_pixel(bg_pixel, sprite_pixel);
+        }
+
         if let Some(sprite_pixel) = sprite_pixel {
             if !self.bg_enabled() || !sprite_pixel.behind_bg || bg_pixel.color == 0 {
-                return (sprite_pixel.palette >> (sprite_pixel.color * 2)) & 0x03;
+                let palette = if sprite_pixel.palette == 0 {
+                    self.obp0
+                } else {
+                    self.obp1
+                };
+                return u16::from((palette >> (sprite_pixel.color * 2)) & 0x03);
+            }
+        }
+
+        u16::from((self.bgp >> (bg_pixel.color * 2)) & 0x03)
+    }
+
+    fn resolve_cgb_pixel(&self, bg_pixel: BgPixel, sprite_pixel: Option<SpritePixel>) -> u16 {
+        if let Some(sprite_pixel) = sprite_pixel {
+            // With LCDC bit 0 cleared, sprites are always drawn on top of BG and window
+            let bg_wins = self.bg_enabled()
+                && bg_pixel.color != 0
+                && (bg_pixel.priority || sprite_pixel.behind_bg);
+            if !bg_wins {
+                return cgb_color(
+                    &self.obj_palette_ram,
+                    sprite_pixel.palette,
+                    sprite_pixel.color,
+                );
             }
         }
 
-        (self.bgp >> (bg_pixel.color * 2)) & 0x03
+        cgb_color(&self.bg_palette_ram, bg_pixel.palette, bg_pixel.color)
     }
 
     fn tick_transfer(&mut self) -> bool {
@@ -534,14 +689,65 @@ impl Ppu {
         self.visible_x >= 160
     }
 
-    pub fn get_screen(&mut self) -> &[[u8; 160]; 144] {
+    /// Returns the last finished frame. In CGB mode every entry is an RGB555 color,
+    /// otherwise it holds the DMG shade (0-3) after applying BGP/OBP0/OBP1.
+    pub fn get_screen(&mut self) -> &[[u16; 160]; 144] {
         self.frame_ready = false;
-        &self.screen
+        &self.front_screen
+    }
+
+    // Called once the last line of a frame is drawn
+    fn finish_frame(&mut self) {
+        if self.skip_frame {
+            self.skip_frame = false;
+            self.blank_frame();
+        } else {
+            self.front_screen.copy_from_slice(&self.screen[..]);
+            self.frame_ready = true;
+        }
+    }
+
+    /// Ends a frame with the screen blank, like while the LCD is off.
+    pub(crate) fn blank_frame(&mut self) {
+        let white = if self.cgb_mode { 0x7FFF } else { 0 };
+        for line in self.front_screen.iter_mut() {
+            line.fill(white);
+        }
+        self.frame_ready = true;
     }
 
     pub fn get_ly(&self) -> u8 {
         self.ly
     }
+
+    // HDMA writes go straight to the selected VRAM bank, regardless of the PPU mode
+    pub fn write_vram_dma(&mut self, address: u16, value: u8) {
+        let bank = if self.cgb_mode { self.vbk as usize } else { 0 };
+        self.vram[(bank * 0x2000) | ((address & 0x1FFF) as usize)] = value;
+    }
+}
+
+pub(crate) fn cgb_color(palette_ram: &[u8; 64], palette: u8, color: u8) -> u16 {
+    let index = (palette as usize) * 8 + (color as usize) * 2;
+    (u16::from(palette_ram[index]) | (u16::from(palette_ram[index + 1]) << 8)) & 0x7FFF
+}
+
+fn read_palette_data(palette_ram: &[u8; 64], spec: u8, mode: PpuMode) -> u8 {
+    if mode == PpuMode::Transferring {
+        0xFF
+    } else {
+        palette_ram[(spec & 0x3F) as usize]
+    }
+}
+
+fn write_palette_data(palette_ram: &mut [u8; 64], spec: &mut u8, value: u8, mode: PpuMode) {
+    if mode != PpuMode::Transferring {
+        palette_ram[(*spec & 0x3F) as usize] = value;
+    }
+    // The index still advances when the write itself is blocked
+    if *spec & 0x80 != 0 {
+        *spec = 0x80 | (spec.wrapping_add(1) & 0x3F);
+    }
 }
 
 impl Memory for Ppu {
@@ -581,12 +787,17 @@ impl Memory for Ppu {
             0xFF4A => self.wy,
             0xFF4B => self.wx,
             0xFF4D => self.key1,
-            0xFF4F => self.vbk,
-            0xFF51..=0xFF55 => self.hdma.read_byte(address),
-            0xFF68 => self.bcps,
-            0xFF69 => self.bcpd,
-            0xFF6A => self.ocps,
-            0xFF6B => self.ocpd,
+            0xFF4F if self.cgb_mode => 0xFE | self.vbk,
+            0xFF51..=0xFF55 if self.cgb_mode => self.hdma.read_byte(address),
+            0xFF68 if self.cgb_mode => self.bcps | 0x40,
+            0xFF69 if self.cgb_mode => {
+                read_palette_data(&self.bg_palette_ram, self.bcps, self.get_ppu_mode())
+            }
+            0xFF6A if self.cgb_mode => self.ocps | 0x40,
+            0xFF6B if self.cgb_mode => {
+                read_palette_data(&self.obj_palette_ram, self.ocps, self.get_ppu_mode())
+            }
+            0xFF6C if self.cgb_mode => 0xFE | self.opri,
             0xFF70 => self.svbk,
             _ => 0xFF,
         }
@@ -649,12 +860,19 @@ impl Memory for Ppu {
             0xFF4A => self.wy = value,
             0xFF4B => self.wx = value,
             0xFF4D => self.key1 = value,
-            0xFF4F => self.vbk = value,
-            0xFF51..=0xFF55 => self.hdma.write_byte(address, value),
-            0xFF68 => self.bcps = value,
-            0xFF69 => self.bcpd = value,
-            0xFF6A => self.ocps = value,
-            0xFF6B => self.ocpd = value,
+            0xFF4F if self.cgb_mode => self.vbk = value & 0x01,
+            0xFF51..=0xFF55 if self.cgb_mode => self.hdma.write_byte(address, value),
+            0xFF68 if self.cgb_mode => self.bcps = value & 0xBF,
+            0xFF69 if self.cgb_mode => {
+                let mode = self.get_ppu_mode();
+                write_palette_data(&mut self.bg_palette_ram, &mut self.bcps, value, mode);
+            }
+            0xFF6A if self.cgb_mode => self.ocps = value & 0xBF,
+            0xFF6B if self.cgb_mode => {
+                let mode = self.get_ppu_mode();
+                write_palette_data(&mut self.obj_palette_ram, &mut self.ocps, value, mode);
+            }
+            0xFF6C if self.cgb_mode => self.opri = value & 0x01,
             0xFF70 => self.svbk = value,
             _ => {}
         };
@@ -688,6 +906,7 @@ impl Gb {
                         if self.ppu.ly == 144 {
                             self.ppu.set_mode(PpuMode::VBlank);
                             self.request_interrupt(Interrupt::Vblank);
+                            self.apply_gameshark_codes();
                             if self.ppu.stat & 0x10 != 0 {
                                 self.request_interrupt(Interrupt::LcdStat);
                             }
@@ -707,7 +926,8 @@ impl Gb {
 
                         if self.ppu.ly > 153 {
                             self.ppu.ly = 0;
-                            self.ppu.frame_ready = true;
+                            self.ppu.finish_frame();
+                            self.frame_cycles = 0;
                             self.ppu.window_line_counter = 0;
                             self.ppu.start_oam_search();
                             if self.ppu.stat & 0x20 != 0 {
@@ -732,6 +952,7 @@ impl Gb {
                         if self.ppu.stat & 0x08 != 0 {
                             self.request_interrupt(Interrupt::LcdStat);
                         }
+                        self.hdma_hblank();
                     }
                 }
             }
@@ -0,0 +1,252 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Decodes VRAM, OAM and the palettes into RGBA images for debug viewers. Nothing here
+//! touches the PPU state, so it can be called at any point of a frame.
+
+use crate::gb::Gb;
+use crate::ppu::cgb_color;
+
+/// DMG shades for the color numbers 0-3, white to black.
+pub const GREYSCALE: [[u8; 4]; 4] = [
+    [0xFF, 0xFF, 0xFF, 0xFF],
+    [0xC0, 0xC0, 0xC0, 0xFF],
+    [0x60, 0x60, 0x60, 0xFF],
+    [0x00, 0x00, 0x00, 0xFF],
+];
+
+const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+// The tile data area holds 384 tiles, shown as a 16x24 grid
+const TILES_PER_ROW: usize = 16;
+const TILE_COUNT: usize = 384;
+
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major, 4 bytes per pixel.
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        let index = (y * self.width + x) * 4;
+        self.pixels[index..index + 4].copy_from_slice(&color);
+    }
+}
+
+pub fn rgb555_to_rgba(color: u16) -> [u8; 4] {
+    let scale = |c: u16| -> u8 {
+        let c = (c & 0x1F) as u8;
+        (c << 3) | (c >> 2)
+    };
+    [scale(color), scale(color >> 5), scale(color >> 10), 0xFF]
+}
+
+/// Scroll and window registers, for drawing where the screen sits on the tile maps.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    /// Tile map (0 for 0x9800, 1 for 0x9C00) used by the background.
+    pub bg_map: usize,
+    pub window_map: usize,
+    pub scx: u8,
+    pub scy: u8,
+    pub wx: u8,
+    pub wy: u8,
+    pub window_enabled: bool,
+}
+
+pub struct SpriteInfo {
+    pub index: usize,
+    pub y: u8,
+    pub x: u8,
+    pub tile: u8,
+    pub attributes: u8,
+    /// The sprite with its palette applied, 8 pixels wide and 8 or 16 tall.
+    pub image: RgbaImage,
+}
+
+impl SpriteInfo {
+    pub fn behind_bg(&self) -> bool {
+        self.attributes & 0x80 != 0
+    }
+
+    pub fn flip_y(&self) -> bool {
+        self.attributes & 0x40 != 0
+    }
+
+    pub fn flip_x(&self) -> bool {
+        self.attributes & 0x20 != 0
+    }
+
+    /// Whether the sprite is inside the visible area of the screen.
+    pub fn visible(&self) -> bool {
+        (1..168).contains(&self.x) && (1..160).contains(&self.y)
+    }
+}
+
+/// CGB palette RAM decoded into colors, 8 palettes of 4 colors each.
+pub struct CgbPalettes {
+    pub background: [[[u8; 4]; 4]; 8],
+    pub objects: [[[u8; 4]; 4]; 8],
+}
+
+// Color number of pixel (x, y) of the tile starting at `address`
+fn tile_pixel(vram: &[u8], address: usize, x: usize, y: usize) -> u8 {
+    let low = vram[address + y * 2];
+    let high = vram[address + y * 2 + 1];
+    let bit = 7 - x;
+    (((high >> bit) & 1) << 1) | ((low >> bit) & 1)
+}
+
+impl Gb {
+    /// All 384 tiles of a VRAM bank, drawn with the raw color numbers as a 128x192 image.
+    pub fn tile_data_image(&self, bank: usize, shades: &[[u8; 4]; 4]) -> RgbaImage {
+        let mut image = RgbaImage::new(TILES_PER_ROW * 8, TILE_COUNT / TILES_PER_ROW * 8);
+        let base = bank * 0x2000;
+        if base >= self.ppu.vram.len() {
+            return image;
+        }
+        for tile in 0..TILE_COUNT {
+            let (tile_x, tile_y) = (tile % TILES_PER_ROW * 8, tile / TILES_PER_ROW * 8);
+            for y in 0..8 {
+                for x in 0..8 {
+                    let color = tile_pixel(&self.ppu.vram, base + tile * 16, x, y);
+                    image.set_pixel(tile_x + x, tile_y + y, shades[color as usize]);
+                }
+            }
+        }
+        image
+    }
+
+    /// One of the two 32x32 tile maps as a 256x256 image, using the current tile data
+    /// addressing mode, BG palettes and, in CGB mode, the tile attributes.
+    pub fn tile_map_image(&self, map: usize, shades: &[[u8; 4]; 4]) -> RgbaImage {
+        let ppu = &self.ppu;
+        let mut image = RgbaImage::new(256, 256);
+        let map_base = if map == 0 { 0x1800 } else { 0x1C00 };
+        for tile_y in 0..32 {
+            for tile_x in 0..32 {
+                let map_address = map_base + tile_y * 32 + tile_x;
+                let tile_number = ppu.vram[map_address];
+                let attributes = if ppu.cgb_mode {
+                    ppu.vram[0x2000 | map_address]
+                } else {
+                    0
+                };
+                let bank = ((attributes as usize >> 3) & 1) * 0x2000;
+                let address = if ppu.lcdc & 0x10 != 0 {
+                    bank | (tile_number as usize * 16)
+                } else {
+                    bank | (0x1000i32 + i32::from(tile_number as i8) * 16) as usize
+                };
+                for y in 0..8 {
+                    for x in 0..8 {
+                        let source_x = if attributes & 0x20 != 0 { 7 - x } else { x };
+                        let source_y = if attributes & 0x40 != 0 { 7 - y } else { y };
+                        let color = tile_pixel(&ppu.vram, address, source_x, source_y);
+                        let rgba = if ppu.cgb_mode {
+                            rgb555_to_rgba(cgb_color(&ppu.bg_palette_ram, attributes & 0x07, color))
+                        } else {
+                            shades[((ppu.bgp >> (color * 2)) & 0x03) as usize]
+                        };
+                        image.set_pixel(tile_x * 8 + x, tile_y * 8 + y, rgba);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        let lcdc = self.ppu.lcdc;
+        Viewport {
+            bg_map: ((lcdc >> 3) & 1) as usize,
+            window_map: ((lcdc >> 6) & 1) as usize,
+            scx: self.ppu.scx,
+            scy: self.ppu.scy,
+            wx: self.ppu.wx,
+            wy: self.ppu.wy,
+            window_enabled: lcdc & 0x20 != 0,
+        }
+    }
+
+    /// The 40 OAM entries, with color 0 left transparent in the images.
+    pub fn sprites(&self, shades: &[[u8; 4]; 4]) -> Vec<SpriteInfo> {
+        let ppu = &self.ppu;
+        let height = if ppu.lcdc & 0x04 != 0 { 16 } else { 8 };
+        ppu.oam
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(index, entry)| {
+                let (y, x, tile, attributes) = (entry[0], entry[1], entry[2], entry[3]);
+                let bank = if ppu.cgb_mode {
+                    ((attributes as usize >> 3) & 1) * 0x2000
+                } else {
+                    0
+                };
+                let first_tile = if height == 16 { tile & !1 } else { tile };
+                let mut image = RgbaImage::new(8, height);
+                for row in 0..height {
+                    let source_row = if attributes & 0x40 != 0 {
+                        height - 1 - row
+                    } else {
+                        row
+                    };
+                    let address = bank | (first_tile as usize * 16 + (source_row / 8) * 16);
+                    for x in 0..8 {
+                        let source_x = if attributes & 0x20 != 0 { 7 - x } else { x };
+                        let color = tile_pixel(&ppu.vram, address, source_x, source_row % 8);
+                        let rgba = if color == 0 {
+                            TRANSPARENT
+                        } else if ppu.cgb_mode {
+                            rgb555_to_rgba(cgb_color(
+                                &ppu.obj_palette_ram,
+                                attributes & 0x07,
+                                color,
+                            ))
+                        } else {
+                            let palette = if attributes & 0x10 != 0 {
+                                ppu.obp1
+                            } else {
+                                ppu.obp0
+                            };
+                            shades[((palette >> (color * 2)) & 0x03) as usize]
+                        };
+                        image.set_pixel(x, row, rgba);
+                    }
+                }
+                SpriteInfo {
+                    index,
+                    y,
+                    x,
+                    tile,
+                    attributes,
+                    image,
+                }
+            })
+            .collect()
+    }
+
+    pub fn cgb_palettes(&self) -> CgbPalettes {
+        let decode = |palette_ram: &[u8; 64]| {
+            std::array::from_fn(|palette| {
+                std::array::from_fn(|color| {
+                    rgb555_to_rgba(cgb_color(palette_ram, palette as u8, color as u8))
+                })
+            })
+        };
+        CgbPalettes {
+            background: decode(&self.ppu.bg_palette_ram),
+            objects: decode(&self.ppu.obj_palette_ram),
+        }
+    }
+}
@@ -1,43 +1,140 @@
 // SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
 //
 // SPDX-License-Identifier: MIT
-use super::cartridge::{load_cartridge, GbMode};
+use super::cartridge::{load_cartridge, CartridgeError, CartridgeOptions, GbMode, RtcMode};
 use super::gb::{Gb, GbTypes};
+use super::power_on::RamPattern;
+use super::storage::{FileStorage, MemoryStorage, SaveStorage};
 
-pub struct GbInstance;
+/// Why [`GbInstance`] couldn't build a [`Gb`].
+#[derive(Debug)]
+pub enum LoadError {
+    Cartridge(CartridgeError),
+    /// The boot ROM doesn't fit the model the cartridge runs on.
+    BootRom(String),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Cartridge(e) => write!(f, "Error loading ROM: {e}"),
+            LoadError::BootRom(e) => write!(f, "Error loading boot ROM: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Cartridge(e) => Some(e),
+            LoadError::BootRom(_) => None,
+        }
+    }
+}
+
+impl From<CartridgeError> for LoadError {
+    fn from(e: CartridgeError) -> Self {
+        LoadError::Cartridge(e)
+    }
+}
+
+/// Builds a [`Gb`] for a cartridge. [`GbInstance::build`] and [`GbInstance::build_from_bytes`]
+/// use the defaults, the rest goes through the builder:
+///
+/// ```no_run
+/// # use sturdygb_core::cartridge::CartridgeOptions;
+/// # use sturdygb_core::power_on::RamPattern;
+/// # use sturdygb_core::prelude::GbInstance;
+/// let gb = GbInstance::default()
+///     .ram_pattern(RamPattern::Seeded(42))
+///     .load_file("game.gb", None, CartridgeOptions::default());
+/// ```
+#[derive(Default)]
+pub struct GbInstance {
+    ram_pattern: RamPattern,
+}
 
 impl GbInstance {
-    pub fn build(filename: &str) -> Result<Gb, String> {
+    pub fn build(
+        filename: &str,
+        boot_rom: Option<Vec<u8>>,
+        options: CartridgeOptions,
+    ) -> Result<Gb, LoadError> {
+        Self::default().load_file(filename, boot_rom, options)
+    }
+
+    pub fn build_from_bytes(
+        rom_data: Vec<u8>,
+        storage: Option<Box<dyn SaveStorage>>,
+        boot_rom: Option<Vec<u8>>,
+        options: CartridgeOptions,
+    ) -> Result<Gb, LoadError> {
+        Self::default().load_bytes(rom_data, storage, boot_rom, options)
+    }
+
+    /// What WRAM and HRAM hold at power on, random by default. Any other pattern also runs
+    /// the cartridge RTC on [`RtcMode::Emulated`], so the same input gives the same run.
+    pub fn ram_pattern(mut self, pattern: RamPattern) -> Self {
+        self.ram_pattern = pattern;
+        self
+    }
+
+    pub fn load_file(
+        self,
+        filename: &str,
+        boot_rom: Option<Vec<u8>>,
+        options: CartridgeOptions,
+    ) -> Result<Gb, LoadError> {
         let gb_type: GbTypes;
-        match load_cartridge(filename) {
+        let mut storage = FileStorage::new(std::path::Path::new(filename).with_extension("sav"));
+        match load_cartridge(filename, &mut storage, options) {
             Ok((mbc, gb_mode)) => {
                 gb_type = if gb_mode == GbMode::CgbMode {
                     GbTypes::Cgb
                 } else {
                     GbTypes::Dmg
                 };
-                Ok(Gb::new(mbc, gb_mode, gb_type))
+                let mut gb = Gb::new(mbc, gb_mode, gb_type);
+                gb.set_save_storage(Box::new(storage));
+                self.finish(gb, boot_rom)
             }
-            Err(e) => Err(format!("Error loading ROM: {e}")),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub fn build_from_bytes(
+    pub fn load_bytes(
+        self,
         rom_data: Vec<u8>,
-        save_path: Option<std::path::PathBuf>,
-    ) -> Result<Gb, String> {
+        storage: Option<Box<dyn SaveStorage>>,
+        boot_rom: Option<Vec<u8>>,
+        options: CartridgeOptions,
+    ) -> Result<Gb, LoadError> {
         use crate::cartridge::load_cartridge_from_bytes;
         let gb_type: GbTypes;
-        match load_cartridge_from_bytes(rom_data, save_path) {
+        let mut storage = storage.unwrap_or_else(|| Box::new(MemoryStorage::default()));
+        match load_cartridge_from_bytes(rom_data, storage.as_mut(), options) {
             Ok((mbc, gb_mode)) => {
                 gb_type = if gb_mode == GbMode::CgbMode {
                     GbTypes::Cgb
                 } else {
                     GbTypes::Dmg
                 };
-                Ok(Gb::new(mbc, gb_mode, gb_type))
+                let mut gb = Gb::new(mbc, gb_mode, gb_type);
+                gb.set_save_storage(storage);
+                self.finish(gb, boot_rom)
             }
-            Err(e) => Err(format!("Error parsing ROM bytes: {e}")),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn finish(&self, mut gb: Gb, boot_rom: Option<Vec<u8>>) -> Result<Gb, LoadError> {
+        if self.ram_pattern != RamPattern::Random {
+            gb.fill_power_on_ram(self.ram_pattern);
+            gb.set_rtc_mode(RtcMode::Emulated);
+        }
+        if let Some(boot_rom) = boot_rom {
+            gb.load_boot_rom(boot_rom).map_err(LoadError::BootRom)?;
         }
+        Ok(gb)
     }
 }
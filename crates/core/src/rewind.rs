@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use std::collections::VecDeque;
+
+use super::gb::Gb;
+
+/// Ring buffer of machine snapshots used to run the game backwards.
+///
+/// Only the newest snapshot is kept whole. Every older one is stored as a delta that turns
+/// its successor back into it: the two snapshots are XORed together and the runs of zeros
+/// left by unchanged bytes are compressed away, so most frames cost a few kilobytes.
+pub struct RewindBuffer {
+    capacity: usize,
+    interval: usize,
+    frames_since_snapshot: usize,
+    current: Option<Vec<u8>>,
+    deltas: VecDeque<Vec<u8>>,
+}
+
+impl RewindBuffer {
+    /// Creates a buffer holding up to `capacity` snapshots, taken every `interval` frames.
+    pub fn new(capacity: usize, interval: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            interval: interval.max(1),
+            frames_since_snapshot: 0,
+            current: None,
+            deltas: VecDeque::new(),
+        }
+    }
+
+    /// Called once per emulated frame, takes a snapshot whenever the interval has elapsed.
+    pub fn push(&mut self, gb: &Gb) {
+        self.frames_since_snapshot += 1;
+        if self.current.is_some() && self.frames_since_snapshot < self.interval {
+            return;
+        }
+        self.frames_since_snapshot = 0;
+
+        let snapshot = gb.save_state();
+        if let Some(previous) = self.current.take() {
+            self.deltas.push_back(encode_delta(&snapshot, &previous));
+            if self.deltas.len() >= self.capacity {
+                self.deltas.pop_front();
+            }
+        }
+        self.current = Some(snapshot);
+    }
+
+    /// Restores the previous snapshot into `gb`. Returns `false` once the oldest snapshot
+    /// has been reached, in which case `gb` is held at that point.
+    pub fn rewind(&mut self, gb: &mut Gb) -> bool {
+        let Some(current) = self.current.take() else {
+            return false;
+        };
+
+        let stepped_back = match self.deltas.pop_back() {
+            Some(delta) => {
+                self.current = Some(apply_delta(&current, &delta));
+                true
+            }
+            None => {
+                self.current = Some(current);
+                false
+            }
+        };
+
+        if let Some(snapshot) = &self.current {
+            // Snapshots come from this same machine, so they can only fail to load if the
+            // buffer was filled by another game; start over in that case.
+            if gb.load_state(snapshot).is_err() {
+                self.clear();
+                return false;
+            }
+        }
+        self.frames_since_snapshot = 0;
+        stepped_back
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.deltas.clear();
+        self.frames_since_snapshot = 0;
+    }
+
+    /// Number of snapshots that can still be rewound to.
+    pub fn len(&self) -> usize {
+        self.deltas.len() + self.current.is_some() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_none()
+    }
+
+    /// Approximate number of bytes used by the stored snapshots.
+    pub fn memory_usage(&self) -> usize {
+        self.current.as_ref().map_or(0, |c| c.len())
+            + self.deltas.iter().map(|d| d.len()).sum::<usize>()
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some(&byte) = data.get(*pos) {
+        *pos += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    value
+}
+
+// Layout: target length, then pairs of (zero run, literal run) over `base ^ target`
+fn encode_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let xor = |i: usize| target[i] ^ base.get(i).copied().unwrap_or(0);
+    let mut out = Vec::new();
+    write_varint(&mut out, target.len());
+
+    let mut i = 0;
+    while i < target.len() {
+        let zeros_start = i;
+        while i < target.len() && xor(i) == 0 {
+            i += 1;
+        }
+        let literal_start = i;
+        while i < target.len() && xor(i) != 0 {
+            i += 1;
+        }
+        write_varint(&mut out, literal_start - zeros_start);
+        write_varint(&mut out, i - literal_start);
+        out.extend((literal_start..i).map(xor));
+    }
+    out
+}
+
+fn apply_delta(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut pos = 0;
+    let len = read_varint(delta, &mut pos);
+    let mut target: Vec<u8> = (0..len)
+        .map(|i| base.get(i).copied().unwrap_or(0))
+        .collect();
+
+    let mut i = 0;
+    while i < len && pos < delta.len() {
+        i += read_varint(delta, &mut pos);
+        let literals = read_varint(delta, &mut pos);
+        for byte in &delta[pos..pos + literals] {
+            target[i] ^= byte;
+            i += 1;
+        }
+        pos += literals;
+    }
+    target
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delta_roundtrip() {
+        let base: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut target = base.clone();
+        target[3] = 0;
+        target[500..520].fill(0xAA);
+        target.truncate(900);
+        target.extend_from_slice(&[1, 2, 3]);
+
+        let delta = encode_delta(&base, &target);
+        assert!(delta.len() < target.len() / 10);
+        assert_eq!(apply_delta(&base, &delta), target);
+        assert_eq!(apply_delta(&target, &encode_delta(&target, &base)), base);
+    }
+}
@@ -0,0 +1,265 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+use std::borrow::Cow;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::cartridge::GbMode;
+use crate::cpu::Cpu;
+use crate::gb::{Gb, SpeedMode};
+use crate::joypad::Joypad;
+use crate::ppu::Ppu;
+use crate::serial::Serial;
+use crate::sound::Sound;
+use crate::timer::Timer;
+
+const SAVE_STATE_MAGIC: &[u8; 4] = b"SGBS";
+// Bump this whenever the layout of any serialized component changes
+const SAVE_STATE_VERSION: u32 = 7;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GbState<'a> {
+    // Title, licensee, header and global checksums, used to reject states from other games
+    rom_header: Vec<u8>,
+    gb_mode: GbMode,
+    cpu: Cow<'a, Cpu>,
+    ppu: Cow<'a, Ppu>,
+    serial: Cow<'a, Serial>,
+    joypad: Cow<'a, Joypad>,
+    sound: Cow<'a, Sound>,
+    timer: Cow<'a, Timer>,
+    wram: Cow<'a, [u8]>,
+    hram: Cow<'a, [u8]>,
+    ram_bank: usize,
+    ie_flag: u8,
+    if_flag: u8,
+    boot_rom: Option<Cow<'a, [u8]>>,
+    prepare_speed_switch: bool,
+    speed_mode: SpeedMode,
+    cycles: u64,
+    frame_cycles: u32,
+    mbc: Vec<u8>,
+}
+
+pub(crate) fn encode_state<T: Serialize>(value: &T) -> Vec<u8> {
+    bincode::serialize(value).expect("save state serialization should never fail")
+}
+
+pub(crate) fn decode_state<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+    bincode::deserialize(data).map_err(|e| format!("Corrupted save state: {e}"))
+}
+
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+pub(crate) fn restore_ram(ram: &mut [u8], saved: &[u8]) -> Result<(), String> {
+    if ram.len() != saved.len() {
+        return Err(format!(
+            "Save state has {} bytes of cartridge RAM, expected {}",
+            saved.len(),
+            ram.len()
+        ));
+    }
+    ram.copy_from_slice(saved);
+    Ok(())
+}
+
+impl Gb {
+    /// Snapshots the whole machine, including the cartridge banking registers, into a
+    /// versioned binary blob that can be restored with [`Gb::load_state`].
+    pub fn save_state(&self) -> Vec<u8> {
+        let state = GbState {
+            rom_header: self.rom_header(),
+            gb_mode: self.gb_mode,
+            cpu: Cow::Borrowed(&self.cpu),
+            ppu: Cow::Borrowed(&self.ppu),
+            serial: Cow::Borrowed(&self.serial),
+            joypad: Cow::Borrowed(&self.joypad),
+            sound: Cow::Borrowed(&self.sound),
+            timer: Cow::Borrowed(&self.timer),
+            wram: Cow::Borrowed(&self.wram),
+            hram: Cow::Borrowed(&self.hram),
+            ram_bank: self.ram_bank,
+            ie_flag: self.ie_flag,
+            if_flag: self.if_flag,
+            boot_rom: self.boot_rom.as_deref().map(Cow::Borrowed),
+            prepare_speed_switch: self.prepare_speed_switch,
+            speed_mode: self.speed_mode,
+            cycles: self.cycles,
+            frame_cycles: self.frame_cycles,
+            mbc: self.mbc.save_state(),
+        };
+
+        let mut data = Vec::from(*SAVE_STATE_MAGIC);
+        data.extend_from_slice(&SAVE_STATE_VERSION.to_le_bytes());
+        data.extend_from_slice(&encode_state(&state));
+        data
+    }
+
+    /// Restores a snapshot taken by [`Gb::save_state`]. The machine is left untouched if the
+    /// state is corrupted, was made by another version or belongs to a different ROM.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        if data.len() < 8 || &data[0..4] != SAVE_STATE_MAGIC {
+            return Err("Not a SturdyGB save state".to_string());
+        }
+        let version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if version != SAVE_STATE_VERSION {
+            return Err(format!(
+                "Unsupported save state version {version} (expected {SAVE_STATE_VERSION})"
+            ));
+        }
+
+        let state: GbState = decode_state(&data[8..])?;
+        if state.rom_header != self.rom_header() || state.gb_mode != self.gb_mode {
+            return Err("Save state belongs to a different ROM".to_string());
+        }
+        // Everything is checked before anything is applied, the mapper goes first as it
+        // checks its own part
+        if state.wram.len() != self.wram.len() || state.hram.len() != self.hram.len() {
+            return Err("Corrupted save state: unexpected RAM size".to_string());
+        }
+        if state.ram_bank == 0 || (state.ram_bank + 1) * 0x1000 > self.wram.len() {
+            return Err("Corrupted save state: invalid WRAM bank".to_string());
+        }
+        let boot_rom_size = if self.gb_mode == GbMode::CgbMode {
+            0x900
+        } else {
+            0x100
+        };
+        if state
+            .boot_rom
+            .as_ref()
+            .is_some_and(|boot_rom| boot_rom.len() != boot_rom_size)
+        {
+            return Err("Corrupted save state: unexpected boot ROM size".to_string());
+        }
+        self.ppu.check_state(&state.ppu)?;
+        self.mbc.load_state(&state.mbc)?;
+        // The state carries its own copy of the battery RAM
+        self.mark_save_dirty();
+
+        let sample_rate = self.sound.get_sample_rate();
+        self.cpu = state.cpu.into_owned();
+        self.ppu = state.ppu.into_owned();
+        self.serial = state.serial.into_owned();
+        self.joypad = state.joypad.into_owned();
+        self.sound = state.sound.into_owned();
+        self.sound.set_sample_rate(sample_rate);
+        self.timer = state.timer.into_owned();
+        self.wram = state.wram.into_owned();
+        self.hram = state.hram.into_owned();
+        self.ram_bank = state.ram_bank;
+        self.ie_flag = state.ie_flag;
+        self.if_flag = state.if_flag;
+        self.boot_rom = state.boot_rom.map(Cow::into_owned);
+        self.prepare_speed_switch = state.prepare_speed_switch;
+        self.speed_mode = state.speed_mode;
+        self.cycles = state.cycles;
+        self.frame_cycles = state.frame_cycles;
+        // Input queued for the cycles left behind would arrive at the wrong time
+        self.input_queue.clear();
+        Ok(())
+    }
+
+    /// A hash of everything [`Gb::save_state`] captures, for quickly checking whether two runs
+    /// are still in sync. It's FNV-1a, so it stays the same across builds and platforms.
+    pub fn state_hash(&self) -> u64 {
+        fnv1a(&self.save_state())
+    }
+
+    fn rom_header(&self) -> Vec<u8> {
+        (0x0134..=0x014F)
+            .map(|address| self.mbc.read_rom(address))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::{load_cartridge, CartridgeOptions};
+    use crate::gb::{Gb, GbTypes};
+    use crate::joypad::JoypadButton;
+    use crate::storage::MemoryStorage;
+
+    fn setup_gb() -> Gb {
+        let (mbc, gb_mode) = load_cartridge(
+            "../../roms/cpu_instrs.gb",
+            &mut MemoryStorage::default(),
+            CartridgeOptions::default(),
+        )
+        .unwrap();
+        Gb::new(mbc, gb_mode, GbTypes::Cgb)
+    }
+
+    #[test]
+    fn test_load_state_resumes_identically() {
+        let mut gb = setup_gb();
+        for _ in 0..30 {
+            gb.run_one_frame();
+        }
+        let state = gb.save_state();
+        for _ in 0..30 {
+            gb.run_one_frame();
+        }
+        let expected_screen = *gb.get_screen_data();
+        let expected_state = gb.save_state();
+        assert_ne!(state, expected_state);
+
+        gb.load_state(&state).unwrap();
+        for _ in 0..30 {
+            gb.run_one_frame();
+        }
+        assert_eq!(*gb.get_screen_data(), expected_screen);
+        assert_eq!(gb.save_state(), expected_state);
+    }
+
+    #[test]
+    fn test_load_state_drops_queued_input() {
+        let mut gb = setup_gb();
+        let state = gb.save_state();
+        gb.queue_input(gb.cycles() + 100, JoypadButton::Start, true);
+        gb.load_state(&state).unwrap();
+        assert!(gb.input_queue.is_empty());
+    }
+
+    #[test]
+    fn test_load_state_rejects_garbage() {
+        let mut gb = setup_gb();
+        assert!(gb.load_state(b"not a state").is_err());
+
+        let mut state = gb.save_state();
+        state[4] = 0xFF;
+        assert!(gb.load_state(&state).is_err());
+    }
+
+    #[test]
+    fn test_bad_state_leaves_machine_untouched() {
+        let mut gb = setup_gb();
+        for _ in 0..10 {
+            gb.run_one_frame();
+        }
+        let saved = gb.save_state();
+        for _ in 0..10 {
+            gb.run_one_frame();
+        }
+        let expected = gb.save_state();
+
+        assert!(gb.load_state(&saved[..saved.len() - 16]).is_err());
+        assert_eq!(gb.save_state(), expected);
+
+        // Decodes fine, but the VRAM doesn't fit this machine
+        let mut state: GbState = decode_state(&saved[8..]).unwrap();
+        state.ppu.to_mut().vram.truncate(0x100);
+        let mut data = saved[..8].to_vec();
+        data.extend_from_slice(&encode_state(&state));
+        assert!(gb.load_state(&data).is_err());
+        assert_eq!(gb.save_state(), expected);
+    }
+}
@@ -2,19 +2,32 @@
 //
 // SPDX-License-Identifier: MIT
 
+use super::cartridge::GbMode;
+use super::gb::Gb;
+use super::interrupts::Interrupt;
 use super::memory::Memory;
 
+// Internal clock runs at 8192 Hz, or 262144 Hz with the CGB fast clock bit set
+const NORMAL_CYCLES_PER_BIT: u32 = 512;
+const FAST_CYCLES_PER_BIT: u32 = 16;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Serial {
     sb: u8,
     sc: u8,
+    cgb_mode: bool,
+    // CPU cycles left until the byte being shifted with the internal clock is done
+    transfer_cycles: u32,
     serial_data: Vec<u8>,
 }
 
 impl Serial {
-    pub fn new() -> Self {
+    pub fn new(gb_mode: &GbMode) -> Self {
         Self {
             sb: 0,
             sc: 0x7E,
+            cgb_mode: *gb_mode == GbMode::CgbMode,
+            transfer_cycles: 0,
             serial_data: Vec::new(),
         }
     }
@@ -28,13 +41,30 @@ impl Serial {
             None
         }
     }
+
+    fn transfer_requested(&self) -> bool {
+        self.sc & 0x80 != 0
+    }
+
+    fn internal_clock(&self) -> bool {
+        self.sc & 0x01 != 0
+    }
+
+    fn cycles_per_bit(&self) -> u32 {
+        if self.cgb_mode && self.sc & 0x02 != 0 {
+            FAST_CYCLES_PER_BIT
+        } else {
+            NORMAL_CYCLES_PER_BIT
+        }
+    }
 }
 
 impl Memory for Serial {
     fn read_byte(&self, address: u16) -> u8 {
         match address {
             0xFF01 => self.sb,
-            0xFF02 => self.sc,
+            0xFF02 if self.cgb_mode => self.sc | 0x7C,
+            0xFF02 => self.sc | 0x7E,
             _ => unreachable!(),
         }
     }
@@ -45,13 +75,62 @@ impl Memory for Serial {
                 self.sb = value;
             }
             0xFF02 => {
-                self.sc = value;
-                if value == 0x81 {
+                self.sc = value & 0x83;
+                if self.transfer_requested() && self.internal_clock() {
+                    self.transfer_cycles = self.cycles_per_bit() * 8;
                     self.serial_data.push(self.sb);
-                    self.sc = 0;
                 }
             }
             _ => unreachable!(),
         };
     }
 }
+
+impl Gb {
+    pub fn serial_write(&mut self, address: u16, value: u8) {
+        self.serial.write_byte(address, value);
+        // The other side of the cable always shifts in whatever is currently in SB
+        if let Some(link) = &mut self.link_port {
+            link.set_outgoing(self.serial.sb);
+        }
+    }
+
+    pub fn serial_tick(&mut self, cycles: u32) {
+        if let Some(incoming) = self.link_port.as_mut().and_then(|link| link.receive()) {
+            // Bytes clocked in by the other side arrive even if no transfer was requested,
+            // but only a pending external clock transfer finishes and raises the interrupt
+            let completes = self.serial.transfer_requested() && !self.serial.internal_clock();
+            self.serial.sb = incoming;
+            if completes {
+                self.finish_serial_transfer();
+            }
+        }
+
+        if !self.serial.transfer_requested() || !self.serial.internal_clock() {
+            return;
+        }
+
+        self.serial.transfer_cycles = self.serial.transfer_cycles.saturating_sub(cycles);
+        if self.serial.transfer_cycles == 0 {
+            // Nothing answers on a disconnected cable, so the line stays high
+            let outgoing = self.serial.sb;
+            let reply = match &mut self.link_port {
+                Some(link) => link.exchange(outgoing),
+                None => Some(0xFF),
+            };
+            // Without a reply yet the transfer stays pending and is retried on the next tick
+            if let Some(reply) = reply {
+                self.serial.sb = reply;
+                self.finish_serial_transfer();
+            }
+        }
+    }
+
+    fn finish_serial_transfer(&mut self) {
+        self.serial.sc &= 0x7F;
+        if let Some(link) = &mut self.link_port {
+            link.set_outgoing(self.serial.sb);
+        }
+        self.request_interrupt(Interrupt::Serial);
+    }
+}
@@ -11,7 +11,7 @@ const DUTY_CYCLES: [[u8; 8]; 4] = [
     [0, 1, 1, 1, 1, 1, 1, 0], // 75%
 ];
 
-#[derive(Default)]
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 struct VolumeEnvelope {
     period: u8,
     direction: i8,
@@ -58,7 +58,7 @@ impl VolumeEnvelope {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 struct Sweep {
     enabled: bool,
     period: u8,
@@ -135,7 +135,7 @@ impl Sweep {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 struct SquareChannel {
     enabled: bool,
     dac_enabled: bool,
@@ -204,7 +204,7 @@ impl SquareChannel {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 struct WaveChannel {
     enabled: bool,
     dac_enabled: bool,
@@ -263,7 +263,7 @@ impl WaveChannel {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
 struct NoiseChannel {
     enabled: bool,
     dac_enabled: bool,
@@ -340,6 +340,7 @@ impl NoiseChannel {
     }
 }
 
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Sound {
     enabled: bool,
     vin_left: bool,
@@ -354,11 +355,12 @@ pub struct Sound {
     ch3: WaveChannel,
     ch4: NoiseChannel,
 
-    frame_seq_timer: u16,
     frame_seq_step: u8,
 
     sample_accumulator: u32,
+    #[serde(skip)]
     sample_rate: u32,
+    #[serde(skip)]
     audio_buffer: std::cell::RefCell<Vec<f32>>,
     
     cap_left: std::cell::Cell<f32>,
@@ -379,7 +381,6 @@ impl Sound {
             ch2: SquareChannel::new(false),
             ch3: WaveChannel::default(),
             ch4: NoiseChannel::new(),
-            frame_seq_timer: 8192,
             frame_seq_step: 0,
             sample_accumulator: 0,
             sample_rate: 44100,
@@ -393,6 +394,10 @@ impl Sound {
         self.sample_rate = rate;
     }
 
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
     pub fn tick(&mut self, cycles: u32) {
         if !self.enabled {
             return;
@@ -405,13 +410,6 @@ impl Sound {
             self.ch3.tick();
             self.ch4.tick();
 
-            // Frame Sequencer
-            self.frame_seq_timer -= 1;
-            if self.frame_seq_timer == 0 {
-                self.frame_seq_timer = 8192;
-                self.step_frame_sequencer();
-            }
-
             // Downsample
             self.sample_accumulator += self.sample_rate;
             if self.sample_accumulator >= 4194304 {
@@ -421,6 +419,13 @@ impl Sound {
         }
     }
 
+    /// Called on the falling edge of the DIV bit driving the frame sequencer.
+    pub fn clock_frame_sequencer(&mut self) {
+        if self.enabled {
+            self.step_frame_sequencer();
+        }
+    }
+
     fn step_frame_sequencer(&mut self) {
         // Step 0, 2, 4, 6: Length
         if self.frame_seq_step % 2 == 0 {
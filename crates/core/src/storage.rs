@@ -0,0 +1,228 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Where battery-backed cartridge RAM (and the RTC state stored after it) is kept.
+//!
+//! The mappers only hold the RAM. It's loaded from a [`SaveStorage`] when the cartridge is
+//! created, and the [`Gb`] writes it back about a second after the game changes it, when
+//! [`Gb::flush_save`] is called and when the [`Gb`] is dropped.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::gb::Gb;
+
+// One second of emulated time, in base clock T-cycles
+const AUTOSAVE_DELAY: u32 = 4_194_304;
+
+pub trait SaveStorage {
+    /// The stored save, `None` when there isn't one yet.
+    fn load(&mut self) -> Result<Option<Vec<u8>>, String>;
+
+    fn store(&mut self, data: &[u8]) -> Result<(), String>;
+}
+
+/// A `.sav` file, written to a temporary file first and renamed over the old one so a
+/// crash halfway through never leaves a truncated save behind.
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl SaveStorage for FileStorage {
+    fn load(&mut self) -> Result<Option<Vec<u8>>, String> {
+        match fs::read(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read save {}: {e}", self.path.display())),
+        }
+    }
+
+    fn store(&mut self, data: &[u8]) -> Result<(), String> {
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+        let error =
+            |e: std::io::Error| format!("Failed to write save {}: {e}", self.path.display());
+
+        let mut file = fs::File::create(&temp_path).map_err(error)?;
+        file.write_all(data).map_err(error)?;
+        file.sync_all().map_err(error)?;
+        fs::rename(&temp_path, &self.path).map_err(error)
+    }
+}
+
+/// Keeps the save in memory only, for ROMs loaded without a place to save to.
+#[derive(Default)]
+pub struct MemoryStorage {
+    data: Option<Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new(data: Option<Vec<u8>>) -> Self {
+        Self { data }
+    }
+
+    pub fn data(&self) -> Option<&[u8]> {
+        self.data.as_deref()
+    }
+}
+
+impl SaveStorage for MemoryStorage {
+    fn load(&mut self) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.data.clone())
+    }
+
+    fn store(&mut self, data: &[u8]) -> Result<(), String> {
+        self.data = Some(data.to_vec());
+        Ok(())
+    }
+}
+
+pub(crate) struct BatterySave {
+    storage: Box<dyn SaveStorage>,
+    dirty: bool,
+    // T-cycles since the first change that hasn't been saved yet
+    pending_cycles: u32,
+    error: Option<String>,
+}
+
+impl Default for BatterySave {
+    fn default() -> Self {
+        Self {
+            storage: Box::new(MemoryStorage::default()),
+            dirty: false,
+            pending_cycles: 0,
+            error: None,
+        }
+    }
+}
+
+impl Gb {
+    /// Replaces where the battery RAM is saved. The current contents are written to the new
+    /// storage the next time the game changes them.
+    pub fn set_save_storage(&mut self, storage: Box<dyn SaveStorage>) {
+        self.battery_save.storage = storage;
+    }
+
+    /// Writes the battery RAM out if it changed since it was last saved.
+    pub fn flush_save(&mut self) -> Result<(), String> {
+        if !self.battery_save.dirty {
+            return Ok(());
+        }
+        // A failed write is tried again a second later
+        self.battery_save.pending_cycles = 0;
+        if let Some(data) = self.mbc.get_battery_ram() {
+            self.battery_save.storage.store(&data)?;
+        }
+        self.battery_save.dirty = false;
+        Ok(())
+    }
+
+    /// The last error hit while autosaving, if any.
+    pub fn take_save_error(&mut self) -> Option<String> {
+        self.battery_save.error.take()
+    }
+
+    pub(crate) fn mark_save_dirty(&mut self) {
+        // Writes to RAM that isn't battery backed have nothing to save
+        self.battery_save.dirty |= self.mbc.has_battery();
+    }
+
+    pub(crate) fn autosave_tick(&mut self, cycles: u32) {
+        if !self.battery_save.dirty {
+            return;
+        }
+        self.battery_save.pending_cycles += cycles;
+        if self.battery_save.pending_cycles >= AUTOSAVE_DELAY {
+            if let Err(e) = self.flush_save() {
+                self.battery_save.error = Some(e);
+            }
+        }
+    }
+}
+
+impl Drop for Gb {
+    fn drop(&mut self) {
+        let _ = self.flush_save();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::prelude::GbInstance;
+
+    #[test]
+    fn test_file_storage_round_trip() {
+        let path = std::env::temp_dir().join(format!("sturdygb_{}.sav", std::process::id()));
+        let mut storage = FileStorage::new(&path);
+        assert_eq!(storage.load(), Ok(None));
+        storage.store(&[1, 2, 3]).unwrap();
+        storage.store(&[4, 5]).unwrap();
+        assert_eq!(storage.load(), Ok(Some(vec![4, 5])));
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        assert!(!Path::new(&temp_path).exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_battery_ram_round_trip() {
+        let path = std::env::temp_dir().join(format!("sturdygb_mbc5_{}.sav", std::process::id()));
+        // MBC5 with battery-backed RAM, 8 KiB of it
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = 0x1B;
+        rom[0x149] = 0x02;
+        let load = || {
+            let options = CartridgeOptions {
+                ignore_header_checksum: true,
+            };
+            let storage = Box::new(FileStorage::new(&path));
+            GbInstance::build_from_bytes(rom.clone(), Some(storage), None, options).unwrap()
+        };
+
+        let mut gb = load();
+        gb.flush_save().unwrap();
+        assert!(!path.exists());
+        gb.write_byte(0x0000, 0x0A);
+        gb.write_byte(0xA123, 0x42);
+        gb.flush_save().unwrap();
+        assert_eq!(fs::read(&path).unwrap()[0x123], 0x42);
+
+        gb.write_byte(0xA124, 0x43);
+        drop(gb);
+        let mut gb = load();
+        gb.write_byte(0x0000, 0x0A);
+        assert_eq!(gb.read_byte(0xA124), 0x43);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_ram_without_battery_is_not_saved() {
+        // MBC5 with 8 KiB of RAM but no battery
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = 0x1A;
+        rom[0x149] = 0x02;
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb = GbInstance::build_from_bytes(rom, None, None, options).unwrap();
+        gb.write_byte(0x0000, 0x0A);
+        gb.write_byte(0xA123, 0x42);
+        assert_eq!(gb.read_byte(0xA123), 0x42);
+        assert!(!gb.battery_save.dirty);
+    }
+}
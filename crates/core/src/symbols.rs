@@ -0,0 +1,158 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Symbol files in the `bank:address label` format written by RGBDS (`rgblink -n`) and
+//! read by no$gmb, BGB and most other debuggers:
+//!
+//! ```text
+//! ; File generated by rgblink
+//! 00:0150 Main
+//! 01:4000 LoadLevel
+//! 00:C000 wPlayerX
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::cartridge::GbMode;
+use crate::gb::Gb;
+
+#[derive(Default)]
+pub struct Symbols {
+    by_address: BTreeMap<(usize, u16), String>,
+    by_name: HashMap<String, (usize, u16)>,
+}
+
+// Symbols only resolve to addresses in the same memory area, a label at the end of one
+// area says nothing about the start of the next
+fn area(address: u16) -> u8 {
+    match address {
+        0x0000..=0x3FFF => 0,
+        0x4000..=0x7FFF => 1,
+        0x8000..=0x9FFF => 2,
+        0xA000..=0xBFFF => 3,
+        0xC000..=0xCFFF => 4,
+        0xD000..=0xDFFF => 5,
+        0xE000..=0xFF7F => 6,
+        0xFF80..=0xFFFF => 7,
+    }
+}
+
+impl Symbols {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut symbols = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || format!("Invalid symbol on line {}: {line}", number + 1);
+            let (location, name) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let (bank, address) = location.split_once(':').ok_or_else(invalid)?;
+            let bank = usize::from_str_radix(bank, 16).map_err(|_| invalid())?;
+            let address = u16::from_str_radix(address, 16).map_err(|_| invalid())?;
+            symbols.insert(bank, address, name.trim());
+        }
+        Ok(symbols)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read symbols {}: {e}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn insert(&mut self, bank: usize, address: u16, name: &str) {
+        // The first label wins when several share an address, like a section and the
+        // routine at its start
+        self.by_address
+            .entry((bank, address))
+            .or_insert_with(|| name.to_string());
+        self.by_name.insert(name.to_string(), (bank, address));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_address.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_address.len()
+    }
+
+    /// Bank and address of a label.
+    pub fn lookup(&self, name: &str) -> Option<(usize, u16)> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Label placed exactly at `address` in `bank`.
+    pub fn label(&self, bank: usize, address: u16) -> Option<&str> {
+        self.by_address.get(&(bank, address)).map(String::as_str)
+    }
+
+    /// Closest label at or before `address` in `bank`, with the distance from it.
+    pub fn nearest(&self, bank: usize, address: u16) -> Option<(&str, u16)> {
+        self.by_address
+            .range(..=(bank, address))
+            .next_back()
+            .filter(|((symbol_bank, symbol_address), _)| {
+                *symbol_bank == bank && area(*symbol_address) == area(address)
+            })
+            .map(|((_, symbol_address), name)| (name.as_str(), address - symbol_address))
+    }
+}
+
+impl Gb {
+    /// Bank mapped at `address` right now, as numbered in symbol files.
+    pub fn mapped_bank(&self, address: u16) -> usize {
+        match address {
+            0x4000..=0x7FFF => self.mbc.rom_bank(),
+            0x8000..=0x9FFF if self.gb_mode == GbMode::CgbMode => {
+                (self.read_byte(0xFF4F) & 0x01) as usize
+            }
+            0xD000..=0xDFFF => self.ram_bank.max(1),
+            _ => 0,
+        }
+    }
+
+    /// Label at `address` in the bank currently mapped there.
+    pub fn symbol_at(&self, address: u16) -> Option<&str> {
+        self.symbols.label(self.mapped_bank(address), address)
+    }
+
+    /// `label` or `label+offset` for the closest label at or before `address`.
+    pub fn describe_address(&self, address: u16) -> Option<String> {
+        let (name, offset) = self.symbols.nearest(self.mapped_bank(address), address)?;
+        Some(if offset == 0 {
+            name.to_string()
+        } else {
+            format!("{name}+{offset:X}")
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_symbols() {
+        let symbols = Symbols::parse(
+            "; File generated by rgblink\n\
+             00:0150 Main\n\
+             01:4000 LoadLevel ; comment\n\
+             00:C000 wPlayerX\n\
+             \n",
+        )
+        .unwrap();
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols.lookup("LoadLevel"), Some((1, 0x4000)));
+        assert_eq!(symbols.label(0, 0x0150), Some("Main"));
+        assert_eq!(symbols.label(1, 0x0150), None);
+        assert_eq!(symbols.nearest(0, 0x0153), Some(("Main", 3)));
+        // wPlayerX is in WRAM, not after Main in ROM
+        assert_eq!(symbols.nearest(0, 0xC000), Some(("wPlayerX", 0)));
+        assert_eq!(symbols.nearest(0, 0x4000), None);
+        assert!(Symbols::parse("0150 Main").is_err());
+    }
+}
@@ -2,91 +2,195 @@
 //
 // SPDX-License-Identifier: MIT
 
-use super::gb::Gb;
+use super::gb::{Gb, SpeedMode};
 use super::interrupts::Interrupt;
 use super::memory::Memory;
 
+// What happens to TIMA in the M-cycles after it overflows
+#[derive(Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum Reload {
+    None,
+    // TIMA overflowed and reads 0, writing to it now cancels the reload and the interrupt
+    Pending,
+    // TIMA was just loaded from TMA, writes to TIMA are ignored and writes to TMA go through
+    Reloading,
+}
+
+/// DIV is the upper byte of a 16-bit counter that runs at the CPU clock. TIMA counts the
+/// falling edges of one of its bits, ANDed with the enable bit in TAC, which is why
+/// resetting DIV or changing TAC can increment TIMA.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Timer {
-    div: u8,
+    counter: u16,
     tima: u8,
     tma: u8,
     tac: u8,
-    frequency: u32,
-    enabled: bool,
-    ticks: u32,
-    cycles: u32,
+    reload: Reload,
 }
 
-fn get_frequency_values(tac: u8) -> u32 {
+// Counter bit selected by the clock select bits of TAC
+fn get_counter_bit(tac: u8) -> u16 {
     match tac & 3 {
-        1 => 16,
-        2 => 64,
-        3 => 256,
-        _ => 1024,
+        1 => 1 << 3,
+        2 => 1 << 5,
+        3 => 1 << 7,
+        _ => 1 << 9,
+    }
+}
+
+// DIV bit clocking the APU frame sequencer at 512 Hz, one higher in double speed mode
+fn get_div_apu_bit(speed_mode: SpeedMode) -> u16 {
+    match speed_mode {
+        SpeedMode::Normal => 1 << 12,
+        SpeedMode::Double => 1 << 13,
     }
 }
 
 impl Timer {
-    pub fn new(div: u8) -> Self {
-        let tac: u8 = 0xF8;
-        let frequency = get_frequency_values(tac);
+    pub fn new(counter: u16) -> Self {
         Self {
-            div,
+            counter,
             tima: 0,
             tma: 0,
-            tac,
-            frequency,
-            enabled: false,
-            ticks: 0,
-            cycles: 0,
+            tac: 0,
+            reload: Reload::None,
+        }
+    }
+
+    fn signal(&self) -> bool {
+        self.tac & 0x04 != 0 && self.counter & get_counter_bit(self.tac) != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (tima, overflow) = self.tima.overflowing_add(1);
+        self.tima = tima;
+        if overflow {
+            self.reload = Reload::Pending;
         }
     }
+
+    // Runs the timer for one M-cycle, returns true when the timer interrupt is requested
+    fn step(&mut self) -> bool {
+        let interrupt = match self.reload {
+            Reload::Pending => {
+                self.tima = self.tma;
+                self.reload = Reload::Reloading;
+                true
+            }
+            Reload::Reloading | Reload::None => {
+                self.reload = Reload::None;
+                false
+            }
+        };
+
+        let signal = self.signal();
+        self.counter = self.counter.wrapping_add(4);
+        if signal && !self.signal() {
+            self.increment_tima();
+        }
+        interrupt
+    }
 }
 
 impl Memory for Timer {
     fn read_byte(&self, address: u16) -> u8 {
         match address {
-            0xFF04 => self.div as u8,
+            0xFF04 => (self.counter >> 8) as u8,
             0xFF05 => self.tima,
             0xFF06 => self.tma,
-            0xFF07 => self.tac,
+            0xFF07 => 0xF8 | self.tac,
             _ => unreachable!(),
         }
     }
 
     fn write_byte(&mut self, address: u16, value: u8) {
+        let signal = self.signal();
         match address {
-            0xFF04 => self.div = 0,
-            0xFF05 => self.tima = value,
-            0xFF06 => self.tma = value,
-            0xFF07 => {
-                self.enabled = value & 0x04 == 0x04;
-                self.tac = value;
-                self.frequency = get_frequency_values(self.tac)
+            0xFF04 => self.counter = 0,
+            0xFF05 => match self.reload {
+                Reload::Pending => {
+                    self.tima = value;
+                    self.reload = Reload::None;
+                }
+                Reload::Reloading => {}
+                Reload::None => self.tima = value,
+            },
+            0xFF06 => {
+                self.tma = value;
+                if self.reload == Reload::Reloading {
+                    self.tima = value;
+                }
             }
+            0xFF07 => self.tac = value & 0x07,
             _ => unreachable!(),
         };
+
+        // Resetting the counter or switching the timer off can pull the signal low
+        if signal && !self.signal() {
+            self.increment_tima();
+        }
     }
 }
 
 impl Gb {
     pub fn timer_tick(&mut self, cycles: u32) {
-        self.timer.cycles += cycles;
-        while self.timer.cycles >= 256 {
-            self.timer.cycles -= 256;
-            self.timer.div = self.timer.div.wrapping_add(1);
+        let div_apu_bit = get_div_apu_bit(self.speed_mode);
+        for _ in 0..cycles / 4 {
+            let div_apu = self.timer.counter & div_apu_bit != 0;
+            if self.timer.step() {
+                self.request_interrupt(Interrupt::Timer);
+            }
+            if div_apu && self.timer.counter & div_apu_bit == 0 {
+                self.sound.clock_frame_sequencer();
+            }
         }
+    }
 
-        if self.timer.enabled {
-            self.timer.ticks += cycles;
-            while self.timer.ticks >= self.timer.frequency {
-                self.timer.ticks -= self.timer.frequency;
-                self.timer.tima = self.timer.tima.wrapping_add(1);
-                if self.timer.tima == 0 {
-                    self.timer.tima = self.timer.tma;
-                    self.request_interrupt(Interrupt::Timer);
-                }
-            }
+    pub fn timer_write(&mut self, address: u16, value: u8) {
+        let div_apu_bit = get_div_apu_bit(self.speed_mode);
+        let div_apu = self.timer.counter & div_apu_bit != 0;
+        self.timer.write_byte(address, value);
+        if div_apu && self.timer.counter & div_apu_bit == 0 {
+            self.sound.clock_frame_sequencer();
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_div_write_increments_tima_on_falling_edge() {
+        // 262144 Hz watches bit 3 of the counter
+        let mut timer = Timer::new(0x0008);
+        timer.write_byte(0xFF07, 0x05);
+        timer.write_byte(0xFF04, 0x00);
+        assert_eq!(timer.read_byte(0xFF05), 1);
+
+        // Bit 3 clear, resetting the counter does nothing
+        let mut timer = Timer::new(0x0004);
+        timer.write_byte(0xFF07, 0x05);
+        timer.write_byte(0xFF04, 0x00);
+        assert_eq!(timer.read_byte(0xFF05), 0);
+    }
+
+    #[test]
+    fn test_tima_reload_is_delayed() {
+        let mut timer = Timer::new(0x0000);
+        timer.write_byte(0xFF06, 0x42);
+        timer.write_byte(0xFF05, 0xFF);
+        timer.write_byte(0xFF07, 0x05);
+        for _ in 0..4 {
+            assert!(!timer.step());
+        }
+        // TIMA reads 0 for one M-cycle before TMA is loaded and the interrupt requested
+        assert_eq!(timer.read_byte(0xFF05), 0x00);
+        assert!(timer.step());
+        assert_eq!(timer.read_byte(0xFF05), 0x42);
+
+        // Writes to TIMA while it is being reloaded are ignored
+        timer.write_byte(0xFF05, 0x10);
+        assert_eq!(timer.read_byte(0xFF05), 0x42);
+    }
+}
@@ -0,0 +1,218 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Per-instruction execution traces. By default each line matches the format
+//! [Gameboy Doctor](https://github.com/robert/gameboy-doctor) expects:
+//!
+//! ```text
+//! A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02
+//! ```
+//!
+//! The optional columns are appended after it, so a trace with extras can still be
+//! compared with `cut`.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::gb::Gb;
+
+#[derive(Clone, Debug, Default)]
+pub struct TraceOptions {
+    /// Append the disassembled instruction.
+    pub disassembly: bool,
+    /// Append the T-cycles run since tracing started.
+    pub cycles: bool,
+    /// Append LY.
+    pub ly: bool,
+    /// Append the ROM bank at 0x4000 and the WRAM bank at 0xD000.
+    pub banks: bool,
+    /// Append the label PC is at, from [`Gb::symbols`].
+    pub symbols: bool,
+    /// Only log instructions with PC inside this range.
+    pub pc_range: Option<RangeInclusive<u16>>,
+    /// Only log instructions running from this ROM bank, 0 being the fixed one at 0x0000.
+    pub rom_bank: Option<usize>,
+}
+
+pub struct Tracer {
+    writer: BufWriter<Box<dyn Write>>,
+    pub options: TraceOptions,
+    cycles: u64,
+    // The first write error, tracing stops writing once one happens
+    error: Option<String>,
+}
+
+impl Tracer {
+    pub fn new(writer: Box<dyn Write>, options: TraceOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            options,
+            cycles: 0,
+            error: None,
+        }
+    }
+
+    pub fn create(path: &Path, options: TraceOptions) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create trace file {}: {e}", path.display()))?;
+        Ok(Self::new(Box::new(file), options))
+    }
+
+    pub(crate) fn add_cycles(&mut self, cycles: u32) {
+        self.cycles += u64::from(cycles);
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer
+            .flush()
+            .map_err(|e| format!("Failed to write trace: {e}"))
+    }
+}
+
+impl Gb {
+    /// Starts logging every executed instruction, replacing any running trace.
+    pub fn start_trace(&mut self, tracer: Tracer) -> Result<(), String> {
+        let previous = self.tracer.replace(tracer);
+        previous.map_or(Ok(()), Tracer::finish)
+    }
+
+    /// Stops tracing and flushes the log, reporting the first error hit while writing it.
+    pub fn stop_trace(&mut self) -> Result<(), String> {
+        self.tracer.take().map_or(Ok(()), Tracer::finish)
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.tracer.is_some()
+    }
+
+    fn code_bank(&self, pc: u16) -> Option<usize> {
+        match pc {
+            0x0000..=0x3FFF => Some(0),
+            0x4000..=0x7FFF => Some(self.mbc.rom_bank()),
+            _ => None,
+        }
+    }
+
+    // Called right before the instruction at PC is executed
+    pub(crate) fn trace_instruction(&mut self) {
+        let Some(tracer) = &self.tracer else {
+            return;
+        };
+        let options = &tracer.options;
+        let pc = self.cpu.pc;
+        if tracer.error.is_some()
+            || options
+                .pc_range
+                .as_ref()
+                .is_some_and(|range| !range.contains(&pc))
+            || options
+                .rom_bank
+                .is_some_and(|bank| self.code_bank(pc) != Some(bank))
+        {
+            return;
+        }
+
+        let mut line = format!(
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            self.cpu.a(),
+            self.cpu.f(),
+            self.cpu.b(),
+            self.cpu.c(),
+            self.cpu.d(),
+            self.cpu.e(),
+            self.cpu.h(),
+            self.cpu.l(),
+            self.cpu.sp,
+            pc,
+            self.read_byte(pc),
+            self.read_byte(pc.wrapping_add(1)),
+            self.read_byte(pc.wrapping_add(2)),
+            self.read_byte(pc.wrapping_add(3)),
+        );
+        if options.ly {
+            line += &format!(" LY:{:02X}", self.ppu.get_ly());
+        }
+        if options.cycles {
+            line += &format!(" CY:{}", tracer.cycles);
+        }
+        if options.banks {
+            line += &format!(" ROM:{:02X} WRAM:{}", self.mbc.rom_bank(), self.ram_bank);
+        }
+        if options.symbols {
+            if let Some(label) = self.describe_address(pc) {
+                line += &format!(" [{label}]");
+            }
+        }
+        if options.disassembly {
+            line += &format!(" | {}", self.disassemble_at(pc));
+        }
+
+        let Some(tracer) = &mut self.tracer else {
+            return;
+        };
+        if let Err(e) = writeln!(tracer.writer, "{line}") {
+            tracer.error = Some(format!("Failed to write trace: {e}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::CartridgeOptions;
+    use crate::prelude::GbInstance;
+    use std::sync::{Arc, Mutex};
+
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_lines() {
+        // NOP, then JP $0150, with an endless JR -2 there
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+        rom[0x150..0x152].copy_from_slice(&[0x18, 0xFE]);
+        let options = CartridgeOptions {
+            ignore_header_checksum: true,
+        };
+        let mut gb = GbInstance::build_from_bytes(rom, None, None, options).unwrap();
+
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let options = TraceOptions {
+            disassembly: true,
+            pc_range: Some(0x0100..=0x014F),
+            ..Default::default()
+        };
+        gb.start_trace(Tracer::new(Box::new(SharedBuffer(buffer.clone())), options))
+            .unwrap();
+        for _ in 0..4 {
+            gb.run();
+        }
+        gb.stop_trace().unwrap();
+
+        let text = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,50,01 | nop"
+        );
+        assert!(lines[1].contains("PC:0101 PCMEM:C3,50,01,00"));
+    }
+}
@@ -0,0 +1,378 @@
+// SPDX-FileCopyrightText: 2026 Pedrenrique G. Guimarães
+//
+// SPDX-License-Identifier: MIT
+
+//! Runs the test ROMs shipped in `roms/` headlessly and prints a pass/fail table for each
+//! suite. ROMs listed as known failures are reported but don't fail the test, so anything
+//! else failing is an accuracy regression. A panic always fails it, listed or not: the list
+//! is only for ROMs the core runs to a wrong result.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use sturdygb_core::cartridge::{CartridgeOptions, GbMode};
+use sturdygb_core::gb::Gb;
+use sturdygb_core::link::SerialCapture;
+use sturdygb_core::prelude::GbInstance;
+
+const ROMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../roms");
+
+// Roughly one emulated second, instructions take a few M-cycles on average
+const INSTRUCTIONS_PER_SECOND: u64 = 400_000;
+
+// Mooneye tests that currently fail
+const MOONEYE_KNOWN_FAILURES: &[&str] = &[
+    "acceptance/add_sp_e_timing.gb",
+    "acceptance/bits/unused_hwio-GS.gb",
+    "acceptance/boot_div-dmgABCmgb.gb",
+    "acceptance/boot_hwio-dmgABCmgb.gb",
+    "acceptance/call_cc_timing.gb",
+    "acceptance/call_cc_timing2.gb",
+    "acceptance/call_timing.gb",
+    "acceptance/call_timing2.gb",
+    "acceptance/ei_sequence.gb",
+    "acceptance/if_ie_registers.gb",
+    "acceptance/jp_cc_timing.gb",
+    "acceptance/jp_timing.gb",
+    "acceptance/ld_hl_sp_e_timing.gb",
+    "acceptance/oam_dma/reg_read.gb",
+    "acceptance/oam_dma/sources-GS.gb",
+    "acceptance/oam_dma_restart.gb",
+    "acceptance/oam_dma_start.gb",
+    "acceptance/oam_dma_timing.gb",
+    "acceptance/ppu/hblank_ly_scx_timing-GS.gb",
+    "acceptance/ppu/intr_2_0_timing.gb",
+    "acceptance/ppu/intr_2_mode0_timing.gb",
+    "acceptance/ppu/intr_2_mode0_timing_sprites.gb",
+    "acceptance/ppu/intr_2_oam_ok_timing.gb",
+    "acceptance/ppu/lcdon_timing-GS.gb",
+    "acceptance/ppu/lcdon_write_timing-GS.gb",
+    "acceptance/ppu/stat_irq_blocking.gb",
+    "acceptance/ppu/stat_lyc_onoff.gb",
+    "acceptance/ppu/vblank_stat_intr-GS.gb",
+    "acceptance/push_timing.gb",
+    "acceptance/ret_cc_timing.gb",
+    "acceptance/ret_timing.gb",
+    "acceptance/reti_timing.gb",
+    "acceptance/rst_timing.gb",
+    "acceptance/serial/boot_sclk_align-dmgABCmgb.gb",
+    "emulator-only/mbc1/multicart_rom_8Mb.gb",
+    "emulator-only/mbc2/bits_ramg.gb",
+    "emulator-only/mbc2/ram.gb",
+    "emulator-only/mbc2/rom_1Mb.gb",
+    "emulator-only/mbc2/rom_512kb.gb",
+    "emulator-only/mbc5/rom_16Mb.gb",
+    "emulator-only/mbc5/rom_1Mb.gb",
+    "emulator-only/mbc5/rom_2Mb.gb",
+    "emulator-only/mbc5/rom_4Mb.gb",
+    "emulator-only/mbc5/rom_512kb.gb",
+    "emulator-only/mbc5/rom_8Mb.gb",
+];
+
+// Blargg tests that currently fail
+const BLARGG_KNOWN_FAILURES: &[&str] = &[
+    "halt_bug.gb",
+    "dmg_sound/dmg_sound.gb",
+    "oam_bug/oam_bug.gb",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Outcome {
+    Pass,
+    Fail,
+    Timeout,
+    Panic,
+}
+
+struct Report {
+    name: String,
+    outcome: Outcome,
+    details: String,
+}
+
+fn load(path: &Path) -> Gb {
+    let rom = std::fs::read(path).unwrap();
+    // No save path, so battery backed tests don't leave .sav files next to the ROMs
+    let mut gb =
+        GbInstance::build_from_bytes(rom, None, None, CartridgeOptions::default()).unwrap();
+    // Results are read through the capture, not printed
+    gb.print_serial = false;
+    gb
+}
+
+fn run_blargg(path: &Path, seconds: u64) -> (Outcome, String) {
+    let mut gb = load(path);
+    let serial = SerialCapture::default();
+    gb.connect_link(Box::new(serial.clone()));
+
+    for _ in 0..seconds * 10 {
+        for _ in 0..INSTRUCTIONS_PER_SECOND / 10 {
+            gb.run();
+        }
+
+        // Newer tests report through cartridge RAM, with a signature at 0xA001
+        let signature = [0xA001, 0xA002, 0xA003].map(|address| gb.read_byte(address));
+        let status = gb.read_byte(0xA000);
+        if signature == [0xDE, 0xB0, 0x61] && status != 0x80 {
+            let text = (0xA004..0xBFFF)
+                .map(|address| gb.read_byte(address))
+                .take_while(|&c| c != 0)
+                .map(|c| c as char)
+                .collect::<String>();
+            let outcome = if status == 0 {
+                Outcome::Pass
+            } else {
+                Outcome::Fail
+            };
+            return (outcome, text.trim().replace('\n', " "));
+        }
+
+        // Older ones only print their result through the serial port
+        let text = String::from_utf8_lossy(&serial.data()).to_string();
+        if text.contains("Passed") {
+            return (Outcome::Pass, String::new());
+        }
+        if text.contains("Failed") {
+            return (Outcome::Fail, text.trim().replace('\n', " "));
+        }
+    }
+    (Outcome::Timeout, String::new())
+}
+
+// Mooneye tests end with LD B,B, with the Fibonacci sequence in the registers on success and
+// 0x42 on failure. The same bytes are sent through the serial port.
+fn run_until_breakpoint(gb: &mut Gb, seconds: u64) -> bool {
+    for _ in 0..seconds * INSTRUCTIONS_PER_SECOND {
+        gb.run();
+        if gb.cpu.current_instruction == 0x40 {
+            return true;
+        }
+    }
+    false
+}
+
+fn mooneye_outcome(values: &[u8]) -> Option<Outcome> {
+    match values {
+        [3, 5, 8, 13, 21, 34] => Some(Outcome::Pass),
+        [0x42, 0x42, 0x42, 0x42, 0x42, 0x42] => Some(Outcome::Fail),
+        _ => None,
+    }
+}
+
+fn run_mooneye(path: &Path) -> (Outcome, String) {
+    let mut gb = load(path);
+    let serial = SerialCapture::default();
+    gb.connect_link(Box::new(serial.clone()));
+
+    for _ in 0..10 * INSTRUCTIONS_PER_SECOND {
+        gb.run();
+        if gb.cpu.current_instruction == 0x40 {
+            let c = &gb.cpu;
+            if let Some(outcome) = mooneye_outcome(&[c.b(), c.c(), c.d(), c.e(), c.h(), c.l()]) {
+                return (outcome, String::new());
+            }
+        }
+        if let Some(outcome) = mooneye_outcome(&serial.data()) {
+            return (outcome, String::new());
+        }
+    }
+    (Outcome::Timeout, String::new())
+}
+
+// Colours used by the acid2 reference screenshots
+fn screen_rgb(gb: &mut Gb) -> Vec<u8> {
+    let cgb = gb.gb_mode == GbMode::CgbMode;
+    let mut rgb = Vec::with_capacity(160 * 144 * 3);
+    for &pixel in gb.get_screen_data().iter().flatten() {
+        if cgb {
+            let scale = |c: u16| {
+                let c = (c & 0x1F) as u8;
+                (c << 3) | (c >> 2)
+            };
+            rgb.extend_from_slice(&[scale(pixel), scale(pixel >> 5), scale(pixel >> 10)]);
+        } else {
+            let shade = [0xFF, 0xAA, 0x55, 0x00][pixel as usize & 3];
+            rgb.extend_from_slice(&[shade; 3]);
+        }
+    }
+    rgb
+}
+
+fn read_png(path: &Path) -> Vec<u8> {
+    let decoder = png::Decoder::new(std::io::BufReader::new(std::fs::File::open(path).unwrap()));
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    let channels = info.color_type.samples();
+    buffer[..info.buffer_size()]
+        .chunks(channels)
+        .flat_map(|pixel| pixel[..3].to_vec())
+        .collect()
+}
+
+fn run_acid2(path: &Path, reference: &Path) -> (Outcome, String) {
+    let mut gb = load(path);
+    if !run_until_breakpoint(&mut gb, 10) {
+        return (Outcome::Timeout, String::new());
+    }
+    // Let the final frame finish drawing
+    for _ in 0..INSTRUCTIONS_PER_SECOND / 10 {
+        gb.run();
+    }
+
+    let screen = screen_rgb(&mut gb);
+    let expected = read_png(reference);
+    let wrong = screen
+        .chunks(3)
+        .zip(expected.chunks(3))
+        .filter(|(a, b)| a != b)
+        .count();
+    if wrong == 0 {
+        (Outcome::Pass, String::new())
+    } else {
+        (Outcome::Fail, format!("{wrong} pixels differ"))
+    }
+}
+
+fn collect_roms(dir: &Path, roms: &mut Vec<PathBuf>) {
+    let mut entries = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_roms(&path, roms);
+        } else if path.extension().is_some_and(|ext| ext == "gb") {
+            roms.push(path);
+        }
+    }
+}
+
+// The suffix after the last dash names the models a mooneye test is meant for, we emulate
+// a DMG of the ABC revisions
+fn runs_on_dmg(path: &Path) -> bool {
+    let stem = path.file_stem().unwrap().to_string_lossy();
+    match stem.rsplit_once('-') {
+        Some((_, models)) => models.contains("dmgABC") || models == "GS" || models == "G",
+        None => true,
+    }
+}
+
+fn run_all<F>(roms: Vec<(String, PathBuf)>, run: F) -> Vec<Report>
+where
+    F: Fn(&Path) -> (Outcome, String) + Sync,
+{
+    let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let next = Mutex::new(roms.into_iter().enumerate());
+    let reports = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let Some((index, (name, path))) = next.lock().unwrap().next() else {
+                    break;
+                };
+                let (outcome, details) =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(&path)))
+                        .unwrap_or((Outcome::Panic, String::new()));
+                reports.lock().unwrap().push((
+                    index,
+                    Report {
+                        name,
+                        outcome,
+                        details,
+                    },
+                ));
+            });
+        }
+    });
+    let mut reports = reports.into_inner().unwrap();
+    reports.sort_by_key(|(index, _)| *index);
+    reports.into_iter().map(|(_, report)| report).collect()
+}
+
+fn check(suite: &str, reports: &[Report], known_failures: &[&str]) {
+    println!("{suite}:");
+    let mut regressions = Vec::new();
+    for report in reports {
+        let known = known_failures.contains(&report.name.as_str());
+        let status = match (report.outcome, known) {
+            (Outcome::Pass, true) => "PASS (listed as a known failure)",
+            (Outcome::Pass, false) => "PASS",
+            (Outcome::Fail, _) => "FAIL",
+            (Outcome::Timeout, _) => "TIMEOUT",
+            (Outcome::Panic, _) => "PANIC",
+        };
+        println!("  {:<50} {status} {}", report.name, report.details);
+        if report.outcome == Outcome::Panic || (report.outcome != Outcome::Pass && !known) {
+            regressions.push(report.name.as_str());
+        }
+    }
+    let passed = reports
+        .iter()
+        .filter(|report| report.outcome == Outcome::Pass)
+        .count();
+    println!("  {passed}/{} passed", reports.len());
+    assert!(
+        regressions.is_empty(),
+        "{suite} regressions: {regressions:?}"
+    );
+}
+
+fn relative_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap()
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[test]
+fn blargg() {
+    let root = Path::new(ROMS).join("gb-test-roms");
+    // The combined ROMs, each one prints which of its sub-tests failed
+    let roms = [
+        ("cpu_instrs/cpu_instrs.gb", 90),
+        ("instr_timing/instr_timing.gb", 5),
+        ("mem_timing/mem_timing.gb", 5),
+        ("mem_timing-2/mem_timing.gb", 5),
+        ("halt_bug.gb", 10),
+        ("dmg_sound/dmg_sound.gb", 40),
+        ("oam_bug/oam_bug.gb", 30),
+    ];
+    let seconds = |path: &Path| {
+        let name = relative_name(&root, path);
+        roms.iter().find(|(rom, _)| *rom == name).unwrap().1
+    };
+    let roms = roms
+        .iter()
+        .map(|(name, _)| (name.to_string(), root.join(name)))
+        .collect();
+    let reports = run_all(roms, |path| run_blargg(path, seconds(path)));
+    check("blargg", &reports, BLARGG_KNOWN_FAILURES);
+}
+
+#[test]
+fn mooneye() {
+    let root = Path::new(ROMS).join("mooneye-test-suite");
+    let mut paths = Vec::new();
+    collect_roms(&root.join("acceptance"), &mut paths);
+    collect_roms(&root.join("emulator-only"), &mut paths);
+    let roms = paths
+        .into_iter()
+        .filter(|path| runs_on_dmg(path))
+        .map(|path| (relative_name(&root, &path), path))
+        .collect();
+    let reports = run_all(roms, run_mooneye);
+    check("mooneye", &reports, MOONEYE_KNOWN_FAILURES);
+}
+
+#[test]
+fn acid2() {
+    let root = Path::new(ROMS);
+    let roms = vec![
+        ("dmg-acid2.gb".to_string(), root.join("dmg-acid2.gb")),
+        ("cgb-acid2.gbc".to_string(), root.join("cgb-acid2.gbc")),
+    ];
+    let reports = run_all(roms, |path| run_acid2(path, &path.with_extension("png")));
+    check("acid2", &reports, &[]);
+}
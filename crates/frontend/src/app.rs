@@ -1,7 +1,30 @@
 use eframe::egui;
 
+use sturdygb_core::cartridge::{CartridgeHeader, CartridgeOptions, GbMode, RtcMode};
+use sturdygb_core::gb::CYCLES_PER_FRAME;
 use sturdygb_core::joypad::JoypadButton;
+use sturdygb_core::movie::buttons_to_input;
+#[cfg(not(target_arch = "wasm32"))]
+use sturdygb_core::movie::{Movie, MovieSession};
+#[cfg(not(target_arch = "wasm32"))]
+use sturdygb_core::power_on::{random_seed, RamPattern};
 use sturdygb_core::prelude::GbInstance;
+use sturdygb_core::rewind::RewindBuffer;
+#[cfg(not(target_arch = "wasm32"))]
+use sturdygb_core::storage::FileStorage;
+use sturdygb_core::storage::SaveStorage;
+#[cfg(not(target_arch = "wasm32"))]
+use sturdygb_core::symbols::Symbols;
+#[cfg(not(target_arch = "wasm32"))]
+use sturdygb_core::trace::{TraceOptions, Tracer};
+
+use crate::cheats::{restore_cheats, saved_cheats, CheatManager, SavedCheat};
+use crate::debugger::DebuggerWindow;
+use crate::memory_viewer::MemoryViewer;
+use crate::movie::ActiveMovie;
+use crate::ppu_viewer::PpuViewer;
+#[cfg(target_arch = "wasm32")]
+use crate::storage::BrowserStorage;
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use std::collections::HashMap;
@@ -19,6 +42,23 @@ use rfd::AsyncFileDialog;
 const GB_W: usize = 160;
 const GB_H: usize = 144;
 
+const REWIND_KEY: egui::Key = egui::Key::Backspace;
+// Snapshot every other frame and keep about 20 seconds of history
+const REWIND_INTERVAL: usize = 2;
+const REWIND_CAPACITY: usize = 600;
+
+const STATE_SLOT_KEYS: [egui::Key; 9] = [
+    egui::Key::F1,
+    egui::Key::F2,
+    egui::Key::F3,
+    egui::Key::F4,
+    egui::Key::F5,
+    egui::Key::F6,
+    egui::Key::F7,
+    egui::Key::F8,
+    egui::Key::F9,
+];
+
 static AUDIO_PRODUCER: Mutex<Option<SyncSender<[f32; 2]>>> = Mutex::new(None);
 static AUDIO_STREAM: Mutex<Option<cpal::Stream>> = Mutex::new(None);
 
@@ -29,6 +69,64 @@ struct State {
     title: String,
     rom_bytes: Vec<u8>,
     save_path: Option<std::path::PathBuf>,
+    // Save states are kept in memory when the ROM has no path to store them next to
+    state_slots: HashMap<usize, Vec<u8>>,
+    rewind: RewindBuffer,
+    movie: Option<ActiveMovie>,
+}
+
+impl State {
+    fn state_slot_path(&self, slot: usize) -> Option<std::path::PathBuf> {
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        self.save_path
+            .as_ref()
+            .map(|path| path.with_extension(format!("ss{slot}")))
+    }
+
+    fn has_state_slot(&self, slot: usize) -> bool {
+        match self.state_slot_path(slot) {
+            Some(path) => path.exists(),
+            None => self.state_slots.contains_key(&slot),
+        }
+    }
+
+    fn save_state_slot(&mut self, slot: usize) -> Result<(), String> {
+        let data = self.gb.save_state();
+        match self.state_slot_path(slot) {
+            Some(path) => std::fs::write(&path, data)
+                .map_err(|e| format!("Could not write {}: {e}", path.display()))?,
+            None => {
+                self.state_slots.insert(slot, data);
+            }
+        }
+        if let Some(movie) = &mut self.movie {
+            movie.state_saved(slot);
+        }
+        Ok(())
+    }
+
+    fn load_state_slot(&mut self, slot: usize) -> Result<(), String> {
+        let movie_frame = match &self.movie {
+            Some(movie) => Some(movie.slot_frame(slot)?),
+            None => None,
+        };
+        let data = match self.state_slot_path(slot) {
+            Some(path) => std::fs::read(&path).map_err(|_| format!("Slot {slot} is empty"))?,
+            None => self
+                .state_slots
+                .get(&slot)
+                .cloned()
+                .ok_or_else(|| format!("Slot {slot} is empty"))?,
+        };
+        self.gb.load_state(&data)?;
+        self.leftover_audio.clear();
+        if let (Some(movie), Some(frame)) = (&mut self.movie, movie_frame) {
+            movie.state_loaded(frame)?;
+        }
+        Ok(())
+    }
 }
 
 pub struct EmuApp {
@@ -52,6 +150,18 @@ pub struct EmuApp {
     paused: bool,
     config: SturdyConfig,
     show_options: bool,
+    debugger: DebuggerWindow,
+    memory_viewer: MemoryViewer,
+    ppu_viewer: PpuViewer,
+    cheat_manager: CheatManager,
+    // Cheats edited since the last save, by ROM title, so switching games doesn't lose
+    // them before eframe stores them
+    edited_cheats: HashMap<String, Vec<SavedCheat>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    trace_details: bool,
+    // Movies played back from now on leave the file untouched
+    #[cfg(not(target_arch = "wasm32"))]
+    movie_read_only: bool,
     #[cfg(not(target_arch = "wasm32"))]
     loading_directory: bool,
     #[cfg(not(target_arch = "wasm32"))]
@@ -60,6 +170,7 @@ pub struct EmuApp {
     frames_rendered: usize,
     last_fps_update: instant::Instant,
     current_fps: usize,
+    status_msg: Option<(String, instant::Instant)>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -72,6 +183,15 @@ pub struct SturdyConfig {
     pub keybinds: HashMap<JoypadButton, egui::Key>,
     #[cfg(not(target_arch = "wasm32"))]
     pub fullscreen: bool,
+    pub rewind_enabled: bool,
+    pub rtc_host_clock: bool,
+    pub ignore_header_checksum: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub dmg_boot_rom: Option<std::path::PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub mgb_boot_rom: Option<std::path::PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub cgb_boot_rom: Option<std::path::PathBuf>,
 }
 
 impl SturdyConfig {
@@ -88,12 +208,47 @@ impl SturdyConfig {
         }
     }
 
+    fn rtc_mode(&self) -> RtcMode {
+        if self.rtc_host_clock {
+            RtcMode::HostClock
+        } else {
+            RtcMode::Emulated
+        }
+    }
+
+    fn cartridge_options(&self) -> CartridgeOptions {
+        CartridgeOptions {
+            ignore_header_checksum: self.ignore_header_checksum,
+        }
+    }
+
     fn keybind(&self, btn: &JoypadButton) -> egui::Key {
         self.keybinds
             .get(btn)
             .copied()
             .unwrap_or_else(|| Self::default_key(btn))
     }
+
+    // DMG games boot with the DMG boot ROM if one is set, falling back to the MGB one
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_boot_rom(&self, is_cgb: bool) -> Result<Option<Vec<u8>>, String> {
+        let path = if is_cgb {
+            self.cgb_boot_rom.as_ref()
+        } else {
+            self.dmg_boot_rom.as_ref().or(self.mgb_boot_rom.as_ref())
+        };
+        match path {
+            Some(path) => std::fs::read(path)
+                .map(Some)
+                .map_err(|e| format!("Could not read boot ROM {}: {e}", path.display())),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_boot_rom(&self, _is_cgb: bool) -> Result<Option<Vec<u8>>, String> {
+        Ok(None)
+    }
 }
 
 impl Default for SturdyConfig {
@@ -116,6 +271,15 @@ impl Default for SturdyConfig {
             keybinds,
             #[cfg(not(target_arch = "wasm32"))]
             fullscreen: false,
+            rewind_enabled: true,
+            rtc_host_clock: true,
+            ignore_header_checksum: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            dmg_boot_rom: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            mgb_boot_rom: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            cgb_boot_rom: None,
         }
     }
 }
@@ -133,6 +297,17 @@ pub enum Palette {
     Pocket,
 }
 
+impl Palette {
+    /// RGB colors for the DMG shades 0-3.
+    pub fn colors(self) -> [(u8, u8, u8); 4] {
+        match self {
+            Palette::Greyscale => [(255, 255, 255), (192, 192, 192), (96, 96, 96), (0, 0, 0)],
+            Palette::ClassicGreen => [(224, 248, 208), (136, 192, 112), (52, 104, 86), (8, 24, 32)],
+            Palette::Pocket => [(232, 232, 232), (160, 160, 160), (88, 88, 88), (16, 16, 16)],
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum SortMethod {
@@ -177,6 +352,15 @@ impl EmuApp {
             paused: false,
             config,
             show_options: false,
+            debugger: DebuggerWindow::default(),
+            memory_viewer: MemoryViewer::default(),
+            ppu_viewer: PpuViewer::default(),
+            cheat_manager: CheatManager::default(),
+            edited_cheats: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            trace_details: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            movie_read_only: true,
             #[cfg(not(target_arch = "wasm32"))]
             loading_directory: false,
             #[cfg(not(target_arch = "wasm32"))]
@@ -185,6 +369,7 @@ impl EmuApp {
             frames_rendered: 0,
             last_fps_update: instant::Instant::now(),
             current_fps: 0,
+            status_msg: None,
         };
 
         if let Some(rom) = initial_rom {
@@ -213,28 +398,64 @@ impl EmuApp {
         &mut self,
         mut bytes: Vec<u8>,
         save_path: Option<std::path::PathBuf>,
-        _storage: Option<&dyn eframe::Storage>,
+        storage: Option<&dyn eframe::Storage>,
     ) {
         if let Some(extracted) = extract_rom_from_bytes(&bytes) {
             bytes = extracted;
         }
 
         let mut title = "Unknown Title".to_string();
-        if let Ok(header) = sturdygb_core::cartridge::CartridgeHeader::new(&bytes) {
+        let mut is_cgb = false;
+        let options = self.config.cartridge_options();
+        if let Ok(header) = CartridgeHeader::parse(&bytes, options) {
             title = header.title;
+            is_cgb = header.cgb_flag & 0x80 != 0;
         }
 
-        match GbInstance::build_from_bytes(bytes.clone(), save_path.clone()) {
+        let boot_rom = match self.config.read_boot_rom(is_cgb) {
+            Ok(boot_rom) => boot_rom,
+            Err(e) => {
+                self.error_msg = Some(format!("Failed to load ROM:\n{e}"));
+                return;
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let save_storage = save_path
+            .clone()
+            .map(|path| Box::new(FileStorage::new(path)) as Box<dyn SaveStorage>);
+        #[cfg(target_arch = "wasm32")]
+        let save_storage = Some(Box::new(BrowserStorage::new(&title)) as Box<dyn SaveStorage>);
+
+        match GbInstance::build_from_bytes(bytes.clone(), save_storage, boot_rom, options) {
             Ok(mut gb) => {
+                self.finish_movie();
+                // RGBDS writes the symbols next to the ROM
+                #[cfg(not(target_arch = "wasm32"))]
+                let symbols_error = save_path
+                    .as_ref()
+                    .map(|path| path.with_extension("sym"))
+                    .filter(|path| path.exists())
+                    .and_then(|path| match Symbols::load(&path) {
+                        Ok(symbols) => {
+                            gb.symbols = symbols;
+                            None
+                        }
+                        Err(e) => Some(e),
+                    });
                 #[cfg(target_arch = "wasm32")]
-                if let Some(storage) = _storage {
-                    if let Some(saved) =
-                        eframe::get_value::<Vec<u8>>(storage, &format!("sturdygb_sram_{title}"))
-                    {
-                        gb.set_battery_ram(&saved);
-                    }
+                let symbols_error = None;
+
+                let cheats = self.edited_cheats.get(&title).cloned().or_else(|| {
+                    storage.and_then(|storage| {
+                        eframe::get_value(storage, &format!("sturdygb_cheats_{title}"))
+                    })
+                });
+                if let Some(cheats) = cheats {
+                    restore_cheats(&mut gb, &cheats);
                 }
 
+                gb.set_rtc_mode(self.config.rtc_mode());
                 setup_audio(&mut gb);
                 self.state = Some(State {
                     gb,
@@ -243,9 +464,12 @@ impl EmuApp {
                     title,
                     rom_bytes: bytes,
                     save_path,
+                    state_slots: HashMap::new(),
+                    rewind: RewindBuffer::new(REWIND_CAPACITY, REWIND_INTERVAL),
+                    movie: None,
                 });
                 self.paused = false;
-                self.error_msg = None;
+                self.error_msg = symbols_error;
                 self.frames_rendered = 0;
                 self.last_fps_update = instant::Instant::now();
             }
@@ -255,6 +479,194 @@ impl EmuApp {
         }
     }
 
+    fn save_state_slot(&mut self, slot: usize) {
+        if let Some(state) = &mut self.state {
+            match state.save_state_slot(slot) {
+                Ok(()) => self.show_status(format!("Saved state to slot {slot}")),
+                Err(e) => self.error_msg = Some(format!("Failed to save state:\n{e}")),
+            }
+        }
+    }
+
+    fn load_state_slot(&mut self, slot: usize) {
+        if let Some(state) = &mut self.state {
+            match state.load_state_slot(slot) {
+                Ok(()) => self.show_status(format!("Loaded state from slot {slot}")),
+                Err(e) => self.error_msg = Some(format!("Failed to load state:\n{e}")),
+            }
+        }
+    }
+
+    fn show_status(&mut self, message: String) {
+        self.status_msg = Some((message, instant::Instant::now()));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn toggle_trace(&mut self) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+        if state.gb.is_tracing() {
+            match state.gb.stop_trace() {
+                Ok(()) => self.show_status("Trace saved".to_string()),
+                Err(e) => self.error_msg = Some(e),
+            }
+            return;
+        }
+
+        let Some(path) = FileDialog::new()
+            .add_filter("Trace", &["log", "txt"])
+            .set_file_name("trace.log")
+            .save_file()
+        else {
+            return;
+        };
+        // Without the details the lines can be checked with Gameboy Doctor
+        let options = TraceOptions {
+            disassembly: self.trace_details,
+            cycles: self.trace_details,
+            ly: self.trace_details,
+            banks: self.trace_details,
+            symbols: self.trace_details,
+            ..Default::default()
+        };
+        match Tracer::create(&path, options).and_then(|tracer| state.gb.start_trace(tracer)) {
+            Ok(()) => self.show_status(format!("Tracing to {}", path.display())),
+            Err(e) => self.error_msg = Some(e),
+        }
+    }
+
+    /// Writes out the movie being recorded or played back, if any, and stops it. Only meant
+    /// for when the game is closed or replaced, see `stop_movie` otherwise.
+    fn finish_movie(&mut self) {
+        if let Some(movie) = self.state.as_mut().and_then(|state| state.movie.take()) {
+            self.write_movie(movie);
+        }
+    }
+
+    fn write_movie(&mut self, movie: ActiveMovie) {
+        match movie.finish() {
+            Ok(()) => self.show_status("Movie stopped".to_string()),
+            Err(e) => self.error_msg = Some(e),
+        }
+    }
+
+    /// Stops the movie, if any, and goes back to playing normally.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn stop_movie(&mut self, storage: Option<&dyn eframe::Storage>) {
+        let Some(movie) = self.state.as_mut().and_then(|state| state.movie.take()) else {
+            return;
+        };
+        self.undo_power_on_for_movie(movie.session.movie().start_state.is_none(), storage);
+        // Written after reloading, which would clear the error otherwise
+        self.write_movie(movie);
+    }
+
+    // A movie from power on ran without the battery save, cheats and configured RTC, so the
+    // game is loaded again like Reset does. One from a state kept them, and only gets its
+    // RTC mode back.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn undo_power_on_for_movie(&mut self, powered_on: bool, storage: Option<&dyn eframe::Storage>) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+        if powered_on {
+            let rom_bytes = state.rom_bytes.clone();
+            let save_path = state.save_path.clone();
+            self.load_rom_bytes(rom_bytes, save_path, storage);
+        } else {
+            state.gb.set_rtc_mode(self.config.rtc_mode());
+        }
+    }
+
+    // Movies that don't start from a state start from power on, with RAM filled from `seed`
+    // and no battery save so they play back the same everywhere. That throws away the game
+    // that was running, `stop_movie` loads it again from its battery save.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn power_on_for_movie(&mut self, seed: Option<u64>) -> Result<(), String> {
+        let options = self.config.cartridge_options();
+        let Some(state) = &mut self.state else {
+            return Ok(());
+        };
+        if let Some(seed) = seed {
+            let mut gb = GbInstance::default()
+                .ram_pattern(RamPattern::Seeded(seed))
+                .load_bytes(state.rom_bytes.clone(), None, None, options)
+                .map_err(|e| e.to_string())?;
+            gb.symbols = std::mem::take(&mut state.gb.symbols);
+            setup_audio(&mut gb);
+            state.gb = gb;
+            state.rewind.clear();
+            state.leftover_audio.clear();
+        } else {
+            // Seeded power on already runs the RTC on emulated time
+            state.gb.set_rtc_mode(RtcMode::Emulated);
+        }
+        Ok(())
+    }
+
+    /// Records from the current state, or from power on, which restarts the game and loses
+    /// whatever wasn't saved to the battery.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn record_movie(&mut self, from_state: bool, storage: Option<&dyn eframe::Storage>) {
+        let Some(path) = FileDialog::new()
+            .add_filter("Movie", &["sgbm"])
+            .set_file_name("movie.sgbm")
+            .save_file()
+        else {
+            return;
+        };
+        self.stop_movie(storage);
+        let seed = random_seed();
+        if let Err(e) = self.power_on_for_movie((!from_state).then_some(seed)) {
+            self.error_msg = Some(format!("Failed to start movie:\n{e}"));
+            return;
+        }
+        if let Some(state) = &mut self.state {
+            let session = MovieSession::record(&state.gb, seed, from_state);
+            state.movie = Some(ActiveMovie::new(session, path));
+            self.paused = false;
+            self.show_status("Recording movie".to_string());
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_movie(&mut self, storage: Option<&dyn eframe::Storage>) {
+        let Some(path) = FileDialog::new().add_filter("Movie", &["sgbm"]).pick_file() else {
+            return;
+        };
+        self.stop_movie(storage);
+        let movie = match std::fs::read(&path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))
+            .and_then(|data| Movie::from_bytes(&data))
+        {
+            Ok(movie) => movie,
+            Err(e) => {
+                self.error_msg = Some(format!("Failed to play movie:\n{e}"));
+                return;
+            }
+        };
+        let powered_on = movie.start_state.is_none();
+        let result = self
+            .power_on_for_movie(powered_on.then_some(movie.seed))
+            .and_then(|()| {
+                let state = self.state.as_mut().ok_or("No game is running")?;
+                let session = MovieSession::play(movie, &mut state.gb, self.movie_read_only)?;
+                state.movie = Some(ActiveMovie::new(session, path));
+                Ok(())
+            });
+        match result {
+            Ok(()) => {
+                self.paused = false;
+                self.show_status("Playing movie".to_string());
+            }
+            Err(e) => {
+                self.undo_power_on_for_movie(powered_on, storage);
+                self.error_msg = Some(format!("Failed to play movie:\n{e}"));
+            }
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn load_directory(&mut self, path: std::path::PathBuf) {
         if !self.config.rom_directories.contains(&path) {
@@ -275,6 +687,7 @@ impl EmuApp {
         self.dir_load_receiver = Some(rx);
         let recursive = self.recursive_search;
         let dirs = self.config.rom_directories.clone();
+        let options = self.config.cartridge_options();
 
         std::thread::spawn(move || {
             for path in dirs {
@@ -305,9 +718,7 @@ impl EmuApp {
                                         let mut header_bytes = vec![0; 0x150];
                                         if f.read_exact(&mut header_bytes).is_ok() {
                                             if let Ok(header) =
-                                                sturdygb_core::cartridge::CartridgeHeader::new(
-                                                    &header_bytes,
-                                                )
+                                                CartridgeHeader::parse(&header_bytes, options)
                                             {
                                                 title = header.title;
                                                 company = header.company;
@@ -329,7 +740,12 @@ impl EmuApp {
                                                             .read_exact(&mut header_bytes)
                                                             .is_ok()
                                                         {
-                                                            if let Ok(header) = sturdygb_core::cartridge::CartridgeHeader::new(&header_bytes) {
+                                                            if let Ok(header) =
+                                                                CartridgeHeader::parse(
+                                                                    &header_bytes,
+                                                                    options,
+                                                                )
+                                                            {
                                                                 title = header.title;
                                                                 company = header.company;
                                                             }
@@ -365,15 +781,13 @@ impl EmuApp {
 impl eframe::App for EmuApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, "sturdygb_config", &self.config);
+        for (title, cheats) in self.edited_cheats.drain() {
+            eframe::set_value(storage, &format!("sturdygb_cheats_{title}"), &cheats);
+        }
 
-        #[cfg(target_arch = "wasm32")]
         if let Some(state) = &mut self.state {
-            if let Some(ram) = state.gb.get_battery_ram() {
-                eframe::set_value(
-                    storage,
-                    &format!("sturdygb_sram_{}", state.title),
-                    &ram.to_vec(),
-                );
+            if let Err(e) = state.gb.flush_save() {
+                self.error_msg = Some(e);
             }
         }
     }
@@ -426,6 +840,19 @@ impl eframe::App for EmuApp {
             }
         }
 
+        // Shift+F1-F9 saves to a state slot, F1-F9 loads it back
+        if self.state.is_some() {
+            for (i, key) in STATE_SLOT_KEYS.iter().enumerate() {
+                let (pressed, shift) =
+                    ctx.input(|input| (input.key_pressed(*key), input.modifiers.shift));
+                if pressed && shift {
+                    self.save_state_slot(i + 1);
+                } else if pressed {
+                    self.load_state_slot(i + 1);
+                }
+            }
+        }
+
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
                 ui.menu_button("File", |ui| {
@@ -476,6 +903,7 @@ impl eframe::App for EmuApp {
                     let has_state = self.state.is_some();
                     if has_state {
                         if ui.button("🟥 Stop").clicked() {
+                            self.finish_movie();
                             self.state = None;
                             self.texture = None;
                             self.paused = false;
@@ -499,6 +927,109 @@ impl eframe::App for EmuApp {
                         self.paused = !self.paused;
                         ui.close();
                     }
+                    if ui
+                        .add_enabled(has_state, egui::Button::new("🐞 Debugger"))
+                        .clicked()
+                    {
+                        self.debugger.open = true;
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(has_state, egui::Button::new("🔍 Memory"))
+                        .clicked()
+                    {
+                        self.memory_viewer.open = true;
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(has_state, egui::Button::new("🖼 PPU Viewer"))
+                        .clicked()
+                    {
+                        self.ppu_viewer.open = true;
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(has_state, egui::Button::new("🎲 Cheats"))
+                        .clicked()
+                    {
+                        self.cheat_manager.open = true;
+                        ui.close();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        let tracing = self
+                            .state
+                            .as_ref()
+                            .is_some_and(|state| state.gb.is_tracing());
+                        if ui
+                            .add_enabled(
+                                has_state,
+                                egui::Button::new(if tracing {
+                                    "⏹ Stop Trace"
+                                } else {
+                                    "⏺ Start Trace..."
+                                }),
+                            )
+                            .clicked()
+                        {
+                            self.toggle_trace();
+                            ui.close();
+                        }
+                        ui.add_enabled(
+                            !tracing,
+                            egui::Checkbox::new(&mut self.trace_details, "Trace Details"),
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        ui.separator();
+                        let has_movie = self
+                            .state
+                            .as_ref()
+                            .is_some_and(|state| state.movie.is_some());
+                        if ui
+                            .add_enabled(has_state, egui::Button::new("⏺ Record Movie..."))
+                            .on_hover_text("Restarts the game, unsaved progress is lost")
+                            .clicked()
+                        {
+                            self.record_movie(false, _frame.storage());
+                            ui.close();
+                        }
+                        if ui
+                            .add_enabled(
+                                has_state,
+                                egui::Button::new("⏺ Record Movie From Here..."),
+                            )
+                            .clicked()
+                        {
+                            self.record_movie(true, _frame.storage());
+                            ui.close();
+                        }
+                        if ui
+                            .add_enabled(has_state, egui::Button::new("▶ Play Movie..."))
+                            .clicked()
+                        {
+                            self.play_movie(_frame.storage());
+                            ui.close();
+                        }
+                        if ui
+                            .add_enabled(has_movie, egui::Button::new("⏹ Stop Movie"))
+                            .clicked()
+                        {
+                            self.stop_movie(_frame.storage());
+                            ui.close();
+                        }
+                        if ui
+                            .checkbox(&mut self.movie_read_only, "Read-Only Playback")
+                            .changed()
+                        {
+                            if let Some(movie) =
+                                self.state.as_mut().and_then(|state| state.movie.as_mut())
+                            {
+                                movie.session.read_only = self.movie_read_only;
+                            }
+                        }
+                    }
                     if ui
                         .add_enabled(has_state, egui::Button::new("🔄 Reset"))
                         .clicked()
@@ -510,6 +1041,35 @@ impl eframe::App for EmuApp {
                         }
                         ui.close();
                     }
+                    ui.separator();
+                    ui.add_enabled_ui(has_state, |ui| {
+                        ui.menu_button("💾 Save State", |ui| {
+                            for slot in 1..=STATE_SLOT_KEYS.len() {
+                                if ui.button(format!("Slot {slot} (Shift+F{slot})")).clicked() {
+                                    self.save_state_slot(slot);
+                                    ui.close();
+                                }
+                            }
+                        });
+                        ui.menu_button("📂 Load State", |ui| {
+                            for slot in 1..=STATE_SLOT_KEYS.len() {
+                                let exists = self
+                                    .state
+                                    .as_ref()
+                                    .is_some_and(|state| state.has_state_slot(slot));
+                                if ui
+                                    .add_enabled(
+                                        exists,
+                                        egui::Button::new(format!("Slot {slot} (F{slot})")),
+                                    )
+                                    .clicked()
+                                {
+                                    self.load_state_slot(slot);
+                                    ui.close();
+                                }
+                            }
+                        });
+                    });
                 });
                 #[cfg(not(target_arch = "wasm32"))]
                 ui.menu_button("View", |ui| {
@@ -629,6 +1189,48 @@ impl eframe::App for EmuApp {
                                     );
                                 });
                             ui.end_row();
+
+                            ui.label("Rewind:");
+                            if ui
+                                .checkbox(
+                                    &mut self.config.rewind_enabled,
+                                    "Hold Backspace to rewind",
+                                )
+                                .changed()
+                            {
+                                if let Some(state) = &mut self.state {
+                                    state.rewind.clear();
+                                }
+                            }
+                            ui.end_row();
+
+                            ui.label("Cartridge header:");
+                            ui.checkbox(
+                                &mut self.config.ignore_header_checksum,
+                                "Load ROMs with a bad checksum",
+                            );
+                            ui.end_row();
+
+                            ui.label("Cartridge clock:");
+                            if ui
+                                .checkbox(
+                                    &mut self.config.rtc_host_clock,
+                                    "Follow the system clock",
+                                )
+                                .changed()
+                            {
+                                if let Some(state) = &mut self.state {
+                                    state.gb.set_rtc_mode(self.config.rtc_mode());
+                                }
+                            }
+                            ui.end_row();
+
+                            #[cfg(not(target_arch = "wasm32"))]
+                            {
+                                boot_rom_picker(ui, "DMG Boot ROM:", &mut self.config.dmg_boot_rom);
+                                boot_rom_picker(ui, "MGB Boot ROM:", &mut self.config.mgb_boot_rom);
+                                boot_rom_picker(ui, "CGB Boot ROM:", &mut self.config.cgb_boot_rom);
+                            }
                         });
 
                     ui.separator();
@@ -713,9 +1315,37 @@ impl eframe::App for EmuApp {
         }
         self.show_options = is_open;
 
+        if self.debugger.open {
+            if let Some(state) = &mut self.state {
+                self.debugger.show(ctx, &mut state.gb, &mut self.paused);
+            }
+        }
+
+        if self.memory_viewer.open {
+            if let Some(state) = &mut self.state {
+                self.memory_viewer.show(ctx, &mut state.gb);
+            }
+        }
+
+        if self.ppu_viewer.open {
+            if let Some(state) = &self.state {
+                self.ppu_viewer.show(ctx, &state.gb, self.config.palette);
+            }
+        }
+
+        if self.cheat_manager.open {
+            if let Some(state) = &mut self.state {
+                if self.cheat_manager.show(ctx, &mut state.gb) {
+                    self.edited_cheats
+                        .insert(state.title.clone(), saved_cheats(&state.gb));
+                }
+            }
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(state) = &mut self.state {
                 if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    self.finish_movie();
                     self.state = None;
                     self.texture = None;
                     self.paused = false;
@@ -735,82 +1365,122 @@ impl eframe::App for EmuApp {
                         JoypadButton::Start,
                         JoypadButton::Select,
                     ];
-                    for btn in buttons {
-                        set_btn(ctx, state, self.config.keybind(&btn), btn);
-                    }
-
-                    // Emulation Loop
-                    let mut channel_full = false;
-                    let mut frames_run = 0;
-
-                    // First try to drain leftover audio
-                    let mut new_leftover = Vec::with_capacity(state.leftover_audio.len());
-                    if let Ok(guard) = AUDIO_PRODUCER.lock() {
-                        if let Some(prod) = guard.as_ref() {
-                            for sample in state.leftover_audio.drain(..) {
-                                if !channel_full {
-                                    if let Err(std::sync::mpsc::TrySendError::Full(val)) =
-                                        prod.try_send(sample)
-                                    {
-                                        channel_full = true;
-                                        new_leftover.push(val);
-                                    }
-                                } else {
-                                    new_leftover.push(sample);
-                                }
-                            }
-                        }
+                    // Movies only change input between frames
+                    let held = buttons_to_input(
+                        buttons
+                            .into_iter()
+                            .filter(|btn| ctx.input(|i| i.key_down(self.config.keybind(btn)))),
+                    );
+                    if state.movie.is_none() {
+                        queue_input(
+                            ctx,
+                            state,
+                            &buttons.map(|btn| (self.config.keybind(&btn), btn)),
+                        );
                     }
-                    state.leftover_audio = new_leftover;
 
-                    while !channel_full && frames_run < 5 {
-                        state.gb.run_one_frame();
-                        frames_run += 1;
+                    // Movies go back through save states instead
+                    let rewinding = self.config.rewind_enabled
+                        && state.movie.is_none()
+                        && ctx.input(|i| i.key_down(REWIND_KEY));
+                    if rewinding {
+                        // Rewind is muted, so drop the audio queued by the frames being undone
+                        state.rewind.rewind(&mut state.gb);
+                        state.gb.get_audio_buffer();
+                        state.leftover_audio.clear();
+                    } else {
+                        // Emulation Loop
+                        let mut channel_full = false;
+                        let mut frames_run = 0;
 
-                        let audio_data = state.gb.get_audio_buffer();
+                        // First try to drain leftover audio
+                        let mut new_leftover = Vec::with_capacity(state.leftover_audio.len());
                         if let Ok(guard) = AUDIO_PRODUCER.lock() {
                             if let Some(prod) = guard.as_ref() {
-                                for frame in audio_data.chunks_exact(2) {
-                                    let sample = [frame[0], frame[1]];
+                                for sample in state.leftover_audio.drain(..) {
                                     if !channel_full {
                                         if let Err(std::sync::mpsc::TrySendError::Full(val)) =
                                             prod.try_send(sample)
                                         {
                                             channel_full = true;
-                                            if state.leftover_audio.len() < 8192 {
-                                                state.leftover_audio.push(val);
-                                            }
+                                            new_leftover.push(val);
                                         }
                                     } else {
-                                        if state.leftover_audio.len() < 8192 {
-                                            state.leftover_audio.push(sample);
+                                        new_leftover.push(sample);
+                                    }
+                                }
+                            }
+                        }
+                        state.leftover_audio = new_leftover;
+
+                        while !channel_full && frames_run < 5 {
+                            // The frame has to be drawn before the next one can run
+                            if state.gb.ppu.frame_ready {
+                                break;
+                            }
+                            if let Some(movie) = &mut state.movie {
+                                movie.session.start_frame(&mut state.gb, held);
+                            }
+                            state.gb.run_one_frame();
+                            if let Some(movie) = &mut state.movie {
+                                movie.session.end_frame(&mut state.gb);
+                            }
+                            frames_run += 1;
+                            if self.config.rewind_enabled {
+                                state.rewind.push(&state.gb);
+                            }
+
+                            let audio_data = state.gb.get_audio_buffer();
+                            if let Ok(guard) = AUDIO_PRODUCER.lock() {
+                                if let Some(prod) = guard.as_ref() {
+                                    for frame in audio_data.chunks_exact(2) {
+                                        let sample = [frame[0], frame[1]];
+                                        if !channel_full {
+                                            if let Err(std::sync::mpsc::TrySendError::Full(val)) =
+                                                prod.try_send(sample)
+                                            {
+                                                channel_full = true;
+                                                if state.leftover_audio.len() < 8192 {
+                                                    state.leftover_audio.push(val);
+                                                }
+                                            }
+                                        } else {
+                                            if state.leftover_audio.len() < 8192 {
+                                                state.leftover_audio.push(sample);
+                                            }
                                         }
                                     }
                                 }
                             }
+
+                            // The frame was cut short by a breakpoint
+                            if state.gb.debugger.hit().is_some() {
+                                self.paused = true;
+                                self.debugger.open = true;
+                                break;
+                            }
+                        }
+
+                        if let Some(e) = state.gb.take_save_error() {
+                            self.error_msg = Some(e);
                         }
                     }
                 }
 
                 // Render video
+                let is_cgb = state.gb.gb_mode == GbMode::CgbMode;
                 let frame_data = state.gb.get_screen_data();
 
-                let palette_colors = match self.config.palette {
-                    Palette::Greyscale => {
-                        [(255, 255, 255), (192, 192, 192), (96, 96, 96), (0, 0, 0)]
-                    }
-                    Palette::ClassicGreen => {
-                        [(224, 248, 208), (136, 192, 112), (52, 104, 86), (8, 24, 32)]
-                    }
-                    Palette::Pocket => {
-                        [(232, 232, 232), (160, 160, 160), (88, 88, 88), (16, 16, 16)]
-                    }
-                };
+                let palette_colors = self.config.palette.colors();
 
                 for y in 0..GB_H {
                     for x in 0..GB_W {
-                        let shade = frame_data[y][x] as usize;
-                        let (r, g, b) = palette_colors[shade];
+                        let pixel = frame_data[y][x];
+                        let (r, g, b) = if is_cgb {
+                            rgb555_to_rgb888(pixel)
+                        } else {
+                            palette_colors[pixel as usize]
+                        };
                         let i = (y * GB_W + x) * 4;
                         state.rgba[i + 0] = r;
                         state.rgba[i + 1] = g;
@@ -863,6 +1533,24 @@ impl eframe::App for EmuApp {
                     egui::Image::new(&*texture).fit_to_exact_size(egui::vec2(width, height)),
                 );
 
+                if let Some(movie) = &state.movie {
+                    movie.show_overlay(ui.painter(), rect);
+                }
+
+                if let Some((message, shown_at)) = &self.status_msg {
+                    if shown_at.elapsed().as_secs_f32() < 2.0 {
+                        ui.painter().text(
+                            rect.left_top() + egui::vec2(8.0, 8.0),
+                            egui::Align2::LEFT_TOP,
+                            message,
+                            egui::FontId::proportional(16.0),
+                            egui::Color32::WHITE,
+                        );
+                    } else {
+                        self.status_msg = None;
+                    }
+                }
+
                 // Request repaint if we are running the emulator
                 ctx.request_repaint();
             } else {
@@ -1084,14 +1772,77 @@ impl eframe::App for EmuApp {
     }
 }
 
-fn set_btn(ctx: &egui::Context, state: &mut State, key: egui::Key, btn: JoypadButton) {
-    if ctx.input(|i| i.key_down(key)) {
-        state.gb.press_button(btn);
-    } else {
-        state.gb.release_button(btn);
+// Key events that arrived since the last update are spread over the next frame in the order
+// they happened, so a quick tap still reaches the game. Buttons without events follow the
+// key state, which catches up on anything pressed while paused.
+fn queue_input(ctx: &egui::Context, state: &mut State, keys: &[(egui::Key, JoypadButton)]) {
+    let button = |key: egui::Key| keys.iter().find(|(k, _)| *k == key).map(|&(_, btn)| btn);
+    let events: Vec<(JoypadButton, bool)> = ctx.input(|i| {
+        i.events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed,
+                    repeat: false,
+                    ..
+                } => button(*key).map(|btn| (btn, *pressed)),
+                _ => None,
+            })
+            .collect()
+    });
+
+    let start = state.gb.cycles();
+    let step = u64::from(CYCLES_PER_FRAME) / events.len().max(1) as u64;
+    for (index, &(btn, pressed)) in events.iter().enumerate() {
+        state
+            .gb
+            .queue_input(start + index as u64 * step, btn, pressed);
+    }
+    for &(key, btn) in keys {
+        if events.iter().any(|&(event_btn, _)| event_btn == btn) {
+            continue;
+        }
+        if ctx.input(|i| i.key_down(key)) {
+            state.gb.press_button(btn);
+        } else {
+            state.gb.release_button(btn);
+        }
     }
 }
 
+// Boot ROM changes take effect the next time a game is loaded or reset
+#[cfg(not(target_arch = "wasm32"))]
+fn boot_rom_picker(ui: &mut egui::Ui, label: &str, path: &mut Option<std::path::PathBuf>) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        let name = path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map_or("None".to_string(), |n| n.to_string_lossy().to_string());
+        if ui.button(name).clicked() {
+            if let Some(picked) = FileDialog::new()
+                .add_filter("Boot ROMs", &["bin", "rom", "gb", "gbc"])
+                .pick_file()
+            {
+                *path = Some(picked);
+            }
+        }
+        if path.is_some() && ui.button("❌").clicked() {
+            *path = None;
+        }
+    });
+    ui.end_row();
+}
+
+fn rgb555_to_rgb888(color: u16) -> (u8, u8, u8) {
+    let scale = |c: u16| -> u8 {
+        let c = (c & 0x1F) as u8;
+        (c << 3) | (c >> 2)
+    };
+    (scale(color), scale(color >> 5), scale(color >> 10))
+}
+
 fn setup_audio(gb: &mut sturdygb_core::gb::Gb) {
     let host = cpal::default_host();
     let device = host.default_output_device();
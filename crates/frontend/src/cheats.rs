@@ -0,0 +1,117 @@
+use eframe::egui;
+
+use sturdygb_core::cheats::Cheat;
+use sturdygb_core::gb::Gb;
+
+/// A cheat as kept in eframe storage, the codes are parsed again when it's loaded.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct SavedCheat {
+    name: String,
+    code: String,
+    enabled: bool,
+}
+
+pub fn saved_cheats(gb: &Gb) -> Vec<SavedCheat> {
+    gb.cheats
+        .list()
+        .iter()
+        .map(|cheat| SavedCheat {
+            name: cheat.name.clone(),
+            code: cheat.code.clone(),
+            enabled: cheat.enabled,
+        })
+        .collect()
+}
+
+/// Adds the saved cheats to `gb`, skipping any that no longer parse.
+pub fn restore_cheats(gb: &mut Gb, saved: &[SavedCheat]) {
+    for saved in saved {
+        if let Ok(mut cheat) = Cheat::new(&saved.name, &saved.code) {
+            cheat.enabled = saved.enabled;
+            gb.cheats.add(cheat);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CheatManager {
+    pub open: bool,
+    name: String,
+    code: String,
+    error: Option<String>,
+}
+
+impl CheatManager {
+    /// Returns whether the cheats changed and need to be saved.
+    pub fn show(&mut self, ctx: &egui::Context, gb: &mut Gb) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+        egui::Window::new("Cheats")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label("Game Genie (ABC-DEF-GHI) or GameShark (01VVAAAA) codes, several can be separated by +");
+                egui::Grid::new("cheat_input")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Name");
+                        ui.text_edit_singleline(&mut self.name);
+                        ui.end_row();
+                        ui.label("Code");
+                        ui.text_edit_singleline(&mut self.code);
+                        ui.end_row();
+                    });
+                if ui.button("Add").clicked() {
+                    let name = if self.name.trim().is_empty() {
+                        self.code.trim()
+                    } else {
+                        self.name.trim()
+                    };
+                    match Cheat::new(name, self.code.trim()) {
+                        Ok(cheat) => {
+                            gb.cheats.add(cheat);
+                            self.name.clear();
+                            self.code.clear();
+                            self.error = None;
+                            changed = true;
+                        }
+                        Err(e) => self.error = Some(e),
+                    }
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                ui.separator();
+
+                if gb.cheats.is_empty() {
+                    ui.label("No cheats");
+                }
+                let mut toggled = None;
+                let mut removed = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (index, cheat) in gb.cheats.list().iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let mut enabled = cheat.enabled;
+                            if ui.checkbox(&mut enabled, &cheat.name).changed() {
+                                toggled = Some((index, enabled));
+                            }
+                            ui.monospace(&cheat.code);
+                            if ui.small_button("🗑").clicked() {
+                                removed = Some(index);
+                            }
+                        });
+                    }
+                });
+                if let Some((index, enabled)) = toggled {
+                    gb.cheats.set_enabled(index, enabled);
+                    changed = true;
+                }
+                if let Some(index) = removed {
+                    gb.cheats.remove(index);
+                    changed = true;
+                }
+            });
+        self.open = open;
+        changed
+    }
+}
@@ -0,0 +1,360 @@
+use eframe::egui;
+
+use sturdygb_core::debugger::{Breakpoint, BreakpointKind, Condition};
+use sturdygb_core::gb::Gb;
+#[cfg(not(target_arch = "wasm32"))]
+use sturdygb_core::symbols::Symbols;
+
+// Instructions shown before and after the one at the top of the view
+const LINES_BEFORE: usize = 8;
+const LINES_AFTER: usize = 24;
+
+pub struct DebuggerWindow {
+    pub open: bool,
+    // Shows the code around PC, otherwise around `view_address`
+    follow_pc: bool,
+    view_address: u16,
+    cursor: Option<u16>,
+    new_kind: BreakpointKind,
+    new_address: String,
+    new_condition: String,
+    error: Option<String>,
+}
+
+impl Default for DebuggerWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            follow_pc: true,
+            view_address: 0x0100,
+            cursor: None,
+            new_kind: BreakpointKind::Execute,
+            new_address: String::new(),
+            new_condition: String::new(),
+            error: None,
+        }
+    }
+}
+
+// Hex addresses, or the name of a label from the symbol file
+fn parse_address(gb: &Gb, text: &str) -> Option<u16> {
+    let text = text.trim();
+    let hex = text.trim_start_matches('$').trim_start_matches("0x");
+    u16::from_str_radix(hex, 16)
+        .ok()
+        .or_else(|| gb.symbols.lookup(text).map(|(_, address)| address))
+}
+
+fn format_address(gb: &Gb, address: u16) -> String {
+    match gb.symbol_at(address) {
+        Some(label) => format!("{address:04X} ({label})"),
+        None => format!("{address:04X}"),
+    }
+}
+
+fn kind_name(kind: BreakpointKind) -> &'static str {
+    match kind {
+        BreakpointKind::Execute => "Execute",
+        BreakpointKind::Read => "Read",
+        BreakpointKind::Write => "Write",
+    }
+}
+
+fn register_editor(ui: &mut egui::Ui, label: &str, value: u8, set: impl FnOnce(u8)) {
+    let mut edited = value;
+    ui.label(label);
+    ui.add(egui::DragValue::new(&mut edited).hexadecimal(2, false, true));
+    if edited != value {
+        set(edited);
+    }
+}
+
+impl DebuggerWindow {
+    /// Draws the window, pausing and resuming the emulation through `paused`.
+    pub fn show(&mut self, ctx: &egui::Context, gb: &mut Gb, paused: &mut bool) {
+        let mut open = self.open;
+        egui::Window::new("Debugger")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                self.controls(ui, gb, paused);
+                ui.separator();
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| self.disassembly(ui, gb));
+                    ui.separator();
+                    ui.vertical(|ui| {
+                        Self::registers(ui, gb);
+                        ui.separator();
+                        Self::call_stack(ui, gb);
+                    });
+                });
+                ui.separator();
+                self.breakpoints(ui, gb);
+            });
+        self.open = open;
+    }
+
+    fn controls(&mut self, ui: &mut egui::Ui, gb: &mut Gb, paused: &mut bool) {
+        ui.horizontal(|ui| {
+            if ui
+                .button(if *paused { "▶ Continue" } else { "⏸ Pause" })
+                .clicked()
+            {
+                *paused = !*paused;
+            }
+            ui.add_enabled_ui(*paused, |ui| {
+                if ui.button("Step Instruction").clicked() {
+                    gb.step_instruction();
+                }
+                if ui.button("Step Frame").clicked() {
+                    gb.run_one_frame();
+                }
+                if ui
+                    .add_enabled(self.cursor.is_some(), egui::Button::new("Run to Cursor"))
+                    .clicked()
+                {
+                    if let Some(cursor) = self.cursor {
+                        gb.debugger.run_to(cursor);
+                        *paused = false;
+                    }
+                }
+            });
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Load Symbols...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Symbol files", &["sym"])
+                    .pick_file()
+                {
+                    match Symbols::load(&path) {
+                        Ok(symbols) => gb.symbols = symbols,
+                        Err(e) => self.error = Some(e),
+                    }
+                }
+            }
+        });
+
+        if let Some(hit) = gb.debugger.hit() {
+            let text = match hit.value {
+                Some(value) => format!(
+                    "{} breakpoint hit at {:04X} (value {value:02X})",
+                    kind_name(hit.kind),
+                    hit.address
+                ),
+                None => format!(
+                    "{} breakpoint hit at {:04X}",
+                    kind_name(hit.kind),
+                    hit.address
+                ),
+            };
+            ui.colored_label(egui::Color32::YELLOW, text);
+        }
+    }
+
+    fn disassembly(&mut self, ui: &mut egui::Ui, gb: &mut Gb) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.follow_pc, "Follow PC");
+            ui.add_enabled_ui(!self.follow_pc, |ui| {
+                ui.label("Address:");
+                ui.add(egui::DragValue::new(&mut self.view_address).hexadecimal(4, false, true));
+            });
+        });
+
+        let pc = gb.cpu.pc;
+        let address = if self.follow_pc {
+            pc
+        } else {
+            self.view_address
+        };
+        let lines = gb.disassemble_around(address, LINES_BEFORE, LINES_AFTER);
+        egui::ScrollArea::vertical()
+            .id_salt("disassembly")
+            .max_height(360.0)
+            .show(ui, |ui| {
+                for (address, text) in lines {
+                    if let Some(label) = gb.symbol_at(address) {
+                        ui.label(
+                            egui::RichText::new(format!("{label}:"))
+                                .monospace()
+                                .color(egui::Color32::LIGHT_BLUE),
+                        );
+                    }
+                    let marker = if gb.debugger.breakpoints.iter().any(|breakpoint| {
+                        breakpoint.kind == BreakpointKind::Execute && breakpoint.address == address
+                    }) {
+                        "●"
+                    } else {
+                        " "
+                    };
+                    let arrow = if address == pc { "▶" } else { " " };
+                    let line =
+                        egui::RichText::new(format!("{marker}{arrow} {address:04X}  {text}"))
+                            .monospace();
+                    let line = if address == pc {
+                        line.color(egui::Color32::YELLOW)
+                    } else {
+                        line
+                    };
+                    let response = ui.selectable_label(self.cursor == Some(address), line);
+                    if response.clicked() {
+                        self.cursor = Some(address);
+                    }
+                    if response.double_clicked() {
+                        Self::toggle_breakpoint(gb, address);
+                    }
+                }
+            });
+        ui.small("Click to place the cursor, double-click to toggle a breakpoint");
+    }
+
+    fn toggle_breakpoint(gb: &mut Gb, address: u16) {
+        let existing = gb.debugger.breakpoints.iter().position(|breakpoint| {
+            breakpoint.kind == BreakpointKind::Execute && breakpoint.address == address
+        });
+        match existing {
+            Some(index) => gb.debugger.remove_breakpoint(index),
+            None => gb
+                .debugger
+                .add_breakpoint(Breakpoint::new(BreakpointKind::Execute, address)),
+        }
+    }
+
+    fn registers(ui: &mut egui::Ui, gb: &mut Gb) {
+        ui.heading("Registers");
+        egui::Grid::new("registers").num_columns(4).show(ui, |ui| {
+            let cpu = &mut gb.cpu;
+            register_editor(ui, "A", cpu.a(), |value| cpu.set_a(value));
+            register_editor(ui, "F", cpu.f(), |value| cpu.set_f(value));
+            ui.end_row();
+            register_editor(ui, "B", cpu.b(), |value| cpu.set_b(value));
+            register_editor(ui, "C", cpu.c(), |value| cpu.set_c(value));
+            ui.end_row();
+            register_editor(ui, "D", cpu.d(), |value| cpu.set_d(value));
+            register_editor(ui, "E", cpu.e(), |value| cpu.set_e(value));
+            ui.end_row();
+            register_editor(ui, "H", cpu.h(), |value| cpu.set_h(value));
+            register_editor(ui, "L", cpu.l(), |value| cpu.set_l(value));
+            ui.end_row();
+            ui.label("SP");
+            ui.add(egui::DragValue::new(&mut cpu.sp).hexadecimal(4, false, true));
+            ui.label("PC");
+            ui.add(egui::DragValue::new(&mut cpu.pc).hexadecimal(4, false, true));
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            let cpu = &mut gb.cpu;
+            let mut flags = [cpu.zero, cpu.negative, cpu.half_carry, cpu.carry];
+            for (flag, name) in flags.iter_mut().zip(["Z", "N", "H", "C"]) {
+                ui.checkbox(flag, name);
+            }
+            cpu.set_zero(flags[0]);
+            cpu.set_negative(flags[1]);
+            cpu.set_half_carry(flags[2]);
+            cpu.set_carry(flags[3]);
+            ui.checkbox(&mut cpu.interrupt_master, "IME");
+        });
+    }
+
+    fn call_stack(ui: &mut egui::Ui, gb: &Gb) {
+        ui.heading("Call Stack");
+        egui::ScrollArea::vertical()
+            .id_salt("call_stack")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for frame in gb.debugger.call_stack().iter().rev() {
+                    let kind = if frame.interrupt { " (interrupt)" } else { "" };
+                    ui.monospace(format!(
+                        "{} → {}{kind}",
+                        format_address(gb, frame.call_site),
+                        format_address(gb, frame.target)
+                    ));
+                }
+            });
+    }
+
+    fn breakpoints(&mut self, ui: &mut egui::Ui, gb: &mut Gb) {
+        ui.heading("Breakpoints");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("breakpoint_kind")
+                .selected_text(kind_name(self.new_kind))
+                .show_ui(ui, |ui| {
+                    for kind in [
+                        BreakpointKind::Execute,
+                        BreakpointKind::Read,
+                        BreakpointKind::Write,
+                    ] {
+                        ui.selectable_value(&mut self.new_kind, kind, kind_name(kind));
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_address)
+                    .hint_text("Address or label")
+                    .desired_width(110.0),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_condition)
+                    .hint_text("Condition, e.g. a == $10")
+                    .desired_width(180.0),
+            );
+            if ui.button("Add").clicked() {
+                match self.new_breakpoint(gb) {
+                    Ok(breakpoint) => {
+                        gb.debugger.add_breakpoint(breakpoint);
+                        self.new_address.clear();
+                        self.new_condition.clear();
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        // Formatted up front, the labels can't be looked up while the list is borrowed
+        let addresses: Vec<String> = gb
+            .debugger
+            .breakpoints
+            .iter()
+            .map(|breakpoint| format_address(gb, breakpoint.address))
+            .collect();
+        let mut removed = None;
+        for ((index, breakpoint), address) in gb
+            .debugger
+            .breakpoints
+            .iter_mut()
+            .enumerate()
+            .zip(addresses)
+        {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut breakpoint.enabled, "");
+                let condition = breakpoint
+                    .condition
+                    .map(|condition| format!(" if {condition}"))
+                    .unwrap_or_default();
+                ui.monospace(format!(
+                    "{} {address}{condition}",
+                    kind_name(breakpoint.kind),
+                ));
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            gb.debugger.remove_breakpoint(index);
+        }
+    }
+
+    fn new_breakpoint(&self, gb: &Gb) -> Result<Breakpoint, String> {
+        let address = parse_address(gb, &self.new_address)
+            .ok_or_else(|| format!("Invalid address or unknown label {}", self.new_address))?;
+        let breakpoint = Breakpoint::new(self.new_kind, address);
+        if self.new_condition.trim().is_empty() {
+            Ok(breakpoint)
+        } else {
+            Ok(breakpoint.with_condition(Condition::parse(&self.new_condition)?))
+        }
+    }
+}
@@ -1,6 +1,13 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+mod cheats;
+mod debugger;
+mod memory_viewer;
+mod movie;
+mod ppu_viewer;
+#[cfg(target_arch = "wasm32")]
+mod storage;
 pub use app::EmuApp;
 
 #[cfg(target_arch = "wasm32")]
@@ -2,6 +2,13 @@
 #![allow(rustdoc::missing_crate_level_docs)] // it's an app
 
 mod app;
+mod cheats;
+mod debugger;
+mod memory_viewer;
+mod movie;
+mod ppu_viewer;
+#[cfg(target_arch = "wasm32")]
+mod storage;
 
 use crate::app::APP_NAME;
 
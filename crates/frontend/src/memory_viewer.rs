@@ -0,0 +1,274 @@
+use eframe::egui;
+
+use sturdygb_core::gb::Gb;
+use sturdygb_core::inspect::MemoryRegion;
+
+const BYTES_PER_ROW: usize = 16;
+// Search results beyond this are dropped, searching for a single common byte would
+// otherwise list most of the ROM
+const MAX_MATCHES: usize = 256;
+
+pub struct MemoryViewer {
+    pub open: bool,
+    region: MemoryRegion,
+    bank: usize,
+    selected: Option<usize>,
+    edit_text: String,
+    // Contents of the bank before and after the last time it changed, used to highlight
+    // what the emulated frame wrote
+    previous: Vec<u8>,
+    current: Vec<u8>,
+    search_text: String,
+    matches: Vec<(usize, usize)>,
+    scroll_to: Option<usize>,
+    error: Option<String>,
+}
+
+impl Default for MemoryViewer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            region: MemoryRegion::Wram,
+            bank: 0,
+            selected: None,
+            edit_text: String::new(),
+            previous: Vec::new(),
+            current: Vec::new(),
+            search_text: String::new(),
+            matches: Vec::new(),
+            scroll_to: None,
+            error: None,
+        }
+    }
+}
+
+fn parse_pattern(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return Err(format!("Invalid byte pattern {text}"));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("Invalid byte pattern {text}"))
+        })
+        .collect()
+}
+
+fn read_bank(gb: &Gb, region: MemoryRegion, bank: usize) -> Vec<u8> {
+    (0..region.bank_size())
+        .map(|offset| gb.peek(region, bank, offset as u16))
+        .collect()
+}
+
+impl MemoryViewer {
+    pub fn show(&mut self, ctx: &egui::Context, gb: &mut Gb) {
+        let mut open = self.open;
+        egui::Window::new("Memory")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                self.bank_selector(ui, gb);
+                self.update_snapshot(gb);
+                ui.separator();
+                self.search(ui, gb);
+                ui.separator();
+                self.hex_view(ui);
+                ui.separator();
+                self.editor(ui, gb);
+            });
+        self.open = open;
+    }
+
+    fn bank_selector(&mut self, ui: &mut egui::Ui, gb: &Gb) {
+        ui.horizontal(|ui| {
+            let mut region = self.region;
+            egui::ComboBox::from_id_salt("memory_region")
+                .selected_text(region.name())
+                .show_ui(ui, |ui| {
+                    for option in MemoryRegion::ALL {
+                        ui.selectable_value(&mut region, option, option.name());
+                    }
+                });
+            let banks = gb.bank_count(region);
+            let mut bank = self.bank.min(banks.saturating_sub(1));
+            ui.label("Bank:");
+            ui.add_enabled(
+                banks > 1,
+                egui::DragValue::new(&mut bank).range(0..=banks.saturating_sub(1)),
+            );
+            ui.label(format!("of {banks}"));
+            self.select_bank(region, bank);
+        });
+    }
+
+    fn select_bank(&mut self, region: MemoryRegion, bank: usize) {
+        if region != self.region || bank != self.bank {
+            self.region = region;
+            self.bank = bank;
+            self.selected = None;
+            self.previous.clear();
+            self.current.clear();
+        }
+    }
+
+    fn update_snapshot(&mut self, gb: &Gb) {
+        let contents = read_bank(gb, self.region, self.bank);
+        if self.current.is_empty() {
+            self.previous = contents.clone();
+            self.current = contents;
+        } else if contents != self.current {
+            self.previous = std::mem::replace(&mut self.current, contents);
+        }
+    }
+
+    fn search(&mut self, ui: &mut egui::Ui, gb: &Gb) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.search_text)
+                    .hint_text("Bytes, e.g. DE AD BE EF")
+                    .desired_width(200.0),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Search").clicked() || submitted {
+                match parse_pattern(&self.search_text) {
+                    Ok(pattern) => {
+                        self.matches = Self::find(gb, self.region, &pattern);
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            if !self.matches.is_empty() {
+                ui.label(format!("{} matches", self.matches.len()));
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        let mut jump = None;
+        egui::ScrollArea::vertical()
+            .id_salt("memory_matches")
+            .max_height(80.0)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for &(bank, offset) in &self.matches {
+                        let address = self.region.base_address(bank) as usize + offset;
+                        if ui
+                            .small_button(format!("{bank:02X}:{address:04X}"))
+                            .clicked()
+                        {
+                            jump = Some((bank, offset));
+                        }
+                    }
+                });
+            });
+        if let Some((bank, offset)) = jump {
+            self.select_bank(self.region, bank);
+            self.update_snapshot(gb);
+            self.selected = Some(offset);
+            self.edit_text = format!("{:02X}", self.current[offset]);
+            self.scroll_to = Some(offset / BYTES_PER_ROW);
+        }
+    }
+
+    /// Every bank and offset in `region` where `pattern` starts. Matches don't span banks.
+    fn find(gb: &Gb, region: MemoryRegion, pattern: &[u8]) -> Vec<(usize, usize)> {
+        let mut matches = Vec::new();
+        for bank in 0..gb.bank_count(region) {
+            let contents = read_bank(gb, region, bank);
+            for (offset, window) in contents.windows(pattern.len()).enumerate() {
+                if window == pattern {
+                    matches.push((bank, offset));
+                    if matches.len() == MAX_MATCHES {
+                        return matches;
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    fn hex_view(&mut self, ui: &mut egui::Ui) {
+        let base = self.region.base_address(self.bank) as usize;
+        let rows = self.current.len().div_ceil(BYTES_PER_ROW);
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let mut scroll = egui::ScrollArea::vertical()
+            .id_salt("memory_hex")
+            .max_height(320.0)
+            .auto_shrink([false, true]);
+        if let Some(row) = self.scroll_to.take() {
+            let spacing = ui.spacing().item_spacing.y;
+            scroll = scroll.vertical_scroll_offset(row as f32 * (row_height + spacing));
+        }
+        scroll.show_rows(ui, row_height, rows, |ui, range| {
+            for row in range {
+                let start = row * BYTES_PER_ROW;
+                let end = (start + BYTES_PER_ROW).min(self.current.len());
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    ui.monospace(format!("{:04X}", base + start));
+                    ui.add_space(4.0);
+                    for offset in start..end {
+                        let value = self.current[offset];
+                        let mut text = egui::RichText::new(format!("{value:02X}")).monospace();
+                        if self.previous.get(offset).is_some_and(|old| *old != value) {
+                            text = text.color(egui::Color32::RED);
+                        }
+                        if ui
+                            .selectable_label(self.selected == Some(offset), text)
+                            .clicked()
+                        {
+                            self.selected = Some(offset);
+                            self.edit_text = format!("{value:02X}");
+                        }
+                    }
+                    ui.add_space(4.0);
+                    let ascii: String = self.current[start..end]
+                        .iter()
+                        .map(|&byte| {
+                            if byte.is_ascii_graphic() || byte == b' ' {
+                                byte as char
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect();
+                    ui.monospace(ascii);
+                });
+            }
+        });
+    }
+
+    fn editor(&mut self, ui: &mut egui::Ui, gb: &mut Gb) {
+        let Some(offset) = self.selected else {
+            ui.small("Click a byte to edit it, changed bytes are shown in red");
+            return;
+        };
+        let address = self.region.base_address(self.bank) as usize + offset;
+        ui.horizontal(|ui| {
+            ui.monospace(format!("{:02X}:{address:04X}", self.bank));
+            if self.region == MemoryRegion::Rom {
+                ui.label("(read-only)");
+                return;
+            }
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.edit_text)
+                    .char_limit(2)
+                    .desired_width(24.0),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Write").clicked() || submitted {
+                match u8::from_str_radix(self.edit_text.trim(), 16) {
+                    Ok(value) => {
+                        gb.poke(self.region, self.bank, offset as u16, value);
+                        self.error = None;
+                    }
+                    Err(_) => self.error = Some(format!("Invalid byte {}", self.edit_text)),
+                }
+            }
+        });
+    }
+}
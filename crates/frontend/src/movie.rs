@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use eframe::egui;
+
+use sturdygb_core::movie::{MovieMode, MovieSession};
+
+/// The movie being recorded or played back, and the file it's written to when it stops.
+pub struct ActiveMovie {
+    pub session: MovieSession,
+    path: PathBuf,
+    // Movie frame each state slot was saved at, so loading the slot can seek there
+    slot_frames: HashMap<usize, usize>,
+}
+
+impl ActiveMovie {
+    pub fn new(session: MovieSession, path: PathBuf) -> Self {
+        Self {
+            session,
+            path,
+            slot_frames: HashMap::new(),
+        }
+    }
+
+    pub fn state_saved(&mut self, slot: usize) {
+        self.slot_frames.insert(slot, self.session.frame());
+    }
+
+    /// The frame to seek to after loading `slot`, checked before the state is loaded.
+    pub fn slot_frame(&self, slot: usize) -> Result<usize, String> {
+        self.slot_frames
+            .get(&slot)
+            .copied()
+            .ok_or_else(|| format!("Slot {slot} wasn't saved during this movie"))
+    }
+
+    pub fn state_loaded(&mut self, frame: usize) -> Result<(), String> {
+        self.session.seek(frame)?;
+        // States saved further along belong to the branch that was just cut off
+        if !self.session.read_only {
+            self.slot_frames
+                .retain(|_, slot_frame| *slot_frame <= frame);
+        }
+        Ok(())
+    }
+
+    /// Writes the movie out, unless it was only watched read-only.
+    pub fn finish(self) -> Result<(), String> {
+        if self.session.read_only {
+            return Ok(());
+        }
+        std::fs::write(&self.path, self.session.movie().to_bytes())
+            .map_err(|e| format!("Could not write {}: {e}", self.path.display()))
+    }
+
+    /// Frame and lag counters, drawn over the top right corner of the screen.
+    pub fn show_overlay(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let session = &self.session;
+        let text = match session.mode() {
+            MovieMode::Recording => format!("⏺ {}", session.frame()),
+            MovieMode::Playing => format!("▶ {}/{}", session.frame(), session.movie().len()),
+            MovieMode::Finished => format!("⏹ {}", session.frame()),
+        };
+        painter.text(
+            rect.right_top() + egui::vec2(-8.0, 8.0),
+            egui::Align2::RIGHT_TOP,
+            format!("{text}\nLag {}", session.lag_frames()),
+            egui::FontId::monospace(14.0),
+            egui::Color32::WHITE,
+        );
+    }
+}
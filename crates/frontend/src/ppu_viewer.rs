@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use sturdygb_core::cartridge::GbMode;
+use sturdygb_core::gb::Gb;
+use sturdygb_core::ppu_viewer::{RgbaImage, SpriteInfo, Viewport};
+
+use crate::app::Palette;
+
+const TILE_SCALE: f32 = 2.0;
+const MAP_SCALE: f32 = 1.5;
+const SPRITE_SCALE: f32 = 3.0;
+const SWATCH_SIZE: f32 = 18.0;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Tab {
+    Tiles,
+    TileMaps,
+    Sprites,
+    Palettes,
+}
+
+pub struct PpuViewer {
+    pub open: bool,
+    tab: Tab,
+    map: usize,
+    show_viewport: bool,
+    textures: HashMap<String, egui::TextureHandle>,
+}
+
+impl Default for PpuViewer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            tab: Tab::Tiles,
+            map: 0,
+            show_viewport: true,
+            textures: HashMap::new(),
+        }
+    }
+}
+
+fn to_color32(rgba: [u8; 4]) -> egui::Color32 {
+    egui::Color32::from_rgba_unmultiplied(rgba[0], rgba[1], rgba[2], rgba[3])
+}
+
+impl PpuViewer {
+    pub fn show(&mut self, ctx: &egui::Context, gb: &Gb, palette: Palette) {
+        let shades = palette.colors().map(|(r, g, b)| [r, g, b, 0xFF]);
+        let mut open = self.open;
+        egui::Window::new("PPU Viewer")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, Tab::Tiles, "Tiles");
+                    ui.selectable_value(&mut self.tab, Tab::TileMaps, "Tile Maps");
+                    ui.selectable_value(&mut self.tab, Tab::Sprites, "OAM");
+                    ui.selectable_value(&mut self.tab, Tab::Palettes, "Palettes");
+                });
+                ui.separator();
+                egui::ScrollArea::both().show(ui, |ui| match self.tab {
+                    Tab::Tiles => self.tiles(ui, gb, &shades),
+                    Tab::TileMaps => self.tile_maps(ui, gb, &shades),
+                    Tab::Sprites => self.sprites(ui, gb, &shades),
+                    Tab::Palettes => Self::palettes(ui, gb, &shades),
+                });
+            });
+        self.open = open;
+    }
+
+    // Uploads `image`, reusing the texture from the last frame with the same name
+    fn texture(
+        &mut self,
+        ctx: &egui::Context,
+        name: &str,
+        image: &RgbaImage,
+    ) -> egui::TextureHandle {
+        let image =
+            egui::ColorImage::from_rgba_unmultiplied([image.width, image.height], &image.pixels);
+        match self.textures.get_mut(name) {
+            Some(texture) => {
+                texture.set(image, egui::TextureOptions::NEAREST);
+                texture.clone()
+            }
+            None => {
+                let texture = ctx.load_texture(name, image, egui::TextureOptions::NEAREST);
+                self.textures.insert(name.to_string(), texture.clone());
+                texture
+            }
+        }
+    }
+
+    fn tiles(&mut self, ui: &mut egui::Ui, gb: &Gb, shades: &[[u8; 4]; 4]) {
+        let banks = gb.ppu.vram.len() / 0x2000;
+        ui.horizontal_top(|ui| {
+            for bank in 0..banks {
+                let image = gb.tile_data_image(bank, shades);
+                let texture = self.texture(ui.ctx(), &format!("tiles{bank}"), &image);
+                ui.vertical(|ui| {
+                    ui.label(format!("Bank {bank}"));
+                    let size = egui::vec2(image.width as f32, image.height as f32) * TILE_SCALE;
+                    let response = ui.add(egui::Image::new(&texture).fit_to_exact_size(size));
+                    if let Some(position) = response.hover_pos() {
+                        let local = (position - response.rect.min) / (8.0 * TILE_SCALE);
+                        let tile = local.y as usize * 16 + local.x as usize;
+                        response.on_hover_text(format!(
+                            "Tile {tile:03X} at {bank}:{:04X}",
+                            0x8000 + tile * 16
+                        ));
+                    }
+                });
+            }
+        });
+    }
+
+    fn tile_maps(&mut self, ui: &mut egui::Ui, gb: &Gb, shades: &[[u8; 4]; 4]) {
+        let viewport = gb.viewport();
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.map, 0, "9800");
+            ui.selectable_value(&mut self.map, 1, "9C00");
+            ui.checkbox(&mut self.show_viewport, "Show viewport");
+        });
+        let mut used_by = Vec::new();
+        if viewport.bg_map == self.map {
+            used_by.push("background");
+        }
+        if viewport.window_enabled && viewport.window_map == self.map {
+            used_by.push("window");
+        }
+        ui.label(format!(
+            "SCX {:02X} SCY {:02X} WX {:02X} WY {:02X}, used by: {}",
+            viewport.scx,
+            viewport.scy,
+            viewport.wx,
+            viewport.wy,
+            if used_by.is_empty() {
+                "nothing".to_string()
+            } else {
+                used_by.join(", ")
+            }
+        ));
+
+        let image = gb.tile_map_image(self.map, shades);
+        let texture = self.texture(ui.ctx(), "tile_map", &image);
+        let response = ui.add(
+            egui::Image::new(&texture)
+                .fit_to_exact_size(egui::vec2(256.0 * MAP_SCALE, 256.0 * MAP_SCALE)),
+        );
+        if self.show_viewport {
+            self.draw_viewport(ui, response.rect, viewport);
+        }
+    }
+
+    // Outlines the part of the map shown on screen, wrapping around the map edges like
+    // the scroll registers do
+    fn draw_viewport(&self, ui: &egui::Ui, rect: egui::Rect, viewport: Viewport) {
+        let painter = ui.painter_at(rect);
+        let outline = |x: f32, y: f32, width: f32, height: f32, color: egui::Color32| {
+            for dx in [0.0, -256.0] {
+                for dy in [0.0, -256.0] {
+                    let min = rect.min + egui::vec2(x + dx, y + dy) * MAP_SCALE;
+                    painter.rect_stroke(
+                        egui::Rect::from_min_size(min, egui::vec2(width, height) * MAP_SCALE),
+                        0.0,
+                        egui::Stroke::new(2.0, color),
+                        egui::StrokeKind::Inside,
+                    );
+                }
+            }
+        };
+        if viewport.bg_map == self.map {
+            outline(
+                f32::from(viewport.scx),
+                f32::from(viewport.scy),
+                160.0,
+                144.0,
+                egui::Color32::RED,
+            );
+        }
+        // The window always starts at the top left of its map and covers the screen from
+        // (WX - 7, WY) to the bottom right corner
+        let window_x = i32::from(viewport.wx) - 7;
+        let window_y = i32::from(viewport.wy);
+        if viewport.window_enabled
+            && viewport.window_map == self.map
+            && window_x < 160
+            && window_y < 144
+        {
+            outline(
+                0.0,
+                0.0,
+                (160 - window_x.max(0)) as f32,
+                (144 - window_y) as f32,
+                egui::Color32::LIGHT_BLUE,
+            );
+        }
+    }
+
+    fn sprites(&mut self, ui: &mut egui::Ui, gb: &Gb, shades: &[[u8; 4]; 4]) {
+        let sprites = gb.sprites(shades);
+        let is_cgb = gb.gb_mode == GbMode::CgbMode;
+        egui::Grid::new("oam_table")
+            .num_columns(7)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["#", "Sprite", "X", "Y", "Tile", "Palette", "Flags"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for sprite in &sprites {
+                    self.sprite_row(ui, sprite, is_cgb);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn sprite_row(&mut self, ui: &mut egui::Ui, sprite: &SpriteInfo, is_cgb: bool) {
+        let texture = self.texture(ui.ctx(), &format!("sprite{}", sprite.index), &sprite.image);
+        let text = |text: String| {
+            let text = egui::RichText::new(text).monospace();
+            if sprite.visible() {
+                text
+            } else {
+                text.weak()
+            }
+        };
+        ui.label(text(format!("{:02}", sprite.index)));
+        let size = egui::vec2(sprite.image.width as f32, sprite.image.height as f32);
+        ui.add(egui::Image::new(&texture).fit_to_exact_size(size * SPRITE_SCALE));
+        ui.label(text(format!("{:02X}", sprite.x)));
+        ui.label(text(format!("{:02X}", sprite.y)));
+        ui.label(text(format!("{:02X}", sprite.tile)));
+        let palette = if is_cgb {
+            format!(
+                "OBJ{} bank {}",
+                sprite.attributes & 0x07,
+                (sprite.attributes >> 3) & 1
+            )
+        } else {
+            format!("OBP{}", (sprite.attributes >> 4) & 1)
+        };
+        ui.label(text(palette));
+        let flags = [
+            (sprite.flip_x(), "X"),
+            (sprite.flip_y(), "Y"),
+            (sprite.behind_bg(), "BG"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(" ");
+        ui.label(text(flags));
+    }
+
+    fn palettes(ui: &mut egui::Ui, gb: &Gb, shades: &[[u8; 4]; 4]) {
+        if gb.gb_mode == GbMode::CgbMode {
+            let palettes = gb.cgb_palettes();
+            ui.columns(2, |columns| {
+                Self::palette_table(&mut columns[0], "BG", &palettes.background);
+                Self::palette_table(&mut columns[1], "OBJ", &palettes.objects);
+            });
+        } else {
+            let registers = [("BGP", 0xFF47), ("OBP0", 0xFF48), ("OBP1", 0xFF49)];
+            let palettes = registers.map(|(_, address)| {
+                let value = gb.read_byte(address);
+                std::array::from_fn(|color| shades[((value >> (color * 2)) & 0x03) as usize])
+            });
+            for ((name, address), colors) in registers.iter().zip(palettes) {
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("{name:<4} {:02X}", gb.read_byte(*address)));
+                    Self::swatches(ui, &colors);
+                });
+            }
+        }
+    }
+
+    fn palette_table(ui: &mut egui::Ui, name: &str, palettes: &[[[u8; 4]; 4]; 8]) {
+        ui.strong(name);
+        for (index, colors) in palettes.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{index}"));
+                Self::swatches(ui, colors);
+            });
+        }
+    }
+
+    fn swatches(ui: &mut egui::Ui, colors: &[[u8; 4]; 4]) {
+        for color in colors {
+            let (rect, response) =
+                ui.allocate_exact_size(egui::vec2(SWATCH_SIZE, SWATCH_SIZE), egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, to_color32(*color));
+            response.on_hover_text(format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2]));
+        }
+    }
+}
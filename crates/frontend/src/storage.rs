@@ -0,0 +1,53 @@
+use sturdygb_core::storage::SaveStorage;
+
+/// Battery saves in the browser's local storage, as the `[1,2,3]` list eframe wrote them
+/// with before, so older saves keep loading.
+pub struct BrowserStorage {
+    key: String,
+}
+
+impl BrowserStorage {
+    pub fn new(title: &str) -> Self {
+        Self {
+            key: format!("sturdygb_sram_{title}"),
+        }
+    }
+}
+
+fn local_storage() -> Result<eframe::web_sys::Storage, String> {
+    eframe::web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| "Browser storage is not available".to_string())
+}
+
+impl SaveStorage for BrowserStorage {
+    fn load(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let Ok(Some(text)) = local_storage()?.get_item(&self.key) else {
+            return Ok(None);
+        };
+        text.trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(str::trim)
+            .filter(|byte| !byte.is_empty())
+            .map(|byte| {
+                byte.parse::<u8>()
+                    .map_err(|_| format!("Corrupted save {}", self.key))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    fn store(&mut self, data: &[u8]) -> Result<(), String> {
+        let bytes: Vec<String> = data.iter().map(u8::to_string).collect();
+        local_storage()?
+            .set_item(&self.key, &format!("[{}]", bytes.join(",")))
+            .map_err(|_| {
+                format!(
+                    "Failed to write save {}, the browser storage may be full",
+                    self.key
+                )
+            })
+    }
+}